@@ -354,7 +354,7 @@ impl ConfigFile {
                     "name", "extends", "odoo_path", "abstract", "addons_paths", "addons_merge",
                     "python_path", "stdlib", "additional_stubs", "additional_stubs_merge",
                     "refresh_mode", "file_cache", "diag_missing_imports",
-                    "ac_filter_model_names", "auto_refresh_delay",
+                    "ac_filter_model_names", "inlay_hints_variables", "inlay_hints_loop_variables", "auto_refresh_delay",
                     "diagnostic_settings", "diagnostic_filters", "no_typeshed_stubs"
                 ];
                 for key in order {
@@ -507,6 +507,17 @@ where
     }
 }
 
+pub fn serialize_inlay_hints<T, S>(opt: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize + Default,
+    S: Serializer,
+{
+    match opt {
+        Some(val) => val.serialize(serializer),
+        None => (Sourced { value: true, ..Default::default() }).serialize(serializer),
+    }
+}
+
 pub fn serialize_auto_refresh_delay<T, S>(opt: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
 where
     T: Serialize + Default,
@@ -636,6 +647,14 @@ pub struct ConfigEntryRaw {
     #[schemars(with = "Option<bool>")]
     ac_filter_model_names: Option<Sourced<bool>>,
 
+    #[serde(default, serialize_with = "serialize_inlay_hints")]
+    #[schemars(with = "Option<bool>")]
+    inlay_hints_variables: Option<Sourced<bool>>,
+
+    #[serde(default, serialize_with = "serialize_inlay_hints")]
+    #[schemars(with = "Option<bool>")]
+    inlay_hints_loop_variables: Option<Sourced<bool>>,
+
     #[serde(default, serialize_with = "serialize_auto_refresh_delay")]
     #[schemars(with = "Option<u64>")]
     auto_refresh_delay: Option<Sourced<u64>>,
@@ -712,6 +731,8 @@ impl Default for ConfigEntryRaw {
             file_cache: None,
             diag_missing_imports: None,
             ac_filter_model_names: None,
+            inlay_hints_variables: None,
+            inlay_hints_loop_variables: None,
             auto_refresh_delay: None,
             version: None,
             base: None,
@@ -758,6 +779,8 @@ pub struct ConfigEntry {
     pub file_cache: bool,
     pub diag_missing_imports: DiagMissingImportsMode,
     pub ac_filter_model_names: bool,
+    pub inlay_hints_variables: bool,
+    pub inlay_hints_loop_variables: bool,
     pub auto_refresh_delay: u64,
     pub stdlib: String,
     pub no_typeshed_stubs: bool,
@@ -778,6 +801,8 @@ impl Default for ConfigEntry {
             file_cache: true,
             diag_missing_imports: DiagMissingImportsMode::default(),
             ac_filter_model_names: true,
+            inlay_hints_variables: true,
+            inlay_hints_loop_variables: true,
             auto_refresh_delay: 1000,
             stdlib: S!(""),
             no_typeshed_stubs: false,
@@ -919,6 +944,8 @@ fn read_config_from_file<P: AsRef<Path>>(path: P) -> Result<HashMap<String, Conf
         entry.file_cache.as_mut().map(|sourced| sourced.sources.insert(path.sanitize()));
         entry.diag_missing_imports.as_mut().map(|sourced| sourced.sources.insert(path.sanitize()));
         entry.ac_filter_model_names.as_mut().map(|sourced| sourced.sources.insert(path.sanitize()));
+        entry.inlay_hints_variables.as_mut().map(|sourced| sourced.sources.insert(path.sanitize()));
+        entry.inlay_hints_loop_variables.as_mut().map(|sourced| sourced.sources.insert(path.sanitize()));
         entry.auto_refresh_delay.as_mut().map(|sourced| sourced.sources.insert(path.sanitize()));
         entry.version.as_mut().map(|sourced| sourced.sources.insert(path.sanitize()));
         entry.diagnostic_settings.values_mut().for_each(|sourced| {
@@ -996,6 +1023,8 @@ fn apply_merge(child: &ConfigEntryRaw, parent: &ConfigEntryRaw) -> ConfigEntryRa
     let file_cache = child.file_cache.clone().or(parent.file_cache.clone());
     let diag_missing_imports = child.diag_missing_imports.clone().or(parent.diag_missing_imports.clone());
     let ac_filter_model_names = child.ac_filter_model_names.clone().or(parent.ac_filter_model_names.clone());
+    let inlay_hints_variables = child.inlay_hints_variables.clone().or(parent.inlay_hints_variables.clone());
+    let inlay_hints_loop_variables = child.inlay_hints_loop_variables.clone().or(parent.inlay_hints_loop_variables.clone());
     let addons_merge = child.addons_merge.clone().or(parent.addons_merge.clone());
     let additional_stubs_merge = child.additional_stubs_merge.clone().or(parent.additional_stubs_merge.clone());
     let extends = child.extends.clone().or(parent.extends.clone());
@@ -1017,6 +1046,8 @@ fn apply_merge(child: &ConfigEntryRaw, parent: &ConfigEntryRaw) -> ConfigEntryRa
         file_cache,
         diag_missing_imports,
         ac_filter_model_names,
+        inlay_hints_variables,
+        inlay_hints_loop_variables,
         addons_merge,
         additional_stubs_merge,
         extends,
@@ -1338,6 +1369,18 @@ fn merge_all_workspaces(
                 key.clone(),
                 "ac_filter_model_names".to_string(),
             )?;
+            merged_entry.inlay_hints_variables = merge_sourced_options(
+                merged_entry.inlay_hints_variables.clone(),
+                raw_entry.inlay_hints_variables.clone(),
+                key.clone(),
+                "inlay_hints_variables".to_string(),
+            )?;
+            merged_entry.inlay_hints_loop_variables = merge_sourced_options(
+                merged_entry.inlay_hints_loop_variables.clone(),
+                raw_entry.inlay_hints_loop_variables.clone(),
+                key.clone(),
+                "inlay_hints_loop_variables".to_string(),
+            )?;
             merged_entry.auto_refresh_delay = merge_sourced_options(
                 merged_entry.auto_refresh_delay.clone(),
                 raw_entry.auto_refresh_delay.clone(),
@@ -1397,6 +1440,8 @@ fn merge_all_workspaces(
                 file_cache: raw_entry.file_cache.map(|op| op.value).unwrap_or(true),
                 diag_missing_imports: raw_entry.diag_missing_imports.map(|op| op.value).unwrap_or_default(),
                 ac_filter_model_names: raw_entry.ac_filter_model_names.map(|op| op.value).unwrap_or(true),
+                inlay_hints_variables: raw_entry.inlay_hints_variables.map(|op| op.value).unwrap_or(true),
+                inlay_hints_loop_variables: raw_entry.inlay_hints_loop_variables.map(|op| op.value).unwrap_or(true),
                 auto_refresh_delay: clamp_auto_refresh_delay(raw_entry.auto_refresh_delay.map(|op| op.value).unwrap_or(1000)),
                 abstract_: raw_entry.abstract_,
                 diagnostic_settings: raw_entry.diagnostic_settings.into_iter()
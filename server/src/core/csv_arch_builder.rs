@@ -1,10 +1,10 @@
-use std::{cell::RefCell, path::PathBuf, rc::{Rc, Weak}};
+use std::{cell::RefCell, ops::Range, path::PathBuf, rc::{Rc, Weak}};
 
 use csv::StringRecord;
 use lsp_types::Diagnostic;
 use weak_table::PtrWeakHashSet;
 
-use crate::{constants::{BuildStatus, BuildSteps, OYarn}, core::xml_data::{OdooData, OdooDataField, OdooDataRecord}, oyarn, threads::SessionInfo, Sy};
+use crate::{constants::{BuildStatus, BuildSteps, OYarn}, core::{xml_data::{OdooData, OdooDataField, OdooDataRecord, XmlDataQWeb}, xml_id_usages::XmlIdUsages}, oyarn, threads::SessionInfo, Sy};
 
 use super::{symbols::{symbol::Symbol}};
 
@@ -21,6 +21,7 @@ impl CsvArchBuilder {
     pub fn load_csv(&mut self, session: &mut SessionInfo, csv_symbol: Rc<RefCell<Symbol>>, content: &String) -> Vec<Diagnostic> {
         let diagnostics = vec![];
         csv_symbol.borrow_mut().set_build_status(BuildSteps::ARCH, BuildStatus::IN_PROGRESS);
+        XmlIdUsages::remove_usages(session, &csv_symbol);
         let model_name_pb = PathBuf::from(&csv_symbol.borrow().paths()[0]);
         let model_name = Sy!(model_name_pb.file_stem().unwrap().to_str().unwrap().to_string());
         let csv_module = csv_symbol.borrow().find_module();
@@ -30,18 +31,20 @@ impl CsvArchBuilder {
         {
             let mut csv_sym = csv_symbol.borrow_mut();
             let csv = csv_sym.as_csv_file_sym_mut();
+            csv.model_name = model_name.clone();
             let mut rdr = csv::Reader::from_reader(content.as_bytes());
             if rdr.has_headers() {
                 if let Ok(header) = rdr.headers() {
-                    for h in header.iter() {
-                        csv.headers.push(oyarn!("{}", h));
+                    let ranges = CsvArchBuilder::get_value_ranges(content, header);
+                    for (h, range) in header.iter().zip(ranges) {
+                        csv.headers.push((oyarn!("{}", h), range));
                     }
                 }
             }
-            if !csv.headers.is_empty() && csv.headers[0] == "id" {
+            if !csv.headers.is_empty() && csv.headers[0].0 == "id" {
                 for result in rdr.records() {
                     if let Ok(result) = result {
-                        let record = self.extract_record(Rc::downgrade(&csv_symbol), model_name.clone(), &csv.headers, &result);
+                        let record = self.extract_record(content, Rc::downgrade(&csv_symbol), model_name.clone(), &csv.headers, &result);
                         if let Some(record) = record {
                             if let Some(xml_id) = record.xml_id.as_ref() {
                                 let id_split = xml_id.split(".").collect::<Vec<&str>>();
@@ -64,42 +67,60 @@ impl CsvArchBuilder {
                 }
             }
         }
+        for occurrence in XmlIdUsages::collect_csv(content).iter().filter(|o| !o.is_declaration) {
+            XmlIdUsages::add_usage(session, &csv_symbol, &occurrence.xml_id);
+        }
         csv_symbol.borrow_mut().set_build_status(BuildSteps::ARCH, BuildStatus::DONE);
+        session.sync_odoo.add_to_validations(csv_symbol.clone());
         diagnostics
     }
 
-    fn extract_record(&self, file_symbol: Weak<RefCell<Symbol>>, model_name: OYarn, headers: &Vec<OYarn>, record: &StringRecord) -> Option<OdooDataRecord> {
+    /* Return the byte ranges of the values of a csv record in the content. Values can be quoted or padded, so they
+    are searched in the source instead of computing their offsets from their lengths. A value that is not found as is
+    (with escaped quotes) gets an empty range */
+    pub fn get_value_ranges(content: &str, record: &StringRecord) -> Vec<Range<usize>> {
+        let mut ranges = vec![];
+        let mut cursor = record.position().map(|p| p.byte() as usize).unwrap_or(0);
+        for value in record.iter() {
+            match content.get(cursor..).and_then(|rest| rest.find(value)).map(|i| i + cursor) {
+                Some(start) => {
+                    cursor = start + value.len();
+                    ranges.push(start..cursor);
+                },
+                None => ranges.push(cursor..cursor),
+            }
+        }
+        ranges
+    }
+
+    fn extract_record(&self, content: &str, file_symbol: Weak<RefCell<Symbol>>, model_name: OYarn, headers: &Vec<(OYarn, Range<usize>)>, record: &StringRecord) -> Option<OdooDataRecord> {
         if record.position().is_none() {
             return None;
         }
         let mut fields = vec![];
-        let mut start = record.position().unwrap().byte();
-        let mut idx = 0;
         let mut last_end = 0;
         let mut xml_id = None;
-        for field in record.iter(){
-            let end = start + field.len() as u64;
-            let field_name = headers.get(idx).unwrap().clone();
+        let ranges = CsvArchBuilder::get_value_ranges(content, record);
+        for (idx, (field, range)) in record.iter().zip(ranges).enumerate() {
+            let mut field_name = headers.get(idx)?.0.clone();
             if field_name == "id" {
                 xml_id = Some(oyarn!("{}", field));
+            } else if let Some(pos) = field_name.find([':', '/']) {
+                //model_id:id, groups_id/id, ...
+                field_name = oyarn!("{}", &field_name[..pos]);
             }
             fields.push(
                 OdooDataField {
                     name: field_name,
-                    range: core::ops::Range {
-                        start: start as usize,
-                        end: end as usize,
-                    },
+                    range: range.clone(),
                     text: Some(field.to_string()),
-                    text_range: Some(core::ops::Range {
-                        start: start as usize,
-                        end: end as usize,
-                    }),
+                    text_range: Some(range.clone()),
+                    typ: None,
+                    ref_id: None,
+                    eval: None,
                 }
             );
-            start = end + 1;
-            last_end = end;
-            idx +=1 ;
+            last_end = range.end;
         }
         Some(OdooDataRecord {
             file_symbol: file_symbol,
@@ -109,6 +130,9 @@ impl CsvArchBuilder {
                 end: 1 as usize
             }),
             xml_id: xml_id,
+            qweb: XmlDataQWeb::default(),
+            arch: vec![],
+            expression_xml_ids: vec![],
             range: core::ops::Range{
                 start: record.position().unwrap().byte() as usize,
                 end: last_end as usize
@@ -428,4 +428,80 @@ OLS05056, DiagnosticSetting::Error, "Model '{0}' not found",
  * Field not found in model
  */
 OLS05057, DiagnosticSetting::Error, "Field '{0}' not found in model '{1}'",
+/**
+ * A t-call is targeting a template that does not exist in the module or its dependencies
+ */
+OLS05058, DiagnosticSetting::Error, "Template with id '{0}' does not exist",
+/**
+ * The inherit_id or t-inherit value is not a view or template declared in the module or its dependencies
+ */
+OLS05059, DiagnosticSetting::Error, "Inherited view with id '{0}' does not exist",
+/**
+ * t-inherit-mode only accepts 'primary' and 'extension'
+ */
+OLS05060, DiagnosticSetting::Error, "Invalid t-inherit-mode '{0}'. Expected 'primary' or 'extension'",
+/**
+ * A button of type 'object' in a view is calling a method that is not defined on the model of the view
+ */
+OLS05061, DiagnosticSetting::Error, "Method '{0}' not found in model '{1}'",
+/**
+ * The position of an inheritance spec must be one of 'before', 'after', 'inside', 'replace', 'attributes' or 'move'
+ */
+OLS05062, DiagnosticSetting::Error, "Invalid position '{0}'",
+/**
+ * The expr of an xpath can't be parsed
+ */
+OLS05063, DiagnosticSetting::Error, "Invalid xpath expression '{0}': {1}",
+/**
+ * A field used as a locator in an inherited view is not in the arch of the parent view, or in the views it inherits
+ */
+OLS05064, DiagnosticSetting::Error, "Field '{0}' not found in the arch of the inherited view '{1}'",
+/**
+ * The action of a menuitem must be a record of one of the ir.actions.* models
+ */
+OLS05065, DiagnosticSetting::Error, "'{0}' is not an action",
+/**
+ * Following the parents of a menuitem leads back to the menuitem itself
+ */
+OLS05066, DiagnosticSetting::Error, "Menuitem '{0}' is its own ancestor",
+/**
+ * A delete node is targeting an xml_id that is not declared in the module or its dependencies
+ */
+OLS05067, DiagnosticSetting::Error, "Record with id '{0}' does not exist",
+/**
+ * The record targeted by a delete node, or referenced by a Many2one field, does not belong to the expected model
+ */
+OLS05068, DiagnosticSetting::Error, "Record '{0}' is of model '{1}', not '{2}'",
+/**
+ * The content of a Selection field is not one of the keys of its selection
+ */
+OLS05069, DiagnosticSetting::Error, "'{0}' is not a valid value for selection field '{1}'",
+/**
+ * One2many and Many2many fields are written with a list of Command (or (command, id, values) tuples), not with ids
+ */
+OLS05070, DiagnosticSetting::Error, "Value of x2many field '{0}' should be a list of Command or (command, id, values) tuples",
+/**
+ * A record creating a new xml_id does not set some required fields that have no default, compute or related
+ * in the model, its extensions or its _inherits parents
+ */
+OLS05071, DiagnosticSetting::Warning, "Missing required fields in record: {0}",
+/**
+ * The value of an attribute containing a python expression (eval, context, domain, invisible, readonly, required,
+ * column_invisible or options) can't be parsed
+ */
+OLS05072, DiagnosticSetting::Error, "Invalid python expression in attribute '{0}': {1}",
+/**
+ * A name used in an expression of a view arch is neither a field of the model of the view (or of the sub-view), nor
+ * a name provided by the web client (context, uid, parent, active_id, current_date, context_today, ...)
+ */
+OLS05073, DiagnosticSetting::Warning, "'{0}' is not a field of '{1}' or a name of the evaluation context",
+/**
+ * The eval attributes of data files are evaluated with ref, obj, time, datetime, timedelta, relativedelta, Command, ...
+ * and the python builtins. Other names are not defined
+ */
+OLS05074, DiagnosticSetting::Warning, "Name '{0}' is not defined in the evaluation context of eval attributes",
+/**
+ * The parent of a menuitem exists, but is not a menuitem
+ */
+OLS05075, DiagnosticSetting::Error, "'{0}' is not a menuitem",
 }
@@ -120,7 +120,8 @@ pub enum ContextValue {
     MODULE(Weak<RefCell<Symbol>>),
     SYMBOL(Weak<RefCell<Symbol>>),
     ARGUMENTS(Arguments),
-    RANGE(TextRange)
+    RANGE(TextRange),
+    LIST(Vec<String>)
 }
 
 impl PartialEq for ContextValue {
@@ -132,6 +133,7 @@ impl PartialEq for ContextValue {
             (ContextValue::STRING(me), ContextValue::STRING(them)) => me == them,
             (ContextValue::ARGUMENTS(me), ContextValue::ARGUMENTS(them)) => me == them,
             (ContextValue::RANGE(me), ContextValue::RANGE(them)) => me == them,
+            (ContextValue::LIST(me), ContextValue::LIST(them)) => me == them,
             _ => false,
         }
     }
@@ -179,6 +181,13 @@ impl ContextValue {
             _ => panic!("Not an arguments")
         }
     }
+
+    pub fn as_list(&self) -> Option<Vec<String>> {
+        match self {
+            ContextValue::LIST(l) => Some(l.clone()),
+            _ => None
+        }
+    }
 }
 
 /** A context can contains: (non-exhaustive)
@@ -1455,7 +1464,7 @@ impl Evaluation {
         filtered_diagnostics
     }
 
-    fn validate_domain(session: &mut SessionInfo, on_object: Weak<RefCell<Symbol>>, from_module: Option<Rc<RefCell<Symbol>>>, value: &Expr) -> Vec<Diagnostic> {
+    pub fn validate_domain(session: &mut SessionInfo, on_object: Weak<RefCell<Symbol>>, from_module: Option<Rc<RefCell<Symbol>>>, value: &Expr) -> Vec<Diagnostic> {
         let mut diagnostics = vec![];
         if !matches!(value, Expr::List(_)) {
             return diagnostics;
@@ -20,7 +20,7 @@ use std::rc::Rc;
 use std::cell::RefCell;
 use crate::S;
 use crate::constants::*;
-use ruff_text_size::{Ranged, TextRange};
+use ruff_text_size::{Ranged, TextRange, TextSize};
 
 use super::odoo::SyncOdoo;
 
@@ -79,6 +79,7 @@ pub struct FileInfo {
     need_push: bool,
     pub file_info_ast: Rc<RefCell<FileInfoAst>>,
     diagnostics: HashMap<BuildSteps, Vec<Diagnostic>>,
+    published_diagnostics: Vec<Diagnostic>, //last diagnostics sent or computed for the client, with noqa and filters applied
     pub noqas_blocs: HashMap<u32, NoqaInfo>,
     noqas_lines: HashMap<u32, NoqaInfo>,
     diagnostic_filters: Vec<DiagnosticFilter>,
@@ -99,6 +100,7 @@ impl FileInfo {
                 ast_type: AstType::Python,
             })),
             diagnostics: HashMap::new(),
+            published_diagnostics: Vec::new(),
             noqas_blocs: HashMap::new(),
             noqas_lines: HashMap::new(),
             diagnostic_filters: Vec::new(),
@@ -215,6 +217,24 @@ impl FileInfo {
         self._build_ast(session, session.sync_odoo.get_file_mgr().borrow().is_in_workspace(&self.uri));
     }
 
+    /* Call the callback after having loaded the ast of the file if needed. An ast loaded for the callback is
+    released afterward, unless the file is opened or the file cache is enabled */
+    pub fn with_ast<T>(file_info: &Rc<RefCell<FileInfo>>, session: &mut SessionInfo, callback: impl FnOnce(&mut SessionInfo) -> T) -> T {
+        let release_ast = file_info.borrow().file_info_ast.borrow().indexed_module.is_none();
+        if release_ast {
+            file_info.borrow_mut().prepare_ast(session);
+        }
+        let result = callback(session);
+        if release_ast && !session.sync_odoo.config.file_cache && !file_info.borrow().opened {
+            let file_info_ast = file_info.borrow().file_info_ast.clone();
+            let mut file_info_ast = file_info_ast.borrow_mut();
+            file_info_ast.indexed_module = None;
+            file_info_ast.text_rope = None;
+            file_info_ast.text_hash = 0;
+        }
+        result
+    }
+
     pub fn extract_tokens(&mut self, parsed_module: &Parsed<ModModule>, source: &String) {
         let mut is_first_expr: bool = true;
         let mut noqa_to_add = None;
@@ -223,7 +243,7 @@ impl FileInfo {
             match token.kind() {
                 TokenKind::Comment => {
                     let text = &source[token.range()];
-                    if text.starts_with("#noqa") || text.starts_with("# noqa") || text.starts_with("# odools: noqa") {
+                    if FileInfo::is_noqa_comment(text) {
                         let after_noqa = text.split("noqa").skip(1).next();
                         if let Some(after_noqa) = after_noqa {
                             let mut codes = vec![];
@@ -271,6 +291,16 @@ impl FileInfo {
         }
     }
 
+    pub fn is_noqa_comment(text: &str) -> bool {
+        text.starts_with("#noqa") || text.starts_with("# noqa") || text.starts_with("# odools: noqa")
+    }
+
+    /* noqa bloc of a class or function. The bloc is stored at the position of the 'class' or 'def' keyword,
+    that can be after the decorators and the 'async' keyword */
+    pub fn get_noqa_bloc(&self, header_range: TextRange) -> Option<&NoqaInfo> {
+        self.noqas_blocs.iter().find(|(index, _)| header_range.contains(TextSize::new(**index))).map(|(_, noqa)| noqa)
+    }
+
     fn add_noqa_bloc(&mut self, index: u32, noqa_to_add: NoqaInfo) {
         if let Some(noqa_bloc) = self.noqas_blocs.remove(&index) {
             self.noqas_blocs.insert(index, combine_noqa_info(&vec![noqa_bloc, noqa_to_add]));
@@ -373,15 +403,46 @@ impl FileInfo {
                 }
                 all_diagnostics.push(updated);
             }
-            session.send_notification::<PublishDiagnosticsParams>(PublishDiagnostics::METHOD, PublishDiagnosticsParams{
-                uri: FileMgr::pathname2uri(&self.uri),
-                diagnostics: all_diagnostics,
-                version: Some(self.version),
-            });
+            self.published_diagnostics = all_diagnostics.clone();
+            if session.sync_odoo.use_pull_diagnostics() {
+                //the client will ask for them
+                session.sync_odoo.need_diagnostic_refresh = true;
+            } else {
+                session.send_notification::<PublishDiagnosticsParams>(PublishDiagnostics::METHOD, PublishDiagnosticsParams{
+                    uri: FileMgr::pathname2uri(&self.uri),
+                    diagnostics: all_diagnostics,
+                    version: Some(self.version),
+                });
+            }
             self.need_push = false;
         }
     }
 
+    /* Remove from the client the diagnostics that were pushed to it */
+    pub fn clear_pushed_diagnostics(&self, session: &mut SessionInfo) {
+        if self.published_diagnostics.is_empty() {
+            return;
+        }
+        session.send_notification::<PublishDiagnosticsParams>(PublishDiagnostics::METHOD, PublishDiagnosticsParams{
+            uri: FileMgr::pathname2uri(&self.uri),
+            diagnostics: vec![],
+            version: Some(self.version),
+        });
+    }
+
+    /* Diagnostics for a pull request of the client, with a result id that changes only if the diagnostics change */
+    pub fn pull_diagnostics(&mut self, session: &mut SessionInfo) -> (String, Vec<Diagnostic>) {
+        if self.need_push && self.file_info_ast.borrow().text_rope.is_some() {
+            //the client is already getting them, no need to ask for a refresh
+            let need_refresh = session.sync_odoo.need_diagnostic_refresh;
+            self.publish_diagnostics(session);
+            session.sync_odoo.need_diagnostic_refresh = need_refresh;
+        }
+        let mut hasher = DefaultHasher::new();
+        serde_json::to_string(&self.published_diagnostics).unwrap_or_default().hash(&mut hasher);
+        (format!("{:x}", hasher.finish()), self.published_diagnostics.clone())
+    }
+
     pub fn offset_to_position_with_rope(rope: &Rope, offset: usize) -> Position {
         let char = rope.try_byte_to_char(offset).expect("unable to get char from bytes");
         let line = rope.try_char_to_line(char).ok().expect("unable to get line from char");
@@ -18,4 +18,6 @@ pub mod symbols;
 pub mod xml_arch_builder;
 pub mod xml_arch_builder_rng_validation;
 pub mod xml_data;
-pub mod xml_validation;
\ No newline at end of file
+pub mod xml_id_usages;
+pub mod xml_validation;
+pub mod xpath;
\ No newline at end of file
@@ -5,25 +5,33 @@ use crate::core::xml_data::OdooData;
 use crate::core::xml_validation::XmlValidator;
 use crate::features::document_symbols::DocumentSymbolFeature;
 use crate::features::references::ReferenceFeature;
-use crate::threads::SessionInfo;
+use crate::features::call_hierarchy::CallHierarchyFeature;
+use crate::features::code_actions::CodeActionFeature;
+use crate::features::inlay_hints::InlayHintFeature;
+use crate::features::rename::RenameFeature;
+use crate::features::semantic_tokens::SemanticTokensFeature;
+use crate::features::type_hierarchy::TypeHierarchyFeature;
+use crate::features::workspace_symbols::WorkspaceSymbolFeature;
+use crate::threads::{ProgressInfo, SessionInfo, REBUILD_PROGRESS_MIN_QUEUE_SIZE};
 use crate::features::completion::CompletionFeature;
 use crate::features::definition::DefinitionFeature;
 use crate::features::hover::HoverFeature;
+use crate::features::implementation::ImplementationFeature;
 use std::collections::HashMap;
 use std::cell::RefCell;
 use std::ffi::OsStr;
 use std::rc::{Rc, Weak};
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
-use lsp_server::ResponseError;
+use lsp_server::{RequestId, ResponseError};
 use lsp_types::*;
-use request::{RegisterCapability, Request, WorkspaceConfiguration};
+use request::{RegisterCapability, Request, WorkspaceConfiguration, WorkspaceDiagnosticRefresh};
 use serde_json::Value;
 use tracing::{error, warn, info, trace};
 
 use std::collections::HashSet;
-use weak_table::PtrWeakHashSet;
+use weak_table::{PtrWeakHashSet, PtrWeakKeyHashMap};
 use std::process::Command;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -32,7 +40,7 @@ use regex::Regex;
 use crate::{constants::*, oyarn, Sy};
 use super::config::{self, default_profile_name, get_configuration, ConfigEntry, ConfigFile, RefreshMode};
 use super::entry_point::{EntryPoint, EntryPointMgr};
-use super::file_mgr::FileMgr;
+use super::file_mgr::{FileInfo, FileMgr};
 use super::import_resolver::ImportCache;
 use super::symbols::symbol::Symbol;
 use crate::core::model::Model;
@@ -76,9 +84,12 @@ pub struct SyncOdoo {
     file_mgr: Rc<RefCell<FileMgr>>,
     pub modules: HashMap<OYarn, Weak<RefCell<Symbol>>>,
     pub models: HashMap<OYarn, Rc<RefCell<Model>>>,
+    pub xml_ids_used_by_files: PtrWeakKeyHashMap<Weak<RefCell<Symbol>>, Vec<(OYarn, OYarn)>>, //(module, xml_id) used by each file. Reverse of ModuleSymbol::xml_id_usages
     pub interrupt_rebuild: Arc<AtomicBool>,
     pub terminate_rebuild: Arc<AtomicBool>,
     pub watched_file_updates: Arc<AtomicU32>,
+    pub cancelled_requests: Arc<Mutex<HashSet<RequestId>>>, //requests cancelled by the client while in progress, filled by Server
+    pub current_request: Option<RequestId>,
     rebuild_arch: PtrWeakHashSet<Weak<RefCell<Symbol>>>,
     rebuild_arch_eval: PtrWeakHashSet<Weak<RefCell<Symbol>>>,
     rebuild_validation: PtrWeakHashSet<Weak<RefCell<Symbol>>>,
@@ -89,6 +100,9 @@ pub struct SyncOdoo {
     pub import_cache: Option<ImportCache>,
     pub capabilities: lsp_types::ClientCapabilities,
     pub opened_files: Vec<String>,
+    pub need_diagnostic_refresh: bool, //diagnostics changed while the client pulls them
+    pub pull_diagnostics_requested: bool, //the client sent a first pull request, diagnostics are not pushed anymore
+    pub progress: Option<ProgressInfo>, //work done progress displayed by the client during builds
 }
 
 unsafe impl Send for SyncOdoo {}
@@ -115,9 +129,12 @@ impl SyncOdoo {
             stdlib_dir: SyncOdoo::default_stdlib(),
             modules: HashMap::new(),
             models: HashMap::new(),
+            xml_ids_used_by_files: PtrWeakKeyHashMap::new(),
             interrupt_rebuild: Arc::new(AtomicBool::new(false)),
             terminate_rebuild: Arc::new(AtomicBool::new(false)),
             watched_file_updates: Arc::new(AtomicU32::new(0)),
+            cancelled_requests: Arc::new(Mutex::new(HashSet::new())),
+            current_request: None,
             rebuild_arch: PtrWeakHashSet::new(),
             rebuild_arch_eval: PtrWeakHashSet::new(),
             rebuild_validation: PtrWeakHashSet::new(),
@@ -128,6 +145,9 @@ impl SyncOdoo {
             import_cache: None,
             capabilities: lsp_types::ClientCapabilities::default(),
             opened_files: vec![],
+            need_diagnostic_refresh: false,
+            pull_diagnostics_requested: false,
+            progress: None,
         };
         sync_odoo
     }
@@ -264,10 +284,15 @@ impl SyncOdoo {
                 error!("{}", stderr);
             }
         }
+        let progress_started = session.start_progress("Odoo: indexing");
+        session.report_progress_stage("Loading stdlib");
         if SyncOdoo::load_builtins(session) {
             session.sync_odoo.state_init = InitState::PYTHON_READY;
             SyncOdoo::build_database(session);
         }
+        if progress_started {
+            session.end_progress();
+        }
         session.send_notification("$Odoo/loadingStatusUpdate", "stop");
         session.log_message(MessageType::INFO, format!("End of initialization. Time taken: {} ms", start_time.elapsed().as_millis()));
     }
@@ -302,6 +327,7 @@ impl SyncOdoo {
 
     pub fn build_database(session: &mut SessionInfo) {
         session.log_message(MessageType::INFO, String::from("Building Database"));
+        session.report_progress_stage("Loading Odoo base");
         let result = SyncOdoo::build_base(session);
         if result {
             SyncOdoo::build_modules(session);
@@ -477,6 +503,7 @@ impl SyncOdoo {
                 }
             }
         }
+        session.report_progress_stage("Loading modules");
         if !SyncOdoo::process_rebuilds(session){
             return;
         }
@@ -602,6 +629,30 @@ impl SyncOdoo {
     }
 
     pub fn process_rebuilds(session: &mut SessionInfo) -> bool {
+        //big rebuilds outside of the initialization (reload of a module, git checkout, ...) get their own progress
+        let progress_started = session.sync_odoo.get_rebuild_queue_size() >= REBUILD_PROGRESS_MIN_QUEUE_SIZE && session.start_progress("Odoo: rebuilding");
+        let result = SyncOdoo::_process_rebuilds(session);
+        if progress_started {
+            session.end_progress();
+        }
+        result
+    }
+
+    fn report_rebuild_progress(session: &mut SessionInfo, symbol: &Rc<RefCell<Symbol>>) {
+        if !session.is_progress_report_due() {
+            return;
+        }
+        let module_name = symbol.borrow().find_module().map(|module| module.borrow().name().to_string());
+        let queues = format!("ARCH: {}, ARCH_EVAL: {}, VALIDATION: {}",
+            session.sync_odoo.rebuild_arch.len(), session.sync_odoo.rebuild_arch_eval.len(), session.sync_odoo.rebuild_validation.len());
+        let message = match module_name {
+            Some(module_name) => format!("{} - {}", module_name, queues),
+            None => queues,
+        };
+        session.report_progress(message, false);
+    }
+
+    fn _process_rebuilds(session: &mut SessionInfo) -> bool {
         session.sync_odoo.interrupt_rebuild.store(false, Ordering::SeqCst);
         SyncOdoo::add_from_self_reload(session);
         session.sync_odoo.import_cache = Some(ImportCache{ modules: HashMap::new(), main_modules: HashMap::new() });
@@ -619,6 +670,7 @@ impl SyncOdoo {
             }
             let sym = session.sync_odoo.pop_item(BuildSteps::ARCH);
             if let Some(sym_rc) = sym {
+                SyncOdoo::report_rebuild_progress(session, &sym_rc);
                 let (tree, entry) = sym_rc.borrow().get_tree_and_entry();
                 if already_arch_rebuilt.contains(&tree) {
                     info!("Already arch rebuilt, skipping");
@@ -631,6 +683,7 @@ impl SyncOdoo {
             }
             let sym = session.sync_odoo.pop_item(BuildSteps::ARCH_EVAL);
             if let Some(sym_rc) = sym {
+                SyncOdoo::report_rebuild_progress(session, &sym_rc);
                 let (tree, entry) = sym_rc.borrow().get_tree_and_entry();
                 if already_arch_eval_rebuilt.contains(&tree) {
                     info!("Already arch eval rebuilt, skipping");
@@ -643,6 +696,7 @@ impl SyncOdoo {
             }
             let sym = session.sync_odoo.pop_item(BuildSteps::VALIDATION);
             if let Some(sym_rc) = sym {
+                SyncOdoo::report_rebuild_progress(session, &sym_rc);
                 let (tree, entry) = sym_rc.borrow_mut().get_tree_and_entry();
                 if already_validation_rebuilt.contains(&tree) {
                     info!("Already validation rebuilt, skipping");
@@ -658,7 +712,7 @@ impl SyncOdoo {
                 }
                 let typ = sym_rc.borrow().typ();
                 match typ {
-                    SymType::XML_FILE => {
+                    SymType::XML_FILE | SymType::CSV_FILE => {
                         let mut validator = XmlValidator::new(entry.as_ref().unwrap(), sym_rc);
                         validator.validate(session);
                     },
@@ -990,12 +1044,31 @@ impl SyncOdoo {
         self.capabilities = capabilities.clone();
     }
 
+    /* If the client supports the pull model and started to pull diagnostics, they are not pushed anymore */
+    pub fn use_pull_diagnostics(&self) -> bool {
+        self.pull_diagnostics_requested && self.capabilities.text_document.as_ref().and_then(|text_document| text_document.diagnostic.as_ref()).is_some()
+    }
+
+    /* Switch to the pull model on the first pull request. The client keeps pushed and pulled diagnostics apart,
+    so the pushed ones are cleared */
+    pub fn start_pull_diagnostics(session: &mut SessionInfo) {
+        if session.sync_odoo.pull_diagnostics_requested {
+            return;
+        }
+        session.sync_odoo.pull_diagnostics_requested = true;
+        let file_infos = session.sync_odoo.get_file_mgr().borrow().files.values().cloned().collect::<Vec<_>>();
+        for file_info in file_infos.iter() {
+            file_info.borrow().clear_pushed_diagnostics(session);
+        }
+    }
+
     /**
      * search for an xml_id in the already registered xml files.
      * */
-    pub fn get_xml_ids(session: &mut SessionInfo, from_file: &Rc<RefCell<Symbol>>, xml_id: &str, range: &std::ops::Range<usize>, diagnostics: &mut Vec<Diagnostic>) -> Vec<OdooData> {
+    /* Return None if xml_id is not a valid xml_id. The error is then pushed in diagnostics */
+    pub fn get_xml_ids(session: &mut SessionInfo, from_file: &Rc<RefCell<Symbol>>, xml_id: &str, range: &std::ops::Range<usize>, diagnostics: &mut Vec<Diagnostic>) -> Option<Vec<OdooData>> {
         if !from_file.borrow().get_entry().unwrap().borrow().is_main() {
-            return vec![];
+            return Some(vec![]);
         }
         let id_split = xml_id.split(".").collect::<Vec<&str>>();
         let mut module = None;
@@ -1017,15 +1090,15 @@ impl SyncOdoo {
                     ..diagnostic.clone()
                 });
             }
-            return vec![];
+            return None;
         }
         if module.is_none() {
             warn!("Module not found for id: {}", xml_id);
-            return vec![];
+            return Some(vec![]);
         }
         let module = module.unwrap();
         let module = module.borrow();
-        module.as_module_package().get_xml_id(&oyarn!("{}", id_split.last().unwrap()))
+        Some(module.as_module_package().get_xml_id(&oyarn!("{}", id_split.last().unwrap())))
     }
 
 }
@@ -1260,6 +1333,268 @@ impl Odoo {
         Ok(None)
     }
 
+    pub fn handle_implementation(session: &mut SessionInfo, params: GotoDefinitionParams) -> Result<Option<GotoDefinitionResponse>, ResponseError> {
+        if session.sync_odoo.state_init == InitState::NOT_READY {
+            return Ok(None);
+        }
+        session.log_message(MessageType::INFO, format!("Implementation requested on {} at {} - {}",
+            params.text_document_position_params.text_document.uri.to_string(),
+            params.text_document_position_params.position.line,
+            params.text_document_position_params.position.character));
+        let uri = params.text_document_position_params.text_document.uri.to_string();
+        let path = FileMgr::uri2pathname(uri.as_str());
+        if uri.ends_with(".py") || uri.ends_with(".pyi") {
+            if let Some(file_symbol) = SyncOdoo::get_symbol_of_opened_file(session, &PathBuf::from(path.clone())) {
+                let file_info = session.sync_odoo.get_file_mgr().borrow().get_file_info(&path);
+                if let Some(file_info) = file_info {
+                    if file_info.borrow().file_info_ast.borrow().indexed_module.is_none() {
+                        file_info.borrow_mut().prepare_ast(session);
+                    }
+                    if file_info.borrow().file_info_ast.borrow().indexed_module.is_some() {
+                        return Ok(ImplementationFeature::get_implementations(session, &file_symbol, &file_info, params.text_document_position_params.position.line, params.text_document_position_params.position.character));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn handle_code_action(session: &mut SessionInfo, params: CodeActionParams) -> Result<Option<CodeActionResponse>, ResponseError> {
+        if session.sync_odoo.state_init == InitState::NOT_READY {
+            return Ok(None);
+        }
+        session.log_message(MessageType::INFO, format!("Code actions requested on {} at {} - {}",
+            params.text_document.uri.to_string(),
+            params.range.start.line,
+            params.range.start.character));
+        let uri = params.text_document.uri.to_string();
+        let path = FileMgr::uri2pathname(uri.as_str());
+        if uri.ends_with(".py") || uri.ends_with(".pyi") || uri.ends_with(".xml") || uri.ends_with(".csv") {
+            if let Some(file_symbol) = SyncOdoo::get_symbol_of_opened_file(session, &PathBuf::from(path.clone())) {
+                let file_info = session.sync_odoo.get_file_mgr().borrow().get_file_info(&path);
+                if let Some(file_info) = file_info {
+                    if file_info.borrow().file_info_ast.borrow().indexed_module.is_none() && (uri.ends_with(".py") || uri.ends_with(".pyi")) {
+                        file_info.borrow_mut().prepare_ast(session);
+                    }
+                    return Ok(CodeActionFeature::get_code_actions(session, &file_symbol, &file_info, &path, &params));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn handle_document_diagnostic(session: &mut SessionInfo, params: DocumentDiagnosticParams) -> Result<Option<DocumentDiagnosticReportResult>, ResponseError> {
+        SyncOdoo::start_pull_diagnostics(session);
+        let path = FileMgr::uri2pathname(params.text_document.uri.as_str());
+        let file_info = session.sync_odoo.get_file_mgr().borrow().get_file_info(&path);
+        let (result_id, items) = match file_info {
+            Some(file_info) => file_info.borrow_mut().pull_diagnostics(session),
+            None => (S!(""), vec![]),
+        };
+        if params.previous_result_id.as_ref() == Some(&result_id) {
+            return Ok(Some(DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+                related_documents: None,
+                unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport { result_id },
+            }))));
+        }
+        Ok(Some(DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+            related_documents: None,
+            full_document_diagnostic_report: FullDocumentDiagnosticReport { result_id: Some(result_id), items },
+        }))))
+    }
+
+    /* Diagnostics of the files of the workspace that are not opened. Opened files are pulled with textDocument/diagnostic.
+    Files without diagnostics are omitted, unless the client got some for them before */
+    pub fn handle_workspace_diagnostic(session: &mut SessionInfo, params: WorkspaceDiagnosticParams) -> Result<Option<WorkspaceDiagnosticReportResult>, ResponseError> {
+        SyncOdoo::start_pull_diagnostics(session);
+        let file_infos: Vec<Rc<RefCell<FileInfo>>> = {
+            let file_mgr = session.sync_odoo.get_file_mgr();
+            let file_mgr = file_mgr.borrow();
+            file_mgr.files.values().filter(|file_info| {
+                let file_info = file_info.borrow();
+                !file_info.opened && file_mgr.is_in_workspace(&file_info.uri)
+            }).cloned().collect()
+        };
+        let mut items = vec![];
+        for file_info in file_infos.iter() {
+            let (result_id, diagnostics) = file_info.borrow_mut().pull_diagnostics(session);
+            let uri = FileMgr::pathname2uri(&file_info.borrow().uri);
+            let previous = params.previous_result_ids.iter().find(|previous| previous.uri == uri);
+            if diagnostics.is_empty() && previous.is_none() {
+                continue;
+            }
+            if previous.is_some_and(|previous| previous.value == result_id) {
+                items.push(WorkspaceDocumentDiagnosticReport::Unchanged(WorkspaceUnchangedDocumentDiagnosticReport {
+                    uri,
+                    version: None,
+                    unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport { result_id },
+                }));
+            } else {
+                items.push(WorkspaceDocumentDiagnosticReport::Full(WorkspaceFullDocumentDiagnosticReport {
+                    uri,
+                    version: None,
+                    full_document_diagnostic_report: FullDocumentDiagnosticReport { result_id: Some(result_id), items: diagnostics },
+                }));
+            }
+        }
+        Ok(Some(WorkspaceDiagnosticReportResult::Report(WorkspaceDiagnosticReport { items })))
+    }
+
+    /* Ask the client to pull the diagnostics again if they changed since the last request */
+    pub fn refresh_diagnostics(session: &mut SessionInfo) {
+        if !session.sync_odoo.need_diagnostic_refresh {
+            return;
+        }
+        session.sync_odoo.need_diagnostic_refresh = false;
+        let refresh_support = session.sync_odoo.capabilities.workspace.as_ref()
+            .and_then(|workspace| workspace.diagnostic.as_ref())
+            .and_then(|diagnostic| diagnostic.refresh_support)
+            .unwrap_or(false);
+        if refresh_support {
+            if let Err(e) = session.send_request::<(), ()>(WorkspaceDiagnosticRefresh::METHOD, ()) {
+                warn!("Unable to refresh diagnostics: {:?}", e);
+            }
+        }
+    }
+
+    pub fn handle_inlay_hints(session: &mut SessionInfo, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>, ResponseError> {
+        if session.sync_odoo.state_init == InitState::NOT_READY {
+            return Ok(None);
+        }
+        let uri = params.text_document.uri.to_string();
+        let path = FileMgr::uri2pathname(uri.as_str());
+        if uri.ends_with(".py") || uri.ends_with(".pyi") {
+            if let Some(file_symbol) = SyncOdoo::get_symbol_of_opened_file(session, &PathBuf::from(path.clone())) {
+                let file_info = session.sync_odoo.get_file_mgr().borrow().get_file_info(&path);
+                if let Some(file_info) = file_info {
+                    if file_info.borrow().file_info_ast.borrow().indexed_module.is_none() {
+                        file_info.borrow_mut().prepare_ast(session);
+                    }
+                    if file_info.borrow().file_info_ast.borrow().indexed_module.is_some() {
+                        return Ok(InlayHintFeature::get_inlay_hints(session, &file_symbol, &file_info, &params.range));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn handle_semantic_tokens_full(session: &mut SessionInfo, params: SemanticTokensParams) -> Result<Option<SemanticTokensResult>, ResponseError> {
+        let data = Odoo::get_semantic_tokens(session, &params.text_document.uri, None);
+        Ok(data.map(|data| SemanticTokensResult::Tokens(SemanticTokens { result_id: None, data })))
+    }
+
+    pub fn handle_semantic_tokens_range(session: &mut SessionInfo, params: SemanticTokensRangeParams) -> Result<Option<SemanticTokensRangeResult>, ResponseError> {
+        let data = Odoo::get_semantic_tokens(session, &params.text_document.uri, Some(params.range));
+        Ok(data.map(|data| SemanticTokensRangeResult::Tokens(SemanticTokens { result_id: None, data })))
+    }
+
+    fn get_semantic_tokens(session: &mut SessionInfo, uri: &Uri, range: Option<Range>) -> Option<Vec<SemanticToken>> {
+        if session.sync_odoo.state_init == InitState::NOT_READY {
+            return None;
+        }
+        let uri = uri.to_string();
+        let path = FileMgr::uri2pathname(uri.as_str());
+        if uri.ends_with(".py") || uri.ends_with(".pyi") {
+            if let Some(file_symbol) = SyncOdoo::get_symbol_of_opened_file(session, &PathBuf::from(path.clone())) {
+                let file_info = session.sync_odoo.get_file_mgr().borrow().get_file_info(&path);
+                if let Some(file_info) = file_info {
+                    if file_info.borrow().file_info_ast.borrow().indexed_module.is_none() {
+                        file_info.borrow_mut().prepare_ast(session);
+                    }
+                    if file_info.borrow().file_info_ast.borrow().indexed_module.is_some() {
+                        return Some(SemanticTokensFeature::get_semantic_tokens(session, &file_symbol, &file_info, range));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    pub fn handle_prepare_call_hierarchy(session: &mut SessionInfo, params: CallHierarchyPrepareParams) -> Result<Option<Vec<CallHierarchyItem>>, ResponseError> {
+        if session.sync_odoo.state_init == InitState::NOT_READY {
+            return Ok(None);
+        }
+        session.log_message(MessageType::INFO, format!("Call hierarchy requested on {} at {} - {}",
+            params.text_document_position_params.text_document.uri.to_string(),
+            params.text_document_position_params.position.line,
+            params.text_document_position_params.position.character));
+        let uri = params.text_document_position_params.text_document.uri.to_string();
+        let path = FileMgr::uri2pathname(uri.as_str());
+        if uri.ends_with(".py") || uri.ends_with(".pyi") {
+            if let Some(file_symbol) = SyncOdoo::get_symbol_of_opened_file(session, &PathBuf::from(path.clone())) {
+                let file_info = session.sync_odoo.get_file_mgr().borrow().get_file_info(&path);
+                if let Some(file_info) = file_info {
+                    if file_info.borrow().file_info_ast.borrow().indexed_module.is_none() {
+                        file_info.borrow_mut().prepare_ast(session);
+                    }
+                    if file_info.borrow().file_info_ast.borrow().indexed_module.is_some() {
+                        return Ok(CallHierarchyFeature::prepare(session, &file_symbol, &file_info, params.text_document_position_params.position.line, params.text_document_position_params.position.character));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn handle_call_hierarchy_incoming_calls(session: &mut SessionInfo, params: CallHierarchyIncomingCallsParams) -> Result<Option<Vec<CallHierarchyIncomingCall>>, ResponseError> {
+        if session.sync_odoo.state_init == InitState::NOT_READY {
+            return Ok(None);
+        }
+        session.log_message(MessageType::INFO, format!("Incoming calls requested for {}", params.item.name));
+        Ok(CallHierarchyFeature::get_incoming_calls(session, &params.item))
+    }
+
+    pub fn handle_call_hierarchy_outgoing_calls(session: &mut SessionInfo, params: CallHierarchyOutgoingCallsParams) -> Result<Option<Vec<CallHierarchyOutgoingCall>>, ResponseError> {
+        if session.sync_odoo.state_init == InitState::NOT_READY {
+            return Ok(None);
+        }
+        session.log_message(MessageType::INFO, format!("Outgoing calls requested for {}", params.item.name));
+        Ok(CallHierarchyFeature::get_outgoing_calls(session, &params.item))
+    }
+
+    pub fn handle_prepare_type_hierarchy(session: &mut SessionInfo, params: TypeHierarchyPrepareParams) -> Result<Option<Vec<TypeHierarchyItem>>, ResponseError> {
+        if session.sync_odoo.state_init == InitState::NOT_READY {
+            return Ok(None);
+        }
+        session.log_message(MessageType::INFO, format!("Type hierarchy requested on {} at {} - {}",
+            params.text_document_position_params.text_document.uri.to_string(),
+            params.text_document_position_params.position.line,
+            params.text_document_position_params.position.character));
+        let uri = params.text_document_position_params.text_document.uri.to_string();
+        let path = FileMgr::uri2pathname(uri.as_str());
+        if uri.ends_with(".py") || uri.ends_with(".pyi") {
+            if let Some(file_symbol) = SyncOdoo::get_symbol_of_opened_file(session, &PathBuf::from(path.clone())) {
+                let file_info = session.sync_odoo.get_file_mgr().borrow().get_file_info(&path);
+                if let Some(file_info) = file_info {
+                    if file_info.borrow().file_info_ast.borrow().indexed_module.is_none() {
+                        file_info.borrow_mut().prepare_ast(session);
+                    }
+                    if file_info.borrow().file_info_ast.borrow().indexed_module.is_some() {
+                        return Ok(TypeHierarchyFeature::prepare(session, &file_symbol, &file_info, params.text_document_position_params.position.line, params.text_document_position_params.position.character));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn handle_type_hierarchy_supertypes(session: &mut SessionInfo, params: TypeHierarchySupertypesParams) -> Result<Option<Vec<TypeHierarchyItem>>, ResponseError> {
+        if session.sync_odoo.state_init == InitState::NOT_READY {
+            return Ok(None);
+        }
+        session.log_message(MessageType::INFO, format!("Supertypes requested for {}", params.item.name));
+        Ok(TypeHierarchyFeature::get_supertypes(session, &params.item))
+    }
+
+    pub fn handle_type_hierarchy_subtypes(session: &mut SessionInfo, params: TypeHierarchySubtypesParams) -> Result<Option<Vec<TypeHierarchyItem>>, ResponseError> {
+        if session.sync_odoo.state_init == InitState::NOT_READY {
+            return Ok(None);
+        }
+        session.log_message(MessageType::INFO, format!("Subtypes requested for {}", params.item.name));
+        Ok(TypeHierarchyFeature::get_subtypes(session, &params.item))
+    }
+
     pub fn handle_references(session: &mut SessionInfo, params: ReferenceParams) -> Result<Option<Vec<Location>>, ResponseError> {
         if session.sync_odoo.state_init == InitState::NOT_READY {
             return Ok(None);
@@ -1297,6 +1632,91 @@ impl Odoo {
         Ok(None)
     }
 
+    pub fn handle_prepare_rename(session: &mut SessionInfo, params: TextDocumentPositionParams) -> Result<Option<PrepareRenameResponse>, ResponseError> {
+        if session.sync_odoo.state_init == InitState::NOT_READY {
+            return Ok(None);
+        }
+        session.log_message(MessageType::INFO, format!("Prepare rename requested on {} at {} - {}",
+            params.text_document.uri.to_string(),
+            params.position.line,
+            params.position.character));
+        let uri = params.text_document.uri.to_string();
+        let path = FileMgr::uri2pathname(uri.as_str());
+        if uri.ends_with(".py") || uri.ends_with(".pyi") || uri.ends_with(".xml") || uri.ends_with(".csv") {
+            if let Some(file_symbol) = SyncOdoo::get_symbol_of_opened_file(session, &PathBuf::from(path.clone())) {
+                let file_info = session.sync_odoo.get_file_mgr().borrow_mut().get_file_info(&path);
+                if let Some(file_info) = file_info {
+                    if file_info.borrow().file_info_ast.borrow().indexed_module.is_none() {
+                        file_info.borrow_mut().prepare_ast(session);
+                    }
+                    let ast_type = file_info.borrow().file_info_ast.borrow().ast_type.clone();
+                    match ast_type {
+                        AstType::Python => {
+                            if file_info.borrow_mut().file_info_ast.borrow().indexed_module.is_some() {
+                                return Ok(RenameFeature::prepare_rename(session, &file_symbol, &file_info, params.position.line, params.position.character));
+                            }
+                        },
+                        AstType::Xml => {
+                            return Ok(RenameFeature::prepare_rename_xml(session, &file_symbol, &file_info, params.position.line, params.position.character));
+                        },
+                        AstType::Csv => {
+                            return Ok(RenameFeature::prepare_rename_csv(session, &file_symbol, &file_info, params.position.line, params.position.character));
+                        },
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn handle_rename(session: &mut SessionInfo, params: RenameParams) -> Result<Option<WorkspaceEdit>, ResponseError> {
+        if session.sync_odoo.state_init == InitState::NOT_READY {
+            return Ok(None);
+        }
+        session.log_message(MessageType::INFO, format!("Rename to {} requested on {} at {} - {}",
+            params.new_name,
+            params.text_document_position.text_document.uri.to_string(),
+            params.text_document_position.position.line,
+            params.text_document_position.position.character));
+        let mut chars = params.new_name.chars();
+        let is_identifier = chars.next().map(|c| c.is_alphabetic() || c == '_').unwrap_or(false) && chars.all(|c| c.is_alphanumeric() || c == '_');
+        if !is_identifier {
+            return Err(ResponseError {
+                code: lsp_server::ErrorCode::InvalidParams as i32,
+                message: format!("{} is not a valid name", params.new_name),
+                data: None
+            });
+        }
+        let uri = params.text_document_position.text_document.uri.to_string();
+        let path = FileMgr::uri2pathname(uri.as_str());
+        let position = params.text_document_position.position;
+        if uri.ends_with(".py") || uri.ends_with(".pyi") || uri.ends_with(".xml") || uri.ends_with(".csv") {
+            if let Some(file_symbol) = SyncOdoo::get_symbol_of_opened_file(session, &PathBuf::from(path.clone())) {
+                let file_info = session.sync_odoo.get_file_mgr().borrow_mut().get_file_info(&path);
+                if let Some(file_info) = file_info {
+                    if file_info.borrow().file_info_ast.borrow().indexed_module.is_none() {
+                        file_info.borrow_mut().prepare_ast(session);
+                    }
+                    let ast_type = file_info.borrow().file_info_ast.borrow().ast_type.clone();
+                    match ast_type {
+                        AstType::Python => {
+                            if file_info.borrow_mut().file_info_ast.borrow().indexed_module.is_some() {
+                                return Ok(RenameFeature::rename(session, &file_symbol, &file_info, position.line, position.character, &params.new_name));
+                            }
+                        },
+                        AstType::Xml => {
+                            return Ok(RenameFeature::rename_xml(session, &file_symbol, &file_info, position.line, position.character, &params.new_name));
+                        },
+                        AstType::Csv => {
+                            return Ok(RenameFeature::rename_csv(session, &file_symbol, &file_info, position.line, position.character, &params.new_name));
+                        },
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
     pub fn handle_autocomplete(session: &mut SessionInfo ,params: CompletionParams) -> Result<Option<CompletionResponse>, ResponseError> {
         if session.sync_odoo.state_init == InitState::NOT_READY {
             return Ok(None);
@@ -1315,8 +1735,19 @@ impl Odoo {
                     if file_info.borrow().file_info_ast.borrow().indexed_module.is_none() {
                         file_info.borrow_mut().prepare_ast(session);
                     }
-                    if file_info.borrow_mut().file_info_ast.borrow().indexed_module.is_some() {
-                        return Ok(CompletionFeature::autocomplete(session, &file_symbol, &file_info, params.text_document_position.position.line, params.text_document_position.position.character));
+                    let ast_type = file_info.borrow().file_info_ast.borrow().ast_type.clone();
+                    match ast_type {
+                        AstType::Python => {
+                            if file_info.borrow_mut().file_info_ast.borrow().indexed_module.is_some() {
+                                return Ok(CompletionFeature::autocomplete(session, &file_symbol, &file_info, params.text_document_position.position.line, params.text_document_position.position.character));
+                            }
+                        },
+                        AstType::Xml => {
+                            return Ok(CompletionFeature::autocomplete_xml(session, &file_symbol, &file_info, params.text_document_position.position.line, params.text_document_position.position.character));
+                        },
+                        AstType::Csv => {
+                            return Ok(CompletionFeature::autocomplete_csv(session, &file_symbol, &file_info, params.text_document_position.position.line, params.text_document_position.position.character));
+                        },
                     }
                 }
             }
@@ -1618,6 +2049,15 @@ impl Odoo {
         }
         Ok(None)
     }
+
+    pub(crate) fn handle_workspace_symbols(session: &mut SessionInfo<'_>, params: WorkspaceSymbolParams) -> Result<Option<WorkspaceSymbolResponse>, ResponseError> {
+        if session.sync_odoo.state_init == InitState::NOT_READY {
+            return Ok(None);
+        }
+        session.log_message(MessageType::INFO, format!("Workspace symbol requested for {}", params.query));
+        Ok(WorkspaceSymbolFeature::get_workspace_symbols(session, &params.query))
+    }
+
     /// Checks if the given path is a configuration file under one of the workspace folders.
     fn is_config_workspace_file(session: &mut SessionInfo, path: &PathBuf) -> bool {
         for (_, ws_dir) in session.sync_odoo.get_file_mgr().borrow().get_workspace_folders().iter() {
@@ -23,6 +23,7 @@ use super::file_mgr::{combine_noqa_info, FileInfo};
 use super::import_resolver::ImportResult;
 use super::odoo::SyncOdoo;
 use super::python_utils::AssignTargetType;
+use super::xml_id_usages::XmlIdUsages;
 use super::symbols::function_symbol::{Argument, ArgumentType};
 use super::symbols::module_symbol::ModuleSymbol;
 use super::symbols::symbol_mgr::SectionIndex;
@@ -73,6 +74,8 @@ impl PythonArchBuilder {
         symbol.borrow_mut().set_build_status(BuildSteps::ARCH, BuildStatus::IN_PROGRESS);
         let path = self.file.borrow().get_symbol_first_path();
         if self.file_mode {
+            //env.ref() calls are registered again during the evaluation of the new content
+            XmlIdUsages::remove_usages(session, &self.file);
             let in_workspace = (self.file.borrow().parent().is_some() &&
                 self.file.borrow().parent().as_ref().unwrap().upgrade().is_some() &&
                 self.file.borrow().parent().as_ref().unwrap().upgrade().unwrap().borrow().in_workspace()) ||
@@ -742,7 +745,7 @@ impl PythonArchBuilder {
             });
         }
         let mut add_noqa = false;
-        if let Some(noqa_bloc) = self.file_info.as_ref().unwrap().borrow().noqas_blocs.get(&func_def.range.start().to_u32()) {
+        if let Some(noqa_bloc) = self.file_info.as_ref().unwrap().borrow().get_noqa_bloc(TextRange::new(func_def.range.start(), func_def.name.range.start())) {
             session.noqas_stack.push(noqa_bloc.clone());
             add_noqa = true;
         }
@@ -782,7 +785,7 @@ impl PythonArchBuilder {
         }
         drop(sym_bw);
         let mut add_noqa = false;
-        if let Some(noqa_bloc) = self.file_info.as_ref().unwrap().borrow().noqas_blocs.get(&class_def.range.start().to_u32()) {
+        if let Some(noqa_bloc) = self.file_info.as_ref().unwrap().borrow().get_noqa_bloc(TextRange::new(class_def.range.start(), class_def.name.range.start())) {
             session.noqas_stack.push(noqa_bloc.clone());
             add_noqa = true;
         }
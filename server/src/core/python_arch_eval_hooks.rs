@@ -26,6 +26,7 @@ use super::entry_point::EntryPoint;
 use super::evaluation::{ContextValue, Evaluation, EvaluationSymbolPtr, EvaluationSymbol, EvaluationSymbolWeak};
 use super::file_mgr::FileMgr;
 use super::python_arch_eval::PythonArchEval;
+use super::xml_id_usages::XmlIdUsages;
 
 type PythonArchEvalHookFile = fn (odoo: &mut SessionInfo, entry: &Rc<RefCell<EntryPoint>>, file_symbol: Rc<RefCell<Symbol>>, symbol: Rc<RefCell<Symbol>>);
 
@@ -754,6 +755,7 @@ impl PythonArchEvalHooks {
                         if let Some(diagnostic_base) = create_diagnostic(&session, DiagnosticCode::OLS03001, &[&format!("{:?}", valid_modules)]) {
                             diagnostics.push(Diagnostic {
                                 range: FileMgr::textRange_to_temporary_Range(&context.get(&S!("range")).unwrap().as_text_range()),
+                                data: Some(serde_json::json!({"model": s})),
                                 ..diagnostic_base.clone()
                             });
                         }
@@ -1016,6 +1018,15 @@ impl PythonArchEvalHooks {
             }
         }
 
+        // Keys of static selections, used to validate values in data files
+        for arg_name in ["selection", "selection_add"] {
+            let arg = PythonArchEvalHooks::find_special_arguments(&parameters, arg_name).map(|(expr, _)| expr)
+                .or_else(|| parameters.args.get(0).filter(|_| arg_name == "selection"));
+            if let Some(keys) = arg.and_then(PythonArchEvalHooks::get_selection_keys) {
+                context.insert(S!(arg_name), ContextValue::LIST(keys));
+            }
+        }
+
         context.extend([
             (S!("field_parent"), ContextValue::SYMBOL(Rc::downgrade(&parent))),
         ]);
@@ -1027,6 +1038,24 @@ impl PythonArchEvalHooks {
         }));
     }
 
+    /* Keys of a selection given as a list of (key, label) tuples */
+    fn get_selection_keys(expr: &Expr) -> Option<Vec<String>> {
+        let Expr::List(list) = expr else {
+            return None;
+        };
+        let mut keys = vec![];
+        for item in list.elts.iter() {
+            let Expr::Tuple(tuple) = item else {
+                return None;
+            };
+            let Some(Expr::StringLiteral(key)) = tuple.elts.first() else {
+                return None;
+            };
+            keys.push(key.value.to_str().to_string());
+        }
+        Some(keys)
+    }
+
     fn eval_init(session: &mut SessionInfo, evaluation_sym: &EvaluationSymbol, maybe_context: &mut Option<Context>, diagnostics: &mut Vec<Diagnostic>, file_symbol: Option<Rc<RefCell<Symbol>>>) -> Option<EvaluationSymbolPtr> {
         return PythonArchEvalHooks::eval_init_common(session, evaluation_sym, maybe_context, diagnostics, file_symbol, false)
     }
@@ -1078,6 +1107,7 @@ impl PythonArchEvalHooks {
             if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS03001, &[]) {
                 diagnostics.push(Diagnostic {
                     range: FileMgr::textRange_to_temporary_Range(&expr.range()),
+                    data: Some(serde_json::json!({"model": returns_str})),
                     ..diagnostic
                 });
             }
@@ -1149,7 +1179,7 @@ impl PythonArchEvalHooks {
         diagnostics
     }
 
-    fn eval_env_ref(session: &mut SessionInfo, _evaluation_sym: &EvaluationSymbol, context: &mut Option<Context>, diagnostics: &mut Vec<Diagnostic>, _scope: Option<Rc<RefCell<Symbol>>>) -> Option<EvaluationSymbolPtr> {
+    fn eval_env_ref(session: &mut SessionInfo, _evaluation_sym: &EvaluationSymbol, context: &mut Option<Context>, diagnostics: &mut Vec<Diagnostic>, scope: Option<Rc<RefCell<Symbol>>>) -> Option<EvaluationSymbolPtr> {
         let Some(context) = context else {return None};
         let in_validation = context.get(&S!("is_in_validation")).unwrap_or(&ContextValue::BOOLEAN(false)).as_bool();
         let Some(parameters) = context.get(&S!("parameters")).map(|ps| ps.as_arguments()) else {return None};
@@ -1193,6 +1223,9 @@ impl PythonArchEvalHooks {
         let Some(module_rc) = module.unwrap().upgrade() else {
             return None;
         };
+        if let Some(file_symbol) = scope.and_then(|s| s.borrow().get_file()).and_then(|w| w.upgrade()) {
+            XmlIdUsages::add_usage(session, &file_symbol, xml_id_str);
+        }
         let module_rc_bw = module_rc.borrow();
         let Some(_symbol) = module_rc_bw.as_module_package().xml_id_locations.get(xml_id.as_str()) else {
             if in_validation {
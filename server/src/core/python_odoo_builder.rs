@@ -10,7 +10,7 @@ use weak_table::PtrWeakHashSet;
 use crate::constants::{OYarn, SymType};
 use crate::core::model::{Model, ModelData};
 use crate::core::symbols::symbol::Symbol;
-use crate::core::xml_data::{OdooData, OdooDataRecord};
+use crate::core::xml_data::{OdooData, OdooDataRecord, XmlDataQWeb};
 use crate::threads::SessionInfo;
 use crate::utils::compare_semver;
 use crate::{oyarn, Sy, S};
@@ -63,6 +63,9 @@ impl PythonOdooBuilder {
                 }),
                 xml_id: Some(xml_id_model_name),
                 fields: vec![],
+                qweb: XmlDataQWeb::default(),
+                arch: vec![],
+                expression_xml_ids: vec![],
                 range: std::ops::Range::<usize> {
                     start: self.symbol.borrow().range().start().to_usize(),
                     end: self.symbol.borrow().range().end().to_usize(),
@@ -342,6 +342,7 @@ impl PythonValidator {
                                             if let Some(diagnostic_base) = create_diagnostic(&session, DiagnosticCode::OLS03003, &[&module.borrow().as_module_package().dir_name]) {
                                                 self.diagnostics.push(Diagnostic {
                                                     range: Range::new(Position::new(alias.range.start().to_u32(), 0), Position::new(alias.range.end().to_u32(), 0)),
+                                                    data: Some(serde_json::json!({"module": module.borrow().as_module_package().dir_name.as_str()})),
                                                     ..diagnostic_base.clone()
                                                 });
                                             }
@@ -476,6 +477,7 @@ impl PythonValidator {
                                 if let Some(diagnostic_base) = create_diagnostic(&session, DiagnosticCode::OLS03015, &[&comodel_field_name]) {
                                     self.diagnostics.push(Diagnostic {
                                         range: Range::new(Position::new(special_arg_range.start().to_u32(), 0), Position::new(special_arg_range.end().to_u32(), 0)),
+                                        data: Some(serde_json::json!({"model": comodel_field_name})),
                                         ..diagnostic_base.clone()
                                     });
                                 }
@@ -642,6 +644,7 @@ impl PythonValidator {
                     if let Some(diagnostic_base) = create_diagnostic(&session, DiagnosticCode::OLS03004, &[]) {
                         self.diagnostics.push(Diagnostic {
                             range: Range::new(Position::new(range.start().to_u32(), 0), Position::new(range.end().to_u32(), 0)),
+                            data: Some(serde_json::json!({"model": model_name.as_str()})),
                             ..diagnostic_base
                         });
                     }
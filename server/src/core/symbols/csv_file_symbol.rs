@@ -1,7 +1,7 @@
 use weak_table::PtrWeakHashSet;
 
 use crate::{constants::{BuildStatus, BuildSteps, OYarn}, core::{file_mgr::NoqaInfo, model::Model, xml_data::OdooData}, oyarn};
-use std::{cell::RefCell, collections::HashMap, rc::{Rc, Weak}};
+use std::{cell::RefCell, collections::HashMap, ops::Range, rc::{Rc, Weak}};
 
 use super::{symbol::Symbol, symbol_mgr::SectionRange};
 
@@ -15,10 +15,11 @@ pub struct CsvFileSymbol {
     pub arch_status: BuildStatus,
     pub validation_status: BuildStatus,
     pub not_found_paths: Vec<(BuildSteps, Vec<OYarn>)>,
+    pub not_found_models: HashMap<OYarn, BuildSteps>,
     in_workspace: bool,
     pub xml_ids: HashMap<OYarn, Vec<OdooData>>,
     pub model_name: OYarn,
-    pub headers: Vec<OYarn>,
+    pub headers: Vec<(OYarn, Range<usize>)>, //header name and its byte range in the file
     pub self_import: bool,
     pub model_dependencies: PtrWeakHashSet<Weak<RefCell<Model>>>, //always on validation level, as odoo step is always required
     pub dependencies: Vec<Vec<Option<PtrWeakHashSet<Weak<RefCell<Symbol>>>>>>,
@@ -45,6 +46,7 @@ impl CsvFileSymbol {
             arch_status: BuildStatus::PENDING,
             validation_status: BuildStatus::PENDING,
             not_found_paths: vec![],
+            not_found_models: HashMap::new(),
             in_workspace: false,
             model_name: OYarn::default(),
             headers: Vec::new(),
@@ -43,6 +43,7 @@ pub struct ModuleSymbol {
     pub module_symbols: HashMap<OYarn, Rc<RefCell<Symbol>>>,
     pub xml_id_locations: HashMap<OYarn, PtrWeakHashSet<Weak<RefCell<Symbol>>>>, //contains all xml_file_symbols that contains the xml_id. Needed because it can be in another module.
     pub xml_ids: HashMap<OYarn, Vec<OdooData>>, //used for dynamic XML_ID records, like ir.models. normal ids are in their XmlFile
+    pub xml_id_usages: HashMap<OYarn, PtrWeakHashSet<Weak<RefCell<Symbol>>>>, //contains all files (xml, csv or python) that use the xml_id. Reverse of xml_id_locations
     pub arch_status: BuildStatus,
     pub arch_eval_status: BuildStatus,
     pub validation_status: BuildStatus,
@@ -82,6 +83,7 @@ impl ModuleSymbol {
             module_name: OYarn::from(""),
             xml_id_locations: HashMap::new(),
             xml_ids: HashMap::new(),
+            xml_id_usages: HashMap::new(),
             dir_name: OYarn::from(""),
             depends: vec!((OYarn::from("base"), TextRange::default())),
             all_depends: HashSet::new(),
@@ -415,6 +417,10 @@ impl ModuleSymbol {
         symbol.borrow().as_module_package().dir_name == *dir_name || symbol.borrow().as_module_package().all_depends.contains(dir_name)
     }
 
+    pub fn get_all_depends(&self) -> &HashSet<OYarn> {
+        &self.all_depends
+    }
+
     pub fn get_dependencies(&self, step: usize, level: usize) -> Option<&PtrWeakHashSet<Weak<RefCell<Symbol>>>>
     {
         self.dependencies.get(step)?.get(level)?.as_ref()
@@ -6,6 +6,7 @@ use weak_table::traits::WeakElement;
 use crate::core::diagnostics::{create_diagnostic, DiagnosticCode};
 use crate::core::file_mgr::NoqaInfo;
 use crate::core::xml_data::OdooData;
+use crate::core::xml_id_usages::XmlIdUsages;
 use crate::{constants::*, oyarn, Sy};
 use crate::core::entry_point::EntryPoint;
 use crate::core::evaluation::{Context, ContextValue, Evaluation, EvaluationSymbolPtr};
@@ -1236,7 +1237,7 @@ impl Symbol {
             Symbol::Class(_) => None,
             Symbol::Function(_) => None,
             Symbol::Variable(_) => None,
-            Symbol::CsvFileSymbol(_) => None,
+            Symbol::CsvFileSymbol(f) => Some(&f.not_found_models),
         }
     }
 
@@ -1252,7 +1253,7 @@ impl Symbol {
             Symbol::Class(_) => None,
             Symbol::Function(_) => None,
             Symbol::Variable(_) => None,
-            Symbol::CsvFileSymbol(_) => None,
+            Symbol::CsvFileSymbol(f) => Some(&mut f.not_found_models),
         }
     }
 
@@ -1797,6 +1798,7 @@ impl Symbol {
             parent_bw.remove_symbol(ref_to_unload.clone());
             drop(parent_bw);
             if matches!(&ref_to_unload.borrow().typ(), SymType::FILE | SymType::PACKAGE(_) | SymType::XML_FILE | SymType::CSV_FILE) {
+                XmlIdUsages::remove_usages(session, &ref_to_unload);
                 Symbol::invalidate(session, ref_to_unload.clone(), &BuildSteps::ARCH);
             }
             //check if we should not reimport automatically
@@ -5,7 +5,7 @@ use roxmltree::{Attribute, Node};
 use tracing::warn;
 use weak_table::PtrWeakHashSet;
 
-use crate::core::{diagnostics::{create_diagnostic, DiagnosticCode}, odoo::SyncOdoo};
+use crate::core::{diagnostics::{create_diagnostic, DiagnosticCode}, odoo::SyncOdoo, xml_id_usages::XmlIdUsages};
 use crate::{constants::{BuildStatus, BuildSteps, OYarn}, core::{entry_point::EntryPointType, xml_data::OdooData}, threads::SessionInfo, Sy};
 
 use super::{file_mgr::FileInfo, symbols::{symbol::Symbol}};
@@ -34,7 +34,11 @@ impl XmlArchBuilder {
         if let Some(ep) = ep {
             self.is_in_main_ep = ep.borrow().typ == EntryPointType::MAIN || ep.borrow().typ == EntryPointType::ADDON;
         }
+        XmlIdUsages::remove_usages(session, &self.xml_symbol);
         self.load_odoo_openerp_data(session, node, &mut diagnostics);
+        for occurrence in XmlIdUsages::collect_xml(node).iter().filter(|o| !o.is_declaration) {
+            XmlIdUsages::add_usage(session, &self.xml_symbol, &occurrence.xml_id);
+        }
         self.xml_symbol.borrow_mut().set_build_status(BuildSteps::ARCH, BuildStatus::DONE);
         file_info.replace_diagnostics(BuildSteps::ARCH, diagnostics);
         session.sync_odoo.add_to_validations(self.xml_symbol.clone());
@@ -86,7 +90,7 @@ impl XmlArchBuilder {
     }
 
     pub fn get_group_ids(&self, session: &mut SessionInfo, xml_id: &str, attr: &Attribute, diagnostics: &mut Vec<Diagnostic>) -> Vec<OdooData> {
-        let xml_ids = SyncOdoo::get_xml_ids(session, &self.xml_symbol, xml_id, &attr.range(), diagnostics);
+        let xml_ids = SyncOdoo::get_xml_ids(session, &self.xml_symbol, xml_id, &attr.range(), diagnostics).unwrap_or_default();
         let mut res = vec![];
         for data in xml_ids.iter() {
             match data {
@@ -3,19 +3,65 @@ use std::rc::Rc;
 use lsp_types::{Diagnostic, Position, Range};
 use roxmltree::Node;
 
-use crate::{constants::OYarn, core::{diagnostics::{create_diagnostic, DiagnosticCode}, odoo::SyncOdoo, xml_data::{OdooData, XmlDataDelete, OdooDataField, XmlDataMenuItem, OdooDataRecord, XmlDataTemplate}}, oyarn, threads::SessionInfo, Sy};
+use crate::{constants::OYarn, core::{diagnostics::{create_diagnostic, DiagnosticCode}, odoo::SyncOdoo, xml_data::{OdooData, XmlDataArchNode, ARCH_EXPRESSION_ATTRIBUTES, XmlDataDelete, OdooDataField, XmlDataMenuItem, OdooDataRecord, XmlDataQWeb, XmlDataTemplate}, xml_id_usages::XmlIdUsages}, oyarn, threads::SessionInfo};
 
 use super::xml_arch_builder::XmlArchBuilder;
 
+/* Tags and attributes allowed by the rules below. They are checked by the validation and proposed by the completion */
+const ROOT_TAGS: [&str; 2] = ["odoo", "openerp"];
+const DATA_CHILDREN: [&str; 8] = ["odoo", "openerp", "data", "menuitem", "record", "template", "delete", "function"];
+const MENUITEM_CHILDREN: [&str; 1] = ["menuitem"];
+const RECORD_CHILDREN: [&str; 1] = ["field"];
+const FIELD_CHILDREN: [&str; 1] = ["record"];
+const FUNCTION_CHILDREN: [&str; 2] = ["value", "function"];
+const DATA_ATTRIBUTES: [&str; 4] = ["noupdate", "auto_sequence", "uid", "context"];
+const MENUITEM_ATTRIBUTES: [&str; 8] = ["id", "name", "sequence", "groups", "active", "action", "parent", "web_icon"];
+const RECORD_ATTRIBUTES: [&str; 5] = ["id", "model", "forcecreate", "uid", "context"];
+const FIELD_ATTRIBUTES: [&str; 8] = ["name", "type", "file", "ref", "eval", "search", "model", "use"];
+const VALUE_ATTRIBUTES: [&str; 7] = ["name", "model", "use", "search", "eval", "type", "file"];
+const FUNCTION_ATTRIBUTES: [&str; 5] = ["model", "name", "uid", "context", "eval"];
+/* any attribute is valid on templates and deletes, these are only used by the completion */
+const TEMPLATE_ATTRIBUTES: [&str; 7] = ["id", "name", "inherit_id", "priority", "primary", "active", "groups"];
+const DELETE_ATTRIBUTES: [&str; 3] = ["model", "id", "search"];
+
 /* Contains the RelaxNG Validation part of the XmlArchBuilder */
 impl XmlArchBuilder {
 
+    /* Tags allowed as children of the given tag (None for the root of the document) */
+    pub fn rng_allowed_children(parent_tag: Option<&str>) -> &'static [&'static str] {
+        match parent_tag {
+            None => &ROOT_TAGS,
+            //nested odoo and openerp nodes are accepted, but not proposed
+            Some("odoo") | Some("openerp") | Some("data") => &DATA_CHILDREN[2..],
+            Some("menuitem") => &MENUITEM_CHILDREN,
+            Some("record") => &RECORD_CHILDREN,
+            Some("field") => &FIELD_CHILDREN,
+            Some("function") => &FUNCTION_CHILDREN,
+            _ => &[]
+        }
+    }
+
+    /* Attributes allowed on the given tag */
+    pub fn rng_allowed_attributes(tag: &str) -> &'static [&'static str] {
+        match tag {
+            "odoo" | "openerp" | "data" => &DATA_ATTRIBUTES,
+            "menuitem" => &MENUITEM_ATTRIBUTES,
+            "record" => &RECORD_ATTRIBUTES,
+            "field" => &FIELD_ATTRIBUTES,
+            "value" => &VALUE_ATTRIBUTES,
+            "template" => &TEMPLATE_ATTRIBUTES,
+            "delete" => &DELETE_ATTRIBUTES,
+            "function" => &FUNCTION_ATTRIBUTES,
+            _ => &[]
+        }
+    }
+
     pub fn load_odoo_openerp_data(&mut self, session: &mut SessionInfo, node: &Node, diagnostics: &mut Vec<Diagnostic>) -> bool {
         match node.tag_name().name() {
             "odoo" | "openerp" | "data" => {
                 for attr in node.attributes() {
                     match attr.name() {
-                        "noupdate" | "auto_sequence" | "uid" | "context" => {},
+                        name if DATA_ATTRIBUTES.contains(&name) => {},
                         _ => {
                             if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05004, &[attr.name(), node.tag_name().name()]) {
                                 diagnostics.push(
@@ -30,12 +76,12 @@ impl XmlArchBuilder {
                 }
 
                 for child in node.children().filter(|n| n.is_element()) {
-                    if !(self.load_odoo_openerp_data(session, &child, diagnostics)
+                    if !(DATA_CHILDREN.contains(&child.tag_name().name()) && (self.load_odoo_openerp_data(session, &child, diagnostics)
                         || self.load_menuitem(session, &child, false, diagnostics)
                         || self.load_record(session, &child, diagnostics)
                         || self.load_template(session, &child, diagnostics)
                         || self.load_delete(session, &child, diagnostics)
-                        || self.load_function(session, &child, diagnostics)
+                        || self.load_function(session, &child, diagnostics))
                         || child.is_text() || child.is_comment()) {
                         if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05005, &[child.tag_name().name(), node.tag_name().name()]) {
                             diagnostics.push(
@@ -85,7 +131,6 @@ impl XmlArchBuilder {
                         }
                     }
                 },
-                "name" | "active" => {},
                 "action" => {
                     if (has_parent || is_submenu) && node.has_children() {
                         let other_than_text = node.children().any(|c| !c.is_text() && !c.is_comment());
@@ -99,7 +144,7 @@ impl XmlArchBuilder {
                         }
                     }
                     //check that action exists
-                    if SyncOdoo::get_xml_ids(session, &self.xml_symbol, attr.value(), &attr.range(), diagnostics).is_empty() {
+                    if SyncOdoo::get_xml_ids(session, &self.xml_symbol, attr.value(), &attr.range(), diagnostics).is_some_and(|xml_ids| xml_ids.is_empty()) {
                         if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05053, &[attr.value()]) {
                             diagnostics.push(Diagnostic {
                                 range: Range { start: Position::new(attr.range().start as u32, 0), end: Position::new(attr.range().end as u32, 0) },
@@ -118,7 +163,7 @@ impl XmlArchBuilder {
                         }
                     } else {
                         //check that parent exists
-                        if SyncOdoo::get_xml_ids(session, &self.xml_symbol, attr.value(), &attr.range(), diagnostics).is_empty() {
+                        if SyncOdoo::get_xml_ids(session, &self.xml_symbol, attr.value(), &attr.range(), diagnostics).is_some_and(|xml_ids| xml_ids.is_empty()) {
                             if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05052, &[attr.value()]) {
                                 diagnostics.push(Diagnostic {
                                     range: Range { start: Position::new(attr.range().start as u32, 0), end: Position::new(attr.range().end as u32, 0) },
@@ -138,7 +183,7 @@ impl XmlArchBuilder {
                         }
                     }
                 }
-                _ => {
+                _ if !MENUITEM_ATTRIBUTES.contains(&attr.name()) => {
                     if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05007, &[attr.name()]) {
                         diagnostics.push(Diagnostic {
                             range: Range { start: Position::new(attr.range().start as u32, 0), end: Position::new(attr.range().end as u32, 0) },
@@ -146,6 +191,7 @@ impl XmlArchBuilder {
                         });
                     }
                 }
+                _ => {}
             }
         }
         if found_id.is_none() {
@@ -157,7 +203,7 @@ impl XmlArchBuilder {
             }
         }
         for child in node.children().filter(|n| n.is_element()) {
-            if child.tag_name().name() != "menuitem" {
+            if !MENUITEM_CHILDREN.contains(&child.tag_name().name()) {
                 if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05011, &[child.tag_name().name()]) {
                     diagnostics.push(Diagnostic {
                         range: Range { start: Position::new(child.range().start as u32, 0), end: Position::new(child.range().end as u32, 0) },
@@ -169,10 +215,17 @@ impl XmlArchBuilder {
                 self.load_menuitem(session, &child, true, diagnostics);
             }
         }
+        let parent = match (is_submenu, node.attribute_node("parent")) {
+            (false, Some(parent)) => Some((oyarn!("{}", parent.value()), parent.range_value())),
+            (true, _) => node.parent_element().and_then(|p| p.attribute("id")).map(|id| (oyarn!("{}", id), node.range())),
+            _ => None
+        };
         let data = OdooData::MENUITEM(XmlDataMenuItem {
             file_symbol: Rc::downgrade(&self.xml_symbol),
             xml_id: found_id.clone().map(|id| oyarn!("{}", id)),
-            range: node.range().clone()
+            range: node.range().clone(),
+            action: node.attribute_node("action").map(|action| (oyarn!("{}", action.value()), action.range_value())),
+            parent,
         });
         self.on_operation_creation(session, found_id, node, data, diagnostics);
         true
@@ -185,10 +238,8 @@ impl XmlArchBuilder {
         for attr in node.attributes() {
             match attr.name() {
                 "id" => {found_id = Some(attr.value().to_string());},
-                "forcecreate" => {},
                 "model" => {found_model = true;},
-                "uid" => {},
-                "context" => {},
+                name if RECORD_ATTRIBUTES.contains(&name) => {},
                 _ => {
                     if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05013, &[attr.name()]) {
                         diagnostics.push(Diagnostic {
@@ -214,10 +265,24 @@ impl XmlArchBuilder {
             model: (oyarn!("{}", node.attribute("model").unwrap()), node.attribute_node("model").unwrap().range()),
             xml_id: found_id.clone().map(|id| oyarn!("{}", id)),
             fields: vec![],
-            range: node.range().clone()
+            range: node.range().clone(),
+            qweb: XmlDataQWeb::default(),
+            arch: vec![],
+            expression_xml_ids: vec![],
         };
+        let is_view = data.model.0 == "ir.ui.view";
         for child in node.children().filter(|n| n.is_element()) {
-            if let Some(field) = self.load_field(session, &child, diagnostics) {
+            if let Some(field) = RECORD_CHILDREN.contains(&child.tag_name().name()).then(|| self.load_field(session, &child, diagnostics)).flatten() {
+                if is_view {
+                    match (field.name.as_str(), child.attribute_node("ref")) {
+                        ("inherit_id", Some(ref_attr)) => data.qweb.inherits.push((oyarn!("{}", ref_attr.value()), ref_attr.range_value())),
+                        ("arch", _) | ("arch_db", _) | ("arch_base", _) => {
+                            XmlArchBuilder::collect_qweb(&child, &mut data.qweb);
+                            XmlArchBuilder::collect_arch(&child, &mut data.arch);
+                        },
+                        _ => {}
+                    }
+                }
                 data.fields.push(field);
             } else {
                 if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05015, &[child.tag_name().name()]) {
@@ -228,6 +293,9 @@ impl XmlArchBuilder {
                 }
             }
         }
+        data.expression_xml_ids = XmlIdUsages::collect_expressions(node).into_iter()
+            .map(|occurrence| (oyarn!("{}", occurrence.xml_id), occurrence.range))
+            .collect();
         let data = OdooData::RECORD(data);
         self.on_operation_creation(session, found_id, node, data, diagnostics);
         true
@@ -314,7 +382,6 @@ impl XmlArchBuilder {
         } 
         for attr in node.attributes() {
             match attr.name() {
-                "name" | "type" | "file" => {},
                 "ref" | "eval" | "search" => {
                     if node.text().is_some() {
                         if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05022, &[attr.name()]) {
@@ -345,7 +412,7 @@ impl XmlArchBuilder {
                         }
                     }
                 }
-                _ => {
+                _ if !FIELD_ATTRIBUTES.contains(&attr.name()) => {
                     if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05025, &[attr.name()]) {
                         diagnostics.push(Diagnostic {
                             range: Range { start: Position::new(attr.range().start as u32, 0), end: Position::new(attr.range().end as u32, 0) },
@@ -353,10 +420,11 @@ impl XmlArchBuilder {
                         });
                     }
                 }
+                _ => {}
             }
         }
         for child in node.children() {
-            if !self.load_record(session, &child, diagnostics) && !child.is_text() && !child.is_comment() && !is_xml_or_html {
+            if !(FIELD_CHILDREN.contains(&child.tag_name().name()) && self.load_record(session, &child, diagnostics)) && !child.is_text() && !child.is_comment() && !is_xml_or_html {
                 if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05026, &[]) {
                     diagnostics.push(Diagnostic {
                         range: Range { start: Position::new(child.range().start as u32, 0), end: Position::new(child.range().end as u32, 0) },
@@ -378,6 +446,9 @@ impl XmlArchBuilder {
             range: node.attribute_node("name").unwrap().range(),
             text: text,
             text_range: text_range,
+            typ: node.attribute("type").map(|t| oyarn!("{}", t)),
+            ref_id: node.attribute_node("ref").map(|r| (oyarn!("{}", r.value()), r.range_value())),
+            eval: node.attribute_node("eval").map(|e| (e.value().to_string(), e.range_value())),
         })
     }
 
@@ -388,7 +459,6 @@ impl XmlArchBuilder {
         let has_type = node.has_attribute("type");
         for attr in node.attributes() {
             match attr.name() {
-                "name" | "model" | "use" => {},
                 "search" => {
                     has_search = true;
                     if has_eval || has_type {
@@ -439,7 +509,7 @@ impl XmlArchBuilder {
                         }
                     }
                 }
-                _ => {
+                _ if !VALUE_ATTRIBUTES.contains(&attr.name()) => {
                     if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05032, &[attr.name()]) {
                         diagnostics.push(Diagnostic {
                             range: Range { start: Position::new(attr.range().start as u32, 0), end: Position::new(attr.range().end as u32, 0) },
@@ -447,6 +517,7 @@ impl XmlArchBuilder {
                         });
                     }
                 }
+                _ => {}
             }
         }
         true
@@ -456,15 +527,63 @@ impl XmlArchBuilder {
         if node.tag_name().name() != "template" { return false; }
         //no interesting rule to check, as 'any' is valid
         let found_id = node.attribute("id").map(|s| s.to_string());
+        let mut qweb = XmlDataQWeb::default();
+        if let Some(inherit_id) = node.attribute_node("inherit_id") {
+            qweb.inherits.push((oyarn!("{}", inherit_id.value()), inherit_id.range_value()));
+        }
+        XmlArchBuilder::collect_qweb(node, &mut qweb);
         let data = OdooData::TEMPLATE(XmlDataTemplate {
             file_symbol: Rc::downgrade(&self.xml_symbol),
             xml_id: found_id.clone().map(|id| oyarn!("{}", id)),
             range: node.range().clone(),
+            qweb,
         });
         self.on_operation_creation(session, found_id, node, data, diagnostics);
         true
     }
 
+    /* Collect the nodes of a view arch that are validated against the model of the view. See XmlDataArchNode */
+    fn collect_arch(node: &Node, arch: &mut Vec<XmlDataArchNode>) {
+        for child in node.children().filter(|n| n.is_element()) {
+            let tag = child.tag_name().name();
+            let has_expression = ARCH_EXPRESSION_ATTRIBUTES.iter().any(|attr| child.has_attribute(*attr));
+            if !["field", "groupby", "button", "xpath"].contains(&tag) && !child.has_attribute("position") && !has_expression {
+                XmlArchBuilder::collect_arch(&child, arch);
+                continue;
+            }
+            let mut arch_node = XmlDataArchNode {
+                tag: oyarn!("{}", tag),
+                attributes: vec![],
+                children: vec![],
+            };
+            for attr in child.attributes().filter(|a| ["name", "type", "position", "expr"].contains(&a.name()) || ARCH_EXPRESSION_ATTRIBUTES.contains(&a.name())) {
+                arch_node.attributes.push((oyarn!("{}", attr.name()), oyarn!("{}", attr.value()), attr.range_value()));
+            }
+            XmlArchBuilder::collect_arch(&child, &mut arch_node.children);
+            arch.push(arch_node);
+        }
+    }
+
+    /* Collect t-call, t-inherit and t-inherit-mode values in the content of a template or of a view arch */
+    fn collect_qweb(node: &Node, qweb: &mut XmlDataQWeb) {
+        for descendant in node.descendants().filter(|n| n.is_element()) {
+            for attr in descendant.attributes() {
+                let value = (oyarn!("{}", attr.value()), attr.range_value());
+                match attr.name() {
+                    "t-call" => {
+                        //"{{ ... }}" or "#{...}" are evaluated at rendering and can't be checked
+                        if !attr.value().contains('{') {
+                            qweb.t_calls.push(value);
+                        }
+                    },
+                    "t-inherit" => qweb.inherits.push(value),
+                    "t-inherit-mode" => qweb.inherit_modes.push(value),
+                    _ => {}
+                }
+            }
+        }
+    }
+
     fn load_delete(&mut self, session: &mut SessionInfo, node: &Node, diagnostics: &mut Vec<Diagnostic>) -> bool {
         if node.tag_name().name() != "delete" { return false; }
         if node.attribute("model").is_none() {
@@ -495,9 +614,13 @@ impl XmlArchBuilder {
         }
         let data = OdooData::DELETE(XmlDataDelete {
             file_symbol: Rc::downgrade(&self.xml_symbol),
-            xml_id: found_id.clone().map(|id| oyarn!("{}", id)),
+            xml_id: node.attribute_node("id").map(|id| (oyarn!("{}", id.value()), id.range_value())),
             range: node.range().clone(),
-            model: Sy!(node.attribute("model").unwrap().to_string()),
+            model: match node.attribute_node("model") {
+                Some(model) => (oyarn!("{}", model.value()), model.range_value()),
+                None => (oyarn!(""), node.range()),
+            },
+            search: node.attribute_node("search").map(|search| (oyarn!("{}", search.value()), search.range_value())),
         });
         self.on_operation_creation(session, found_id, node, data, diagnostics);
         true
@@ -518,12 +641,10 @@ impl XmlArchBuilder {
         let mut has_eval = false;
         for attr in node.attributes() {
             match attr.name() {
-                "model" | "name" => {},
-                "uid" => {},
-                "context" => {},
                 "eval" => {
                     has_eval = true;
                 }
+                name if FUNCTION_ATTRIBUTES.contains(&name) => {},
                 _ => {
                     if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05046, &[attr.name()]) {
                         diagnostics.push(Diagnostic {
@@ -535,7 +656,8 @@ impl XmlArchBuilder {
             }
         }
         for child in node.children().filter(|n| n.is_element()) {
-            if self.load_value(session, &child, diagnostics) {
+            let tag_allowed = FUNCTION_CHILDREN.contains(&child.tag_name().name());
+            if tag_allowed && self.load_value(session, &child, diagnostics) {
                 if has_eval {
                     if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05045, &[]) {
                         diagnostics.push(Diagnostic {
@@ -544,7 +666,7 @@ impl XmlArchBuilder {
                         });
                     }
                 }
-            } else if self.load_function(session, &child, diagnostics) {
+            } else if tag_allowed && self.load_function(session, &child, diagnostics) {
                 if has_eval {
                     if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05047, &[]) {
                         diagnostics.push(Diagnostic {
@@ -18,6 +18,9 @@ pub struct OdooDataRecord {
     pub xml_id: Option<OYarn>,
     pub fields: Vec<OdooDataField>,
     pub range: Range<usize>,
+    pub qweb: XmlDataQWeb, //only filled for ir.ui.view records
+    pub arch: Vec<XmlDataArchNode>, //only filled for ir.ui.view records
+    pub expression_xml_ids: Vec<(OYarn, Range<usize>)>, //ref('xml_id') in eval and %(xml_id)d in other attributes
 }
 
 #[derive(Debug, Clone)]
@@ -26,6 +29,9 @@ pub struct OdooDataField {
     pub range: Range<usize>,
     pub text: Option<String>,
     pub text_range: Option<Range<usize>>,
+    pub typ: Option<OYarn>, //type attribute
+    pub ref_id: Option<(OYarn, Range<usize>)>,
+    pub eval: Option<(String, Range<usize>)>,
 }
 
 #[derive(Debug, Clone)]
@@ -33,6 +39,8 @@ pub struct XmlDataMenuItem {
     pub file_symbol: Weak<RefCell<Symbol>>,
     pub xml_id: Option<OYarn>,
     pub range: Range<usize>,
+    pub action: Option<(OYarn, Range<usize>)>,
+    pub parent: Option<(OYarn, Range<usize>)>, //for submenus, the id of the enclosing menuitem and the range of the submenu
 }
 
 #[derive(Debug, Clone)]
@@ -40,14 +48,60 @@ pub struct XmlDataTemplate {
     pub file_symbol: Weak<RefCell<Symbol>>,
     pub xml_id: Option<OYarn>,
     pub range: Range<usize>,
+    pub qweb: XmlDataQWeb,
 }
 
+/* Templates referenced by a QWeb template or view. Ranges are the ones of the attribute values */
+#[derive(Debug, Clone, Default)]
+pub struct XmlDataQWeb {
+    pub t_calls: Vec<(OYarn, Range<usize>)>,
+    pub inherits: Vec<(OYarn, Range<usize>)>, //inherit_id and t-inherit
+    pub inherit_modes: Vec<(OYarn, Range<usize>)>,
+}
+
+/* Node of a view arch that refers to the model of the view (field, groupby, button, or any node with a python
+expression in its attributes) or that locates a node of the inherited view (xpath, or any node with a position).
+Other nodes are not kept and their children are lifted */
+#[derive(Debug, Clone)]
+pub struct XmlDataArchNode {
+    pub tag: OYarn,
+    pub attributes: Vec<(OYarn, OYarn, Range<usize>)>, //name, value and range of the value, for name, type, position, expr and ARCH_EXPRESSION_ATTRIBUTES only
+    pub children: Vec<XmlDataArchNode>,
+}
+
+/* Attributes of the nodes of a view arch that contain a python expression */
+pub const ARCH_EXPRESSION_ATTRIBUTES: [&str; 7] = ["context", "domain", "invisible", "readonly", "required", "column_invisible", "options"];
+
 #[derive(Debug, Clone)]
 pub struct XmlDataDelete {
     pub file_symbol: Weak<RefCell<Symbol>>,
-    pub xml_id: Option<OYarn>,
+    pub xml_id: Option<(OYarn, Range<usize>)>,
     pub range: Range<usize>,
-    pub model: OYarn,
+    pub model: (OYarn, Range<usize>),
+    pub search: Option<(OYarn, Range<usize>)>,
+}
+
+impl XmlDataArchNode {
+
+    pub fn get_attribute(&self, name: &str) -> Option<(&OYarn, &Range<usize>)> {
+        self.attributes.iter().find(|(attr, _, _)| attr.as_str() == name).map(|(_, value, range)| (value, range))
+    }
+
+    /* Return false if the nodes added by an xpath could be in the sub-view of a relational field, and so
+    not be related to the model of the view. */
+    pub fn xpath_keeps_model(expr: &str, position: Option<&str>) -> bool {
+        let steps = expr.split('/').filter(|s| !s.is_empty()).collect::<Vec<&str>>();
+        for (index, step) in steps.iter().enumerate() {
+            let is_last = index + 1 == steps.len();
+            if step.starts_with("field") && (!is_last || position == Some("inside")) {
+                return false;
+            }
+            if index > 0 && ["list", "tree", "kanban", "form"].iter().any(|tag| step.starts_with(tag)) {
+                return false;
+            }
+        }
+        true
+    }
 }
 
 impl OdooData {
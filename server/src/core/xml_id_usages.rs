@@ -0,0 +1,278 @@
+use std::{cell::RefCell, ops::Range, rc::Rc};
+
+use roxmltree::Node;
+use ruff_python_ast::{visitor::{walk_expr, Visitor}, Expr, Stmt};
+use ruff_text_size::Ranged;
+use weak_table::PtrWeakHashSet;
+
+use crate::{constants::OYarn, core::{csv_arch_builder::CsvArchBuilder, symbols::symbol::Symbol}, oyarn, threads::SessionInfo};
+
+/* An xml_id as written in a file ("module.xml_id" or "xml_id"), and the byte range of this text */
+#[derive(Debug, Clone)]
+pub struct XmlIdOccurrence {
+    pub xml_id: String,
+    pub range: Range<usize>,
+    pub is_declaration: bool,
+}
+
+impl XmlIdOccurrence {
+
+    /* range of the xml_id without the "module." part */
+    pub fn id_range(&self) -> Range<usize> {
+        match self.xml_id.rfind('.') {
+            Some(index) => Range { start: self.range.start + index + 1, end: self.range.end },
+            None => self.range.clone()
+        }
+    }
+}
+
+/*
+Collect declarations and usages of xml_ids in data and python files, and maintain the reverse index of usages
+(ModuleSymbol::xml_id_usages), that is the counterpart of ModuleSymbol::xml_id_locations.
+*/
+pub struct XmlIdUsages {}
+
+impl XmlIdUsages {
+
+    /* Return the module name and the xml_id without module part, as it would be resolved from the given file */
+    pub fn resolve(from_file: &Rc<RefCell<Symbol>>, xml_id: &str) -> Option<(OYarn, OYarn)> {
+        let id_split = xml_id.split(".").collect::<Vec<&str>>();
+        match id_split.len() {
+            1 => {
+                let module = from_file.borrow().find_module()?;
+                let module_name = module.borrow().name().clone();
+                Some((module_name, oyarn!("{}", id_split[0])))
+            },
+            2 => Some((oyarn!("{}", id_split[0]), oyarn!("{}", id_split[1]))),
+            _ => None
+        }
+    }
+
+    /* Register from_file as a file using xml_id */
+    pub fn add_usage(session: &mut SessionInfo, from_file: &Rc<RefCell<Symbol>>, xml_id: &str) {
+        if !from_file.borrow().get_entry().map(|e| e.borrow().is_main()).unwrap_or(false) {
+            return;
+        }
+        let Some((module_name, id)) = XmlIdUsages::resolve(from_file, xml_id) else {
+            return;
+        };
+        if id.is_empty() {
+            return;
+        }
+        let Some(module) = session.sync_odoo.modules.get(&module_name).and_then(|m| m.upgrade()) else {
+            return;
+        };
+        module.borrow_mut().as_module_package_mut().xml_id_usages.entry(id.clone()).or_insert(PtrWeakHashSet::new()).insert(from_file.clone());
+        session.sync_odoo.xml_ids_used_by_files.entry(from_file.clone()).or_insert(vec![]).push((module_name, id));
+    }
+
+    /* Forget all usages registered by a file. Must be called before its content is loaded again, or when it is unloaded */
+    pub fn remove_usages(session: &mut SessionInfo, file: &Rc<RefCell<Symbol>>) {
+        let Some(usages) = session.sync_odoo.xml_ids_used_by_files.remove(file) else {
+            return;
+        };
+        for (module_name, id) in usages.iter() {
+            let Some(module) = session.sync_odoo.modules.get(module_name).and_then(|m| m.upgrade()) else {
+                continue;
+            };
+            let mut module = module.borrow_mut();
+            let xml_id_usages = &mut module.as_module_package_mut().xml_id_usages;
+            if let Some(files) = xml_id_usages.get_mut(id) {
+                files.remove(file);
+                if files.is_empty() {
+                    xml_id_usages.remove(id);
+                }
+            }
+        }
+    }
+
+    pub fn collect_xml(root: &Node) -> Vec<XmlIdOccurrence> {
+        let mut occurrences = vec![];
+        XmlIdUsages::visit_xml_node(root, false, &mut occurrences);
+        occurrences
+    }
+
+    fn visit_xml_node(node: &Node, in_arch: bool, occurrences: &mut Vec<XmlIdOccurrence>) {
+        if !node.is_element() {
+            return;
+        }
+        let source = node.document().input_text();
+        let tag = node.tag_name().name();
+        for attr in node.attributes() {
+            let range = attr.range_value();
+            let raw = &source[range.clone()];
+            match (attr.name(), in_arch) {
+                ("id", false) if ["record", "menuitem", "template", "report", "act_window"].contains(&tag) => {
+                    XmlIdUsages::push_occurrence(occurrences, raw, range.start, true);
+                },
+                ("id", false) if tag == "delete" => {
+                    XmlIdUsages::push_occurrence(occurrences, raw, range.start, false);
+                },
+                ("ref", false) | ("parent", false) | ("action", false) | ("inherit_id", false) => {
+                    XmlIdUsages::push_occurrence(occurrences, raw, range.start, false);
+                },
+                ("groups", _) => {
+                    XmlIdUsages::push_list(occurrences, raw, range.start);
+                },
+                ("t-call", true) | ("t-inherit", true) => {
+                    XmlIdUsages::push_occurrence(occurrences, raw, range.start, false);
+                },
+                _ => {
+                    XmlIdUsages::push_expression(occurrences, attr.name(), raw, range.start);
+                }
+            }
+        }
+        //content of views and templates are not data nodes anymore
+        let child_in_arch = in_arch || tag == "template" || (tag == "field" && matches!(node.attribute("type"), Some("xml") | Some("html")));
+        for child in node.children() {
+            XmlIdUsages::visit_xml_node(&child, child_in_arch, occurrences);
+        }
+    }
+
+    /* Collect the xml_ids written in the python expressions of the attributes of a node and its descendants */
+    pub fn collect_expressions(node: &Node) -> Vec<XmlIdOccurrence> {
+        let mut occurrences = vec![];
+        let source = node.document().input_text();
+        for descendant in node.descendants().filter(|n| n.is_element()) {
+            for attr in descendant.attributes() {
+                let range = attr.range_value();
+                XmlIdUsages::push_expression(&mut occurrences, attr.name(), &source[range.clone()], range.start);
+            }
+        }
+        occurrences
+    }
+
+    /* ref('xml_id') in eval attributes, %(xml_id)d in the other ones (button names, contexts, ...) */
+    pub fn push_expression(occurrences: &mut Vec<XmlIdOccurrence>, attribute: &str, raw: &str, start: usize) {
+        if attribute == "eval" {
+            XmlIdUsages::push_pattern(occurrences, raw, start, "ref(", ")");
+        } else {
+            XmlIdUsages::push_pattern(occurrences, raw, start, "%(", ")d");
+        }
+    }
+
+    /* Collect xml_ids of the id column and of the columns ending with ":id" or "/id" */
+    pub fn collect_csv(content: &str) -> Vec<XmlIdOccurrence> {
+        let mut occurrences = vec![];
+        let mut rdr = csv::Reader::from_reader(content.as_bytes());
+        let Ok(headers) = rdr.headers() else {
+            return occurrences;
+        };
+        let columns = headers.iter().map(|h| {
+            if h == "id" {
+                Some(true)
+            } else if h.ends_with(":id") || h.ends_with("/id") {
+                Some(false)
+            } else {
+                None
+            }
+        }).collect::<Vec<_>>();
+        if columns.iter().all(|c| c.is_none()) {
+            return occurrences;
+        }
+        for record in rdr.records() {
+            let Ok(record) = record else {
+                continue;
+            };
+            if record.position().is_none() {
+                continue;
+            }
+            let ranges = CsvArchBuilder::get_value_ranges(content, &record);
+            for (index, (value, range)) in record.iter().zip(ranges).enumerate() {
+                if range.len() != value.len() {
+                    continue;
+                }
+                match columns.get(index) {
+                    Some(Some(true)) => XmlIdUsages::push_occurrence(&mut occurrences, value, range.start, true),
+                    Some(Some(false)) => XmlIdUsages::push_list(&mut occurrences, value, range.start),
+                    _ => {}
+                }
+            }
+        }
+        occurrences
+    }
+
+    /* Collect the string arguments of "ref" calls (env.ref("module.xml_id"), self.ref(...)) */
+    pub fn collect_python(stmts: &Vec<Stmt>) -> Vec<XmlIdOccurrence> {
+        let mut visitor = RefCallVisitor { occurrences: vec![] };
+        for stmt in stmts.iter() {
+            visitor.visit_stmt(stmt);
+        }
+        visitor.occurrences
+    }
+
+    fn push_occurrence(occurrences: &mut Vec<XmlIdOccurrence>, raw: &str, start: usize, is_declaration: bool) {
+        let trimmed = raw.trim_start();
+        let start = start + raw.len() - trimmed.len();
+        let trimmed = trimmed.trim_end();
+        if trimmed.is_empty() || trimmed.contains(|c: char| c.is_whitespace() || c == '\'' || c == '"') {
+            return;
+        }
+        occurrences.push(XmlIdOccurrence {
+            xml_id: trimmed.to_string(),
+            range: Range { start, end: start + trimmed.len() },
+            is_declaration,
+        });
+    }
+
+    /* comma separated list of xml_ids, that can be prefixed by '-' or '!' */
+    fn push_list(occurrences: &mut Vec<XmlIdOccurrence>, raw: &str, start: usize) {
+        let mut offset = start;
+        for part in raw.split(',') {
+            let trimmed = part.trim_start_matches(|c: char| c.is_whitespace() || c == '-' || c == '!');
+            XmlIdUsages::push_occurrence(occurrences, trimmed, offset + part.len() - trimmed.len(), false);
+            offset += part.len() + 1;
+        }
+    }
+
+    /* xml_ids between the prefix and the suffix, like "ref('xml_id')" or "%(xml_id)d" */
+    fn push_pattern(occurrences: &mut Vec<XmlIdOccurrence>, raw: &str, start: usize, prefix: &str, suffix: &str) {
+        let mut search_from = 0;
+        while let Some(index) = raw[search_from..].find(prefix) {
+            let begin = search_from + index;
+            search_from = begin + prefix.len();
+            let is_word_prefix = raw[..begin].chars().last().map(|c| c.is_alphanumeric() || c == '_' || c == '.').unwrap_or(false);
+            if prefix == "ref(" && is_word_prefix {
+                continue;
+            }
+            let Some(end) = raw[search_from..].find(suffix).map(|i| i + search_from) else {
+                break;
+            };
+            let content = &raw[search_from..end];
+            let unquoted = content.trim().trim_matches(|c| c == '\'' || c == '"');
+            if prefix == "ref(" && unquoted.len() == content.trim().len() {
+                continue; //not a string literal
+            }
+            if let Some(index) = content.find(unquoted) {
+                XmlIdUsages::push_occurrence(occurrences, unquoted, start + search_from + index, false);
+            }
+            search_from = end + suffix.len();
+        }
+    }
+}
+
+struct RefCallVisitor {
+    occurrences: Vec<XmlIdOccurrence>,
+}
+
+impl<'a> Visitor<'a> for RefCallVisitor {
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let Expr::Call(call) = expr {
+            if let Expr::Attribute(attr) = call.func.as_ref() {
+                if attr.attr.id.as_str() == "ref" {
+                    if let Some(Expr::StringLiteral(string)) = call.arguments.args.first() {
+                        let value = string.value.to_str();
+                        let range = string.range();
+                        //skip the quotes
+                        let start = range.start().to_usize() + 1;
+                        if range.len().to_usize() == value.len() + 2 {
+                            XmlIdUsages::push_occurrence(&mut self.occurrences, value, start, false);
+                        }
+                    }
+                }
+            }
+        }
+        walk_expr(self, expr);
+    }
+}
@@ -1,11 +1,22 @@
 use std::{cell::RefCell, cmp::Ordering, collections::{HashMap, HashSet}, rc::Rc};
 
 use lsp_types::{Diagnostic, Position, Range};
+use ruff_python_ast::{visitor::{walk_expr, walk_parameter, Visitor}, Expr, ExprContext, ExprName, Mod, Parameter};
+use ruff_python_parser::{Mode, ParseOptions};
 use tracing::{info, trace};
 
-use crate::{constants::{BuildSteps, OYarn, DEBUG_STEPS}, core::{diagnostics::{create_diagnostic, DiagnosticCode}, entry_point::{EntryPoint, EntryPointType}, evaluation::ContextValue, file_mgr::FileInfo, model::Model, odoo::SyncOdoo, symbols::symbol::Symbol, xml_data::{OdooData, XmlDataDelete, XmlDataMenuItem, OdooDataRecord, XmlDataTemplate}}, oyarn, threads::SessionInfo, utils::compare_semver, Sy};
+use crate::{constants::{BuildSteps, OYarn, SymType, DEBUG_STEPS}, core::{diagnostics::{create_diagnostic, DiagnosticCode}, entry_point::{EntryPoint, EntryPointType}, evaluation::{Context, ContextValue, Evaluation}, file_mgr::FileInfo, model::Model, odoo::SyncOdoo, symbols::{module_symbol::ModuleSymbol, symbol::Symbol}, xml_data::{OdooData, OdooDataField, XmlDataArchNode, ARCH_EXPRESSION_ATTRIBUTES, XmlDataDelete, XmlDataMenuItem, OdooDataRecord, XmlDataQWeb, XmlDataTemplate}, xml_id_usages::XmlIdUsages, xpath::check_xpath_syntax}, oyarn, threads::SessionInfo, utils::compare_semver, Sy};
 
 
+/* Names available in the eval attributes of data files, with the python builtins */
+const DATA_EVAL_CONTEXT: [&str; 12] = ["ref", "obj", "time", "DateTime", "datetime", "timedelta", "relativedelta", "version", "pytz", "Command", "uid", "context_today"];
+/* Names available in the expressions of a view arch, evaluated by the web client, in addition to the fields of the record */
+const ARCH_EVAL_CONTEXT: [&str; 17] = ["context", "uid", "parent", "id", "active_id", "active_ids", "active_model", "current_date", "today", "now",
+    "current_company_id", "allowed_company_ids", "companies", "context_today", "time", "datetime", "relativedelta"];
+/* Builtins allowed by safe_eval (_BUILTINS of odoo.tools.safe_eval) */
+const PYTHON_BUILTINS: [&str; 36] = ["True", "False", "None", "bytes", "str", "unicode", "bool", "int", "float", "enumerate", "dict", "list",
+    "tuple", "map", "abs", "min", "max", "sum", "reduce", "filter", "sorted", "round", "len", "repr", "set", "all", "any", "ord", "chr",
+    "divmod", "isinstance", "range", "xrange", "zip", "Exception", "datetime"];
 
 pub struct XmlValidator {
     pub xml_symbol: Rc<RefCell<Symbol>>,
@@ -38,7 +49,12 @@ impl XmlValidator {
         let mut model_dependencies = vec![];
         let mut missing_model_dependencies = HashSet::new();
         let mut diagnostics = vec![];
-        for xml_ids in self.xml_symbol.borrow().as_xml_file_sym().xml_ids.values() {
+        let xml_ids = if self.xml_symbol.borrow().typ() == SymType::CSV_FILE {
+            self.xml_symbol.borrow().as_csv_file_sym().xml_ids.clone()
+        } else {
+            self.xml_symbol.borrow().as_xml_file_sym().xml_ids.clone()
+        };
+        for xml_ids in xml_ids.values() {
             for xml_id in xml_ids.iter() {
                 self.validate_xml_id(session, &module, xml_id, &mut diagnostics, &mut dependencies, &mut model_dependencies, &mut missing_model_dependencies);
             }
@@ -52,14 +68,16 @@ impl XmlValidator {
         if !missing_model_dependencies.is_empty() {
             session.sync_odoo.get_main_entry().borrow_mut().not_found_symbols_for_models.insert(self.xml_symbol.clone());
         }
-        self.xml_symbol.borrow_mut().as_xml_file_sym_mut().not_found_models.extend(missing_model_dependencies.into_iter().map(|m| (m, BuildSteps::VALIDATION)));
+        if let Some(not_found_models) = self.xml_symbol.borrow_mut().not_found_models_mut() {
+            not_found_models.extend(missing_model_dependencies.into_iter().map(|m| (m, BuildSteps::VALIDATION)));
+        }
         let file_info = self.get_file_info(&mut session.sync_odoo);
         file_info.borrow_mut().replace_diagnostics(BuildSteps::VALIDATION, diagnostics);
         file_info.borrow_mut().publish_diagnostics(session);
     }
 
     pub fn validate_xml_id(&self, session: &mut SessionInfo, module: &Rc<RefCell<Symbol>>, data: &OdooData, diagnostics: &mut Vec<Diagnostic>, dependencies: &mut Vec<Rc<RefCell<Symbol>>>, model_dependencies: &mut Vec<Rc<RefCell<Model>>>, missing_model_dependencies: &mut HashSet<OYarn>) {
-        let Some(_) = data.get_xml_file_symbol() else {
+        let Some(_) = data.get_file_symbol().and_then(|f| f.upgrade()) else {
             return;
         };
         match data {
@@ -70,6 +88,9 @@ impl XmlValidator {
         }
     }
     fn validate_record(&self, session: &mut SessionInfo, module: &Rc<RefCell<Symbol>>, xml_data_record: &OdooDataRecord, diagnostics: &mut Vec<Diagnostic>, dependencies: &mut Vec<Rc<RefCell<Symbol>>>, model_dependencies: &mut Vec<Rc<RefCell<Model>>>, missing_model_dependencies: &mut HashSet<OYarn>) {
+        self.validate_qweb(session, module, &xml_data_record.qweb, diagnostics, dependencies);
+        self.validate_evals(session, xml_data_record, diagnostics);
+        self.validate_expression_xml_ids(session, module, xml_data_record, diagnostics, dependencies);
         let maybe_model = session.sync_odoo.models.get(&xml_data_record.model.0).cloned();
         let model_exists = maybe_model.as_ref().map(|m| m.borrow_mut().has_symbols()).unwrap_or(false);
         if !model_exists {
@@ -77,6 +98,7 @@ impl XmlValidator {
             if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05055, &[&xml_data_record.model.0, module.borrow().name()]) {
                 diagnostics.push(Diagnostic {
                     range: Range { start: Position::new(xml_data_record.model.1.start.try_into().unwrap(), 0), end: Position::new(xml_data_record.model.1.end.try_into().unwrap(), 0) },
+                    data: Some(serde_json::json!({"model": xml_data_record.model.0.as_str()})),
                     ..diagnostic.clone()
                 });
             }
@@ -92,32 +114,420 @@ impl XmlValidator {
         let Some(main_symbol) = main_symbols.get(0) else { return; };
         let all_fields = Symbol::all_fields(main_symbol, session, Some(module.clone()));
         self.validate_fields(session, xml_data_record, &all_fields, diagnostics, missing_model_dependencies);
+        self.validate_required_fields(session, module, &model, xml_data_record, &all_fields, diagnostics, dependencies);
+        if xml_data_record.model.0 == "ir.ui.view" {
+            self.validate_arch(session, module, xml_data_record, diagnostics, dependencies, model_dependencies);
+            self.validate_inheritance(session, module, xml_data_record, diagnostics);
+        }
     }
 
-    fn validate_fields(&self, session: &mut SessionInfo, xml_data_record: &OdooDataRecord, all_fields: &HashMap<OYarn, Vec<(Rc<RefCell<Symbol>>, Option<OYarn>)>>, diagnostics: &mut Vec<Diagnostic>, missing_model_dependencies: &mut HashSet<OYarn>) {
-        //Compute mandatory fields
-        let mut mandatory_fields: Vec<String> = vec![];
-        for (field_name, field_sym) in all_fields.iter() {
-            for (fs, deps) in field_sym.iter() {
-                if deps.is_none() {
-                    let has_required = fs.borrow().evaluations().unwrap_or(&vec![]).iter()
-                    .any(|eval|
-                        eval.symbol.get_symbol_as_weak(session, &mut None, diagnostics, None)
-                        .context.get("required").unwrap_or(&ContextValue::BOOLEAN(false)).as_bool()
-                    );
-                    let has_default = fs.borrow().evaluations().unwrap_or(&vec![]).iter()
-                    .any(|eval|
-                        eval.symbol.get_symbol_as_weak(session, &mut None, diagnostics, None)
-                        .context.contains_key("default")
-                    );
-                    if has_required && !has_default {
-                        mandatory_fields.push(field_name.to_string());
+    /* Check the specs of a view inheriting another one: positions, xpath expressions and field locators */
+    fn validate_inheritance(&self, session: &mut SessionInfo, module: &Rc<RefCell<Symbol>>, xml_data_record: &OdooDataRecord, diagnostics: &mut Vec<Diagnostic>) {
+        let Some((inherit_id, _)) = xml_data_record.qweb.inherits.first() else {
+            return;
+        };
+        if !self.is_in_main_ep {
+            return;
+        }
+        self.validate_specs(session, &xml_data_record.arch, diagnostics);
+        let Some(parent_view) = XmlIdUsages::resolve(&self.xml_symbol, inherit_id) else {
+            return;
+        };
+        let own_id = xml_data_record.xml_id.as_ref().and_then(|id| XmlIdUsages::resolve(&self.xml_symbol, id));
+        let Some(parent_fields) = XmlValidator::get_inherited_arch_fields(session, module, parent_view, own_id) else {
+            return; //parent view not found, already reported by validate_qweb
+        };
+        //top level fields of an inheriting view are locators, whatever their position is
+        for node in xml_data_record.arch.iter().filter(|n| n.tag == "field") {
+            let Some((name, range)) = node.get_attribute("name") else {
+                continue;
+            };
+            if !parent_fields.contains(name) {
+                if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05064, &[name, inherit_id]) {
+                    diagnostics.push(Diagnostic {
+                        range: Range { start: Position::new(range.start as u32, 0), end: Position::new(range.end as u32, 0) },
+                        ..diagnostic.clone()
+                    });
+                }
+            }
+        }
+    }
+
+    fn validate_specs(&self, session: &mut SessionInfo, nodes: &[XmlDataArchNode], diagnostics: &mut Vec<Diagnostic>) {
+        for node in nodes.iter() {
+            if let Some((position, range)) = node.get_attribute("position") {
+                if !["before", "after", "inside", "replace", "attributes", "move"].contains(&position.as_str()) {
+                    if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05062, &[position]) {
+                        diagnostics.push(Diagnostic {
+                            range: Range { start: Position::new(range.start as u32, 0), end: Position::new(range.end as u32, 0) },
+                            ..diagnostic.clone()
+                        });
                     }
                 }
             }
+            if node.tag == "xpath" {
+                if let Some((expr, range)) = node.get_attribute("expr") {
+                    if let Err(error) = check_xpath_syntax(expr) {
+                        if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05063, &[expr, &error]) {
+                            diagnostics.push(Diagnostic {
+                                range: Range { start: Position::new(range.start as u32, 0), end: Position::new(range.end as u32, 0) },
+                                ..diagnostic.clone()
+                            });
+                        }
+                    }
+                }
+            }
+            self.validate_specs(session, &node.children, diagnostics);
+        }
+    }
+
+    /* Names of the fields available in the arch of the inherited view: the ones of the view, of the views it inherits, and
+    of the other views extending them in the dependencies of the module. Return None if the inherited view is not found */
+    fn get_inherited_arch_fields(session: &mut SessionInfo, module: &Rc<RefCell<Symbol>>, root: (OYarn, OYarn), own_id: Option<(OYarn, OYarn)>) -> Option<HashSet<OYarn>> {
+        let mut fields = HashSet::new();
+        let mut found = false;
+        let mut visited = HashSet::new();
+        visited.extend(own_id);
+        let mut queue = vec![root.clone()];
+        while let Some((module_name, xml_id)) = queue.pop() {
+            if !visited.insert((module_name.clone(), xml_id.clone())) || !ModuleSymbol::is_in_deps(session, module, &module_name) {
+                continue;
+            }
+            let Some(view_module) = session.sync_odoo.modules.get(&module_name).and_then(|m| m.upgrade()) else {
+                continue;
+            };
+            for data in view_module.borrow().as_module_package().get_xml_id(&xml_id) {
+                let OdooData::RECORD(view) = data else {
+                    continue;
+                };
+                if view.model.0 != "ir.ui.view" {
+                    continue;
+                }
+                found |= (module_name.clone(), xml_id.clone()) == root;
+                XmlValidator::collect_arch_fields(&view.arch, view.qweb.inherits.is_empty(), &mut fields);
+                let Some(view_file) = view.file_symbol.upgrade() else {
+                    continue;
+                };
+                queue.extend(view.qweb.inherits.iter().filter_map(|(parent_id, _)| XmlIdUsages::resolve(&view_file, parent_id)));
+            }
+            let users = view_module.borrow().as_module_package().xml_id_usages.get(&xml_id).map(|set| set.iter().collect::<Vec<_>>()).unwrap_or_default();
+            for file in users.iter().filter(|f| f.borrow().typ() == SymType::XML_FILE) {
+                for data in file.borrow().as_xml_file_sym().xml_ids.values().flatten() {
+                    let OdooData::RECORD(view) = data else {
+                        continue;
+                    };
+                    let extends_view = view.qweb.inherits.iter().any(|(parent_id, _)| XmlIdUsages::resolve(file, parent_id) == Some((module_name.clone(), xml_id.clone())));
+                    if let (true, Some(view_id)) = (extends_view, view.xml_id.as_ref()) {
+                        queue.extend(XmlIdUsages::resolve(file, view_id));
+                    }
+                }
+            }
+        }
+        found.then_some(fields)
+    }
+
+    /* Top level fields of an inheriting view are locators, and are only collected if include_top_level is set */
+    fn collect_arch_fields(nodes: &[XmlDataArchNode], include_top_level: bool, fields: &mut HashSet<OYarn>) {
+        for node in nodes.iter() {
+            if node.tag == "field" && include_top_level {
+                if let Some((name, _)) = node.get_attribute("name") {
+                    fields.insert(name.clone());
+                }
+            }
+            XmlValidator::collect_arch_fields(&node.children, true, fields);
+        }
+    }
+
+    /* Check the fields and the buttons of a view arch against the model of the view */
+    fn validate_arch(&self, session: &mut SessionInfo, module: &Rc<RefCell<Symbol>>, xml_data_record: &OdooDataRecord, diagnostics: &mut Vec<Diagnostic>, dependencies: &mut Vec<Rc<RefCell<Symbol>>>, model_dependencies: &mut Vec<Rc<RefCell<Model>>>) {
+        if !self.is_in_main_ep || xml_data_record.arch.is_empty() {
+            return;
+        }
+        let is_qweb = xml_data_record.fields.iter().any(|f| f.name == "type" && f.text.as_ref().map(|t| t.trim() == "qweb").unwrap_or(false));
+        if is_qweb {
+            return;
+        }
+        //without model, only the syntax of the expressions is checked
+        let main_symbol = XmlValidator::get_view_model(session, xml_data_record, 0)
+            .and_then(|model_name| self.get_model_main_symbol(session, module, &model_name, dependencies, model_dependencies));
+        self.validate_arch_nodes(session, module, &xml_data_record.arch, main_symbol, diagnostics, dependencies, model_dependencies);
+    }
+
+    fn validate_arch_nodes(&self, session: &mut SessionInfo, module: &Rc<RefCell<Symbol>>, nodes: &[XmlDataArchNode], main_symbol: Option<Rc<RefCell<Symbol>>>, diagnostics: &mut Vec<Diagnostic>, dependencies: &mut Vec<Rc<RefCell<Symbol>>>, model_dependencies: &mut Vec<Rc<RefCell<Model>>>) {
+        for node in nodes.iter() {
+            let mut children_symbol = main_symbol.clone();
+            let mut domain_symbol = main_symbol.clone();
+            let position = node.get_attribute("position").map(|(p, _)| p.as_str());
+            match (node.tag.as_str(), node.get_attribute("name"), main_symbol.as_ref()) {
+                ("field", Some((name, range)), Some(symbol)) | ("groupby", Some((name, range)), Some(symbol)) => {
+                    let fields = XmlValidator::get_model_members(session, Some(module.clone()), symbol, name, false);
+                    if fields.is_empty() {
+                        self.push_arch_diagnostic(session, DiagnosticCode::OLS05057, name, symbol, range, diagnostics);
+                    }
+                    let comodel_symbol = XmlValidator::get_comodel_symbol(session, Some(module.clone()), &fields);
+                    if let Some(comodel_symbol) = comodel_symbol.as_ref() {
+                        if let Some(file) = comodel_symbol.borrow().get_file().and_then(|f| f.upgrade()) {
+                            dependencies.push(file);
+                        }
+                    }
+                    //the domain of a field filters its comodel
+                    domain_symbol = comodel_symbol.clone();
+                    //a locator of an inherited view adds nodes next to the field, or inside its sub-views
+                    if position.is_none() || position == Some("inside") {
+                        children_symbol = comodel_symbol;
+                    }
+                },
+                ("button", Some((name, range)), Some(symbol)) => {
+                    let is_object = node.get_attribute("type").map(|(t, _)| t.as_str() == "object").unwrap_or(false);
+                    if is_object && XmlValidator::get_model_members(session, Some(module.clone()), symbol, name, true).is_empty() {
+                        self.push_arch_diagnostic(session, DiagnosticCode::OLS05061, name, symbol, range, diagnostics);
+                    }
+                },
+                ("xpath", _, _) => {
+                    let expr = node.get_attribute("expr").map(|(e, _)| e.as_str()).unwrap_or("");
+                    if !XmlDataArchNode::xpath_keeps_model(expr, position) {
+                        children_symbol = None;
+                    }
+                },
+                _ => {}
+            }
+            self.validate_arch_expressions(session, module, node, main_symbol.as_ref(), domain_symbol, diagnostics);
+            self.validate_arch_nodes(session, module, &node.children, children_symbol, diagnostics, dependencies, model_dependencies);
+        }
+    }
+
+    fn push_arch_diagnostic(&self, session: &mut SessionInfo, code: DiagnosticCode, name: &OYarn, main_symbol: &Rc<RefCell<Symbol>>, range: &std::ops::Range<usize>, diagnostics: &mut Vec<Diagnostic>) {
+        let model_name = main_symbol.borrow().as_class_sym()._model.as_ref().map(|m| m.name.to_string()).unwrap_or_default();
+        if let Some(diagnostic) = create_diagnostic(session, code, &[name, &model_name]) {
+            diagnostics.push(Diagnostic {
+                range: Range { start: Position::new(range.start as u32, 0), end: Position::new(range.end as u32, 0) },
+                ..diagnostic.clone()
+            });
+        }
+    }
+
+    /* Check the python expressions in the attributes of an arch node. Names must be fields of the model of the node
+    or names of the evaluation context of the web client. Domains are also checked against domain_symbol */
+    fn validate_arch_expressions(&self, session: &mut SessionInfo, module: &Rc<RefCell<Symbol>>, node: &XmlDataArchNode, main_symbol: Option<&Rc<RefCell<Symbol>>>, domain_symbol: Option<Rc<RefCell<Symbol>>>, diagnostics: &mut Vec<Diagnostic>) {
+        for (attribute, value, range) in node.attributes.iter() {
+            if !ARCH_EXPRESSION_ATTRIBUTES.contains(&attribute.as_str()) {
+                continue;
+            }
+            let Some((expr, offset)) = self.parse_attribute_expression(session, attribute, value, range, diagnostics) else {
+                continue;
+            };
+            if let Some(symbol) = main_symbol {
+                for (name, name_range) in XmlValidator::get_free_names(&expr) {
+                    if ARCH_EVAL_CONTEXT.contains(&name) || PYTHON_BUILTINS.contains(&name) {
+                        continue;
+                    }
+                    if XmlValidator::get_model_members(session, Some(module.clone()), symbol, name, false).is_empty() {
+                        let name_range = XmlValidator::get_expression_range(value, range, offset, name_range);
+                        self.push_arch_diagnostic(session, DiagnosticCode::OLS05073, &oyarn!("{}", name), symbol, &name_range, diagnostics);
+                    }
+                }
+            }
+            if attribute.as_str() == "domain" {
+                let Some(domain_symbol) = domain_symbol.as_ref() else {
+                    continue;
+                };
+                //ranges of the domain diagnostics are offsets in the expression
+                for diagnostic in Evaluation::validate_domain(session, Rc::downgrade(domain_symbol), Some(module.clone()), &expr) {
+                    let part = diagnostic.range.start.line as usize..diagnostic.range.end.line as usize;
+                    let domain_range = XmlValidator::get_expression_range(value, range, offset, part);
+                    diagnostics.push(Diagnostic {
+                        range: Range { start: Position::new(domain_range.start as u32, 0), end: Position::new(domain_range.end as u32, 0) },
+                        ..diagnostic
+                    });
+                }
+            }
         }
+    }
+
+    /* Check the python expressions of the eval attributes of the fields of a record */
+    fn validate_evals(&self, session: &mut SessionInfo, xml_data_record: &OdooDataRecord, diagnostics: &mut Vec<Diagnostic>) {
+        if !self.is_in_main_ep {
+            return;
+        }
+        for field in xml_data_record.fields.iter() {
+            let Some((eval, range)) = field.eval.as_ref() else {
+                continue;
+            };
+            let Some((expr, offset)) = self.parse_attribute_expression(session, "eval", eval, range, diagnostics) else {
+                continue;
+            };
+            for (name, name_range) in XmlValidator::get_free_names(&expr) {
+                if DATA_EVAL_CONTEXT.contains(&name) || PYTHON_BUILTINS.contains(&name) {
+                    continue;
+                }
+                let name_range = XmlValidator::get_expression_range(eval, range, offset, name_range);
+                if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05074, &[name]) {
+                    diagnostics.push(Diagnostic {
+                        range: Range { start: Position::new(name_range.start as u32, 0), end: Position::new(name_range.end as u32, 0) },
+                        ..diagnostic.clone()
+                    });
+                }
+            }
+        }
+    }
+
+    /* Check the xml_ids used by ref() in evals and by %(xml_id)d in the other attributes of a record */
+    fn validate_expression_xml_ids(&self, session: &mut SessionInfo, module: &Rc<RefCell<Symbol>>, xml_data_record: &OdooDataRecord, diagnostics: &mut Vec<Diagnostic>, dependencies: &mut Vec<Rc<RefCell<Symbol>>>) {
+        if !self.is_in_main_ep {
+            return;
+        }
+        for (xml_id, range) in xml_data_record.expression_xml_ids.iter() {
+            let code = match xml_id.split('.').collect::<Vec<&str>>().as_slice() {
+                [module_name, _] if !session.sync_odoo.modules.contains_key(*module_name) => DiagnosticCode::OLS05003,
+                [_] | [_, _] => {
+                    if !self.get_visible_xml_ids(session, module, xml_id, range, diagnostics, dependencies).unwrap_or_default().is_empty() {
+                        continue;
+                    }
+                    if !xml_id.contains('.') && XmlValidator::is_xml_id_of_other_module(session, module, xml_id) {
+                        DiagnosticCode::OLS05002
+                    } else {
+                        DiagnosticCode::OLS05001
+                    }
+                },
+                _ => {
+                    //invalid xml_id, reported by get_xml_ids
+                    self.get_visible_xml_ids(session, module, xml_id, range, diagnostics, dependencies);
+                    continue;
+                }
+            };
+            if let Some(diagnostic) = create_diagnostic(session, code, &[]) {
+                diagnostics.push(Diagnostic {
+                    range: Range { start: Position::new(range.start as u32, 0), end: Position::new(range.end as u32, 0) },
+                    ..diagnostic.clone()
+                });
+            }
+        }
+    }
+
+    /* True if an xml_id written without module is declared by another module than the current one */
+    fn is_xml_id_of_other_module(session: &mut SessionInfo, module: &Rc<RefCell<Symbol>>, xml_id: &str) -> bool {
+        let xml_id = oyarn!("{}", xml_id);
+        session.sync_odoo.modules.values().filter_map(|m| m.upgrade()).any(|other_module|
+            !Rc::ptr_eq(&other_module, module) && !other_module.borrow().as_module_package().get_xml_id(&xml_id).is_empty()
+        )
+    }
+
+    /* Parse the python expression in the value of an attribute. Return the expression and its offset in the value,
+    or None if the value is empty or invalid. Syntax errors are reported on the attribute */
+    fn parse_attribute_expression(&self, session: &mut SessionInfo, attribute: &str, value: &str, range: &std::ops::Range<usize>, diagnostics: &mut Vec<Diagnostic>) -> Option<(Expr, usize)> {
+        let expression = value.trim_start();
+        if expression.trim_end().is_empty() {
+            return None;
+        }
+        let offset = value.len() - expression.len();
+        let parsed = ruff_python_parser::parse_unchecked(expression, ParseOptions::from(Mode::Expression));
+        if let Some(error) = parsed.errors().first() {
+            let error_range = XmlValidator::get_expression_range(value, range, offset, error.location.start().to_usize()..error.location.end().to_usize());
+            if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05072, &[attribute, &error.error.to_string()]) {
+                diagnostics.push(Diagnostic {
+                    range: Range { start: Position::new(error_range.start as u32, 0), end: Position::new(error_range.end as u32, 0) },
+                    ..diagnostic.clone()
+                });
+            }
+            return None;
+        }
+        let Mod::Expression(expression) = parsed.into_syntax() else {
+            return None;
+        };
+        Some((*expression.body, offset))
+    }
+
+    /* Range in the file of a part of an attribute value. Entities (&lt;, &quot;...) make the value shorter than
+    its source, so the range of the whole value is returned in this case */
+    fn get_expression_range(value: &str, range: &std::ops::Range<usize>, offset: usize, part: std::ops::Range<usize>) -> std::ops::Range<usize> {
+        if range.len() != value.len() {
+            return range.clone();
+        }
+        range.start + offset + part.start..range.start + offset + part.end
+    }
+
+    /* Names read by an expression that are not bound by a comprehension or a lambda in it, with their ranges */
+    fn get_free_names(expr: &Expr) -> Vec<(&str, std::ops::Range<usize>)> {
+        let mut visitor = FreeNameVisitor { loaded: vec![], bound: HashSet::new() };
+        visitor.visit_expr(expr);
+        visitor.loaded.into_iter()
+            .filter(|name| !visitor.bound.contains(name.id.as_str()))
+            .map(|name| (name.id.as_str(), name.range.start().to_usize()..name.range.end().to_usize()))
+            .collect()
+    }
+
+    fn get_model_main_symbol(&self, session: &mut SessionInfo, module: &Rc<RefCell<Symbol>>, model_name: &OYarn, dependencies: &mut Vec<Rc<RefCell<Symbol>>>, model_dependencies: &mut Vec<Rc<RefCell<Model>>>) -> Option<Rc<RefCell<Symbol>>> {
+        let model = session.sync_odoo.models.get(model_name).cloned()?;
+        model_dependencies.push(model.clone());
+        let main_symbols = model.borrow().get_main_symbols(session, Some(module.clone()));
+        let main_symbol = main_symbols.first()?;
+        if let Some(file) = main_symbol.borrow().get_file().and_then(|f| f.upgrade()) {
+            dependencies.push(file);
+        }
+        Some(main_symbol.clone())
+    }
+
+    /* Return the model of a view, given by its 'model' field or by the view it inherits */
+    pub fn get_view_model(session: &mut SessionInfo, xml_data_record: &OdooDataRecord, depth: u32) -> Option<OYarn> {
+        if let Some(model) = xml_data_record.fields.iter().find(|f| f.name == "model").and_then(|f| f.text.as_ref()) {
+            return Some(oyarn!("{}", model.trim()));
+        }
+        let from_module = xml_data_record.file_symbol.upgrade()?.borrow().find_module()?;
+        for (inherit_id, _) in xml_data_record.qweb.inherits.iter() {
+            if let Some(model) = XmlValidator::get_inherited_view_model(session, &from_module, inherit_id, depth) {
+                return Some(model);
+            }
+        }
+        None
+    }
+
+    pub fn get_inherited_view_model(session: &mut SessionInfo, from_module: &Rc<RefCell<Symbol>>, inherit_id: &str, depth: u32) -> Option<OYarn> {
+        if depth > 20 { //inheritance loop
+            return None;
+        }
+        let (module_name, xml_id) = match inherit_id.split_once('.') {
+            Some((module_name, xml_id)) => (oyarn!("{}", module_name), oyarn!("{}", xml_id)),
+            None => (from_module.borrow().name().clone(), oyarn!("{}", inherit_id)),
+        };
+        let module = session.sync_odoo.modules.get(&module_name).and_then(|m| m.upgrade())?;
+        let views = module.borrow().as_module_package().get_xml_id(&xml_id);
+        for view in views.iter() {
+            if let OdooData::RECORD(record) = view {
+                if record.model.0 == "ir.ui.view" {
+                    if let Some(model) = XmlValidator::get_view_model(session, record, depth + 1) {
+                        return Some(model);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /* Fields (or methods) of a model that can be used in a view arch */
+    pub fn get_model_members(session: &mut SessionInfo, from_module: Option<Rc<RefCell<Symbol>>>, main_symbol: &Rc<RefCell<Symbol>>, name: &str, methods: bool) -> Vec<Rc<RefCell<Symbol>>> {
+        let (symbols, _) = main_symbol.borrow().get_member_symbol(session, &name.to_string(), from_module, false, !methods, false, false);
+        let typ = if methods { SymType::FUNCTION } else { SymType::VARIABLE };
+        symbols.into_iter().filter(|s| s.borrow().typ() == typ).collect()
+    }
+
+    /* Main class of the comodel of relational fields, that is the model of their sub-views */
+    pub fn get_comodel_symbol(session: &mut SessionInfo, from_module: Option<Rc<RefCell<Symbol>>>, fields: &[Rc<RefCell<Symbol>>]) -> Option<Rc<RefCell<Symbol>>> {
+        for field in fields.iter() {
+            let comodels = field.borrow().as_variable().get_relational_model(session, from_module.clone());
+            if let Some(comodel) = comodels.first() {
+                return Some(comodel.clone());
+            }
+        }
+        None
+    }
+
+    fn validate_fields(&self, session: &mut SessionInfo, xml_data_record: &OdooDataRecord, all_fields: &HashMap<OYarn, Vec<(Rc<RefCell<Symbol>>, Option<OYarn>)>>, diagnostics: &mut Vec<Diagnostic>, missing_model_dependencies: &mut HashSet<OYarn>) {
+        let is_csv = self.xml_symbol.borrow().typ() == SymType::CSV_FILE;
         //check each field in the record
         for field in &xml_data_record.fields {
+            if is_csv && field.name == "id" {
+                continue;
+            }
             let mut field_name = Sy!(field.name.clone());
             let mut has_translation = false;
             if compare_semver(&session.sync_odoo.full_version, "18.2.0") >= Ordering::Equal {
@@ -129,8 +539,8 @@ impl XmlValidator {
                 }
             }
             //Check that the field belong to the model
-            if all_fields.contains_key(&field_name) {
-                mandatory_fields.retain(|f| f != &field_name.to_string());
+            if let Some(field_symbols) = all_fields.get(&field_name) {
+                self.validate_field_value(session, field, field_symbols, diagnostics);
                 //Check specific attributes
                 let (Some(field_text), Some(field_text_range)) = (field.text.as_ref(), field.text_range.as_ref()) else {
                     continue;
@@ -158,7 +568,6 @@ impl XmlValidator {
                     },
                     _ => {}
                 }
-                //TODO check type
             } else {
                 if has_translation {
                     continue;
@@ -171,30 +580,478 @@ impl XmlValidator {
                 }
             }
         }
-        //Diagnostic if some mandatory fields are not detected
-        // if !mandatory_fields.is_empty() {
-        // We have to check  that remaining fields are not declared in an inherited record or is automatically field (delegate=True)
-        //     diagnostics.push(Diagnostic::new(
-        //         Range::new(Position::new(xml_data_record.range.start.try_into().unwrap(), 0), Position::new(xml_data_record.range.end.try_into().unwrap(), 0)),
-        //         Some(lsp_types::DiagnosticSeverity::ERROR),
-        //         Some(lsp_types::NumberOrString::String(S!("OLS30452"))),
-        //         Some(EXTENSION_NAME.to_string()),
-        //         format!("Some mandatory fields are not declared in the record: {:?}", mandatory_fields),
-        //         None,
-        //         None
-        //     ));
-        // }
     }
 
-    fn validate_menu_item(&self, _session: &mut SessionInfo, _module: &Rc<RefCell<Symbol>>, _xml_data_menu_item: &XmlDataMenuItem, _diagnostics: &mut Vec<Diagnostic>, _dependencies: &mut Vec<Rc<RefCell<Symbol>>>, _model_dependencies: &mut Vec<Rc<RefCell<Model>>>, _missing_model_dependencies: &mut HashSet<OYarn>) {
+    /* Report the required fields without default that are not set by a record creating a new xml_id */
+    fn validate_required_fields(&self, session: &mut SessionInfo, module: &Rc<RefCell<Symbol>>, model: &Rc<RefCell<Model>>, xml_data_record: &OdooDataRecord, all_fields: &HashMap<OYarn, Vec<(Rc<RefCell<Symbol>>, Option<OYarn>)>>, diagnostics: &mut Vec<Diagnostic>, dependencies: &mut Vec<Rc<RefCell<Symbol>>>) {
+        if !self.is_in_main_ep || !self.is_record_creation(session, module, xml_data_record, dependencies) {
+            return;
+        }
+        let is_csv = self.xml_symbol.borrow().typ() == SymType::CSV_FILE;
+        let record_fields: HashSet<OYarn> = xml_data_record.fields.iter()
+            .filter(|f| !is_csv || f.text.as_ref().map(|t| !t.is_empty()).unwrap_or(false)) //empty cells are not set
+            .map(|f| oyarn!("{}", f.name.split('@').next().unwrap_or("")))
+            .collect();
+        let mut missing = vec![];
+        for (field_name, delegation_field) in XmlValidator::get_mandatory_fields(session, module, model, all_fields) {
+            if record_fields.contains(&field_name) {
+                continue;
+            }
+            //fields of an _inherits parent are not required when the record is linked to an existing parent
+            if delegation_field.as_ref().map(|d| record_fields.contains(d)).unwrap_or(false) {
+                continue;
+            }
+            missing.push(field_name);
+        }
+        if missing.is_empty() {
+            return;
+        }
+        let mut missing = missing.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+        missing.sort();
+        let range = if is_csv { &xml_data_record.range } else { &xml_data_record.model.1 };
+        let missing = missing.join(", ");
+        if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05071, &[&missing]) {
+            diagnostics.push(Diagnostic {
+                range: Range { start: Position::new(range.start as u32, 0), end: Position::new(range.end as u32, 0) },
+                ..diagnostic.clone()
+            });
+        }
+    }
+
+    /* A record creates a new record in database if its xml_id is not already declared before it
+    (noupdate records of other modules, or records declared in an earlier file or earlier in the same file) */
+    fn is_record_creation(&self, session: &mut SessionInfo, module: &Rc<RefCell<Symbol>>, xml_data_record: &OdooDataRecord, dependencies: &mut Vec<Rc<RefCell<Symbol>>>) -> bool {
+        let Some(xml_id) = xml_data_record.xml_id.as_ref() else {
+            return true;
+        };
+        let Some((id_module, _)) = XmlIdUsages::resolve(&self.xml_symbol, xml_id) else {
+            return false;
+        };
+        if id_module != *module.borrow().name() {
+            return false;
+        }
+        for data in self.get_visible_xml_ids(session, module, xml_id, &xml_data_record.range, &mut vec![], dependencies).unwrap_or_default() {
+            let Some(file) = data.get_file_symbol().and_then(|f| f.upgrade()) else {
+                continue;
+            };
+            if Rc::ptr_eq(&file, &self.xml_symbol) && data.get_range().start >= xml_data_record.range.start {
+                continue;
+            }
+            return false;
+        }
+        true
+    }
+
+    /* Required fields of the model that have no default, compute or related in any of its classes visible from the module.
+    Fields coming from an _inherits parent are returned with the name of the delegation field */
+    fn get_mandatory_fields(session: &mut SessionInfo, module: &Rc<RefCell<Symbol>>, model: &Rc<RefCell<Model>>, all_fields: &HashMap<OYarn, Vec<(Rc<RefCell<Symbol>>, Option<OYarn>)>>) -> Vec<(OYarn, Option<OYarn>)> {
+        let (own_classes, inherits_classes) = model.borrow().all_symbols_inherits(session, Some(module.clone()));
+        let own_classes = own_classes.into_iter().filter(|(_, dep)| dep.is_none()).map(|(c, _)| c).collect::<Vec<_>>();
+        let mut delegations: HashMap<OYarn, OYarn> = HashMap::new();
+        for class in own_classes.iter() {
+            if let Some(model_data) = class.borrow().as_class_sym()._model.as_ref() {
+                for (parent_model, delegation_field) in model_data.inherits.iter() {
+                    delegations.insert(parent_model.clone(), delegation_field.clone());
+                }
+            }
+        }
+        let mut result = vec![];
+        for (field_name, field_symbols) in all_fields.iter() {
+            if delegations.values().any(|d| d == field_name) {
+                continue;
+            }
+            let mut required = false;
+            let mut optional = false;
+            let mut is_own = false;
+            let mut delegation_field = None;
+            for (field_symbol, _) in field_symbols.iter() {
+                if field_symbol.borrow().typ() != SymType::VARIABLE {
+                    continue;
+                }
+                let Some(class) = field_symbol.borrow().parent().and_then(|p| p.upgrade()) else {
+                    continue;
+                };
+                if own_classes.iter().any(|c| Rc::ptr_eq(c, &class)) {
+                    is_own = true;
+                } else if inherits_classes.iter().any(|(c, _)| Rc::ptr_eq(c, &class)) {
+                    let class_model = class.borrow().as_class_sym()._model.as_ref().map(|m| m.name.clone());
+                    delegation_field = delegation_field.or(class_model.and_then(|m| delegations.get(&m).cloned()));
+                } else {
+                    continue; //class out of the dependencies of the module
+                }
+                for context in XmlValidator::get_field_contexts(session, field_symbol) {
+                    match context.get("required") {
+                        Some(ContextValue::BOOLEAN(true)) => required = true,
+                        Some(ContextValue::BOOLEAN(false)) => optional = true,
+                        _ => {}
+                    }
+                    if ["default", "compute", "related"].iter().any(|k| context.contains_key(*k)) {
+                        optional = true;
+                    }
+                }
+            }
+            if !required || optional {
+                continue;
+            }
+            if is_own {
+                result.push((field_name.clone(), None));
+            } else if delegation_field.is_some() {
+                //fields of the parents of the _inherits parents are ignored
+                result.push((field_name.clone(), delegation_field));
+            }
+        }
+        result
+    }
+
+    fn get_field_contexts(session: &mut SessionInfo, field_symbol: &Rc<RefCell<Symbol>>) -> Vec<Context> {
+        let field_symbol = field_symbol.borrow();
+        field_symbol.evaluations().unwrap_or(&vec![]).iter()
+            .map(|evaluation| evaluation.symbol.get_symbol_as_weak(session, &mut None, &mut vec![], None).context)
+            .collect()
+    }
+
+    /* Check the content, ref or eval of a field against the type of the python field */
+    fn validate_field_value(&self, session: &mut SessionInfo, field: &OdooDataField, field_symbols: &[(Rc<RefCell<Symbol>>, Option<OYarn>)], diagnostics: &mut Vec<Diagnostic>) {
+        let symbols = field_symbols.iter().map(|(s, _)| s.clone()).collect::<Vec<_>>();
+        let Some(symbol) = symbols.first().cloned() else {
+            return;
+        };
+        if symbol.borrow().typ() != SymType::VARIABLE {
+            return;
+        }
+        let (code, args, range) = match (&field.ref_id, &field.eval, &field.text, &field.text_range) {
+            (Some((ref_id, range)), _, _, _) => {
+                if !symbol.borrow().is_specific_field(session, &["Many2one"]) {
+                    return;
+                }
+                let Some((record_model, comodel)) = self.get_ref_model_mismatch(session, &symbol, ref_id, range) else {
+                    return;
+                };
+                (DiagnosticCode::OLS05068, vec![ref_id.to_string(), record_model.to_string(), comodel.to_string()], range)
+            },
+            (None, Some((eval, range)), _, _) => {
+                if !symbol.borrow().is_specific_field(session, &["One2many", "Many2many"]) || XmlValidator::is_x2many_value(eval) {
+                    return;
+                }
+                (DiagnosticCode::OLS05070, vec![field.name.to_string()], range)
+            },
+            (None, None, Some(text), Some(range)) if field.typ.is_none() && !text.trim().is_empty() => {
+                //without type attribute, the rng validation can't check the content: the type comes from the python field
+                let text = text.trim();
+                if symbol.borrow().is_specific_field(session, &["Integer"]) {
+                    if text.parse::<i64>().is_ok() {
+                        return;
+                    }
+                    (DiagnosticCode::OLS05018, vec![text.to_string()], range)
+                } else if symbol.borrow().is_specific_field(session, &["Float", "Monetary"]) {
+                    if text.parse::<f64>().is_ok() {
+                        return;
+                    }
+                    (DiagnosticCode::OLS05019, vec![text.to_string()], range)
+                } else if symbol.borrow().is_specific_field(session, &["Selection"]) {
+                    match XmlValidator::get_selection_keys(session, &symbols) {
+                        Some(keys) if !keys.iter().any(|k| k == text) => (DiagnosticCode::OLS05069, vec![text.to_string(), field.name.to_string()], range),
+                        _ => return,
+                    }
+                } else {
+                    return;
+                }
+            },
+            _ => return,
+        };
+        let args = args.iter().map(|a| a.as_str()).collect::<Vec<_>>();
+        if let Some(diagnostic) = create_diagnostic(session, code, &args) {
+            diagnostics.push(Diagnostic {
+                range: Range { start: Position::new(range.start as u32, 0), end: Position::new(range.end as u32, 0) },
+                ..diagnostic.clone()
+            });
+        }
+    }
+
+    /* Return the model of the record ref_id and the comodel of the Many2one field if the record is not of the comodel */
+    fn get_ref_model_mismatch(&self, session: &mut SessionInfo, field: &Rc<RefCell<Symbol>>, ref_id: &OYarn, range: &std::ops::Range<usize>) -> Option<(OYarn, OYarn)> {
+        let from_module = self.xml_symbol.borrow().find_module();
+        let comodels = field.borrow().as_variable().get_relational_model(session, from_module);
+        let comodel = comodels.first()?.borrow().as_class_sym()._model.as_ref()?.name.clone();
+        let record_models = SyncOdoo::get_xml_ids(session, &self.xml_symbol, ref_id, range, &mut vec![]).unwrap_or_default().iter().filter_map(|data| match data {
+            OdooData::RECORD(record) => Some(record.model.0.clone()),
+            OdooData::MENUITEM(_) => Some(oyarn!("ir.ui.menu")),
+            OdooData::TEMPLATE(_) => Some(oyarn!("ir.ui.view")),
+            OdooData::DELETE(_) => None,
+        }).collect::<Vec<_>>();
+        let record_model = record_models.first()?.clone();
+        for model_name in record_models.iter() {
+            if *model_name == comodel {
+                return None;
+            }
+            //records of models inheriting the comodel under another name (like ir.actions.*) are accepted
+            let Some(model) = session.sync_odoo.models.get(model_name).cloned() else {
+                return None;
+            };
+            let inherits_comodel = model.borrow().get_symbols(session, None).iter().any(|s|
+                s.borrow().as_class_sym()._model.as_ref().map(|m| m.inherit.contains(&comodel)).unwrap_or(false)
+            );
+            if inherits_comodel {
+                return None;
+            }
+        }
+        Some((record_model, comodel))
+    }
+
+    /* Return false if the eval of an x2many field is clearly not a list of commands: a ref, an id, or a list of them */
+    fn is_x2many_value(eval: &str) -> bool {
+        let parsed = ruff_python_parser::parse_unchecked(eval, ParseOptions::from(Mode::Expression));
+        if !parsed.errors().is_empty() {
+            return true; //not our job to report syntax errors here
+        }
+        let Mod::Expression(expression) = parsed.into_syntax() else {
+            return true;
+        };
+        let is_id = |expr: &Expr| match expr {
+            Expr::NumberLiteral(_) | Expr::StringLiteral(_) => true,
+            Expr::Call(call) => matches!(call.func.as_ref(), Expr::Name(name) if name.id.as_str() == "ref"),
+            _ => false
+        };
+        match expression.body.as_ref() {
+            Expr::List(list) => !list.elts.iter().any(is_id),
+            expr => !is_id(expr),
+        }
+    }
+
+    /* Keys of a Selection field, with the selection_add of its overrides. None if the selection is not a static list */
+    fn get_selection_keys(session: &mut SessionInfo, symbols: &[Rc<RefCell<Symbol>>]) -> Option<Vec<String>> {
+        let mut keys = vec![];
+        let mut is_static = false;
+        for symbol in symbols.iter() {
+            for context in XmlValidator::get_field_contexts(session, symbol) {
+                if let Some(selection) = context.get("selection").and_then(|s| s.as_list()) {
+                    keys.extend(selection);
+                    is_static = true;
+                }
+                if let Some(selection_add) = context.get("selection_add").and_then(|s| s.as_list()) {
+                    keys.extend(selection_add);
+                }
+            }
+        }
+        is_static.then_some(keys)
+    }
+
+    fn validate_menu_item(&self, session: &mut SessionInfo, module: &Rc<RefCell<Symbol>>, xml_data_menu_item: &XmlDataMenuItem, diagnostics: &mut Vec<Diagnostic>, dependencies: &mut Vec<Rc<RefCell<Symbol>>>, _model_dependencies: &mut Vec<Rc<RefCell<Model>>>, _missing_model_dependencies: &mut HashSet<OYarn>) {
+        if !self.is_in_main_ep {
+            return;
+        }
+        //missing actions and parents are already reported by the arch builder
+        if let Some((action, range)) = xml_data_menu_item.action.as_ref() {
+            let actions = self.get_visible_xml_ids(session, module, action, range, &mut vec![], dependencies).unwrap_or_default();
+            let is_action = |data: &OdooData| matches!(data, OdooData::RECORD(record) if record.model.0.starts_with("ir.actions."));
+            if !actions.is_empty() && !actions.iter().any(is_action) {
+                if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05065, &[action]) {
+                    diagnostics.push(Diagnostic {
+                        range: Range { start: Position::new(range.start as u32, 0), end: Position::new(range.end as u32, 0) },
+                        ..diagnostic.clone()
+                    });
+                }
+            }
+        }
+        if let Some((parent, range)) = xml_data_menu_item.parent.as_ref() {
+            let parents = self.get_visible_xml_ids(session, module, parent, range, &mut vec![], dependencies).unwrap_or_default();
+            if !parents.is_empty() && !parents.iter().any(|data| matches!(data, OdooData::MENUITEM(_))) {
+                if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05075, &[parent]) {
+                    diagnostics.push(Diagnostic {
+                        range: Range { start: Position::new(range.start as u32, 0), end: Position::new(range.end as u32, 0) },
+                        ..diagnostic.clone()
+                    });
+                }
+            }
+            if let Some(xml_id) = xml_data_menu_item.xml_id.as_ref() {
+                if self.is_menu_ancestor(session, xml_id, parent) {
+                    if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05066, &[xml_id]) {
+                        diagnostics.push(Diagnostic {
+                            range: Range { start: Position::new(range.start as u32, 0), end: Position::new(range.end as u32, 0) },
+                            ..diagnostic.clone()
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /* Return true if the menuitem xml_id is found by following the parents of the menuitem parent_id */
+    fn is_menu_ancestor(&self, session: &mut SessionInfo, xml_id: &OYarn, parent_id: &OYarn) -> bool {
+        let Some(menu) = XmlIdUsages::resolve(&self.xml_symbol, xml_id) else {
+            return false;
+        };
+        let mut visited = HashSet::new();
+        let mut current = Some((parent_id.clone(), self.xml_symbol.clone()));
+        while let Some((parent_id, file)) = current.take() {
+            let Some(parent) = XmlIdUsages::resolve(&file, &parent_id) else {
+                break;
+            };
+            if parent == menu {
+                return true;
+            }
+            if !visited.insert(parent.clone()) {
+                break; //a loop that doesn't contain the menuitem, it will be reported on its own menuitems
+            }
+            let Some(parent_module) = session.sync_odoo.modules.get(&parent.0).and_then(|m| m.upgrade()) else {
+                break;
+            };
+            let parent_menu = parent_module.borrow().as_module_package().get_xml_id(&parent.1).into_iter().find_map(|data| match data {
+                OdooData::MENUITEM(menu_item) => Some(menu_item),
+                _ => None
+            });
+            if let Some(parent_menu) = parent_menu {
+                if let (Some((grand_parent, _)), Some(parent_file)) = (parent_menu.parent, parent_menu.file_symbol.upgrade()) {
+                    current = Some((grand_parent, parent_file));
+                }
+            }
+        }
+        false
+    }
 
+    fn validate_template(&self, session: &mut SessionInfo, module: &Rc<RefCell<Symbol>>, xml_data_template: &XmlDataTemplate, diagnostics: &mut Vec<Diagnostic>, dependencies: &mut Vec<Rc<RefCell<Symbol>>>, _model_dependencies: &mut Vec<Rc<RefCell<Model>>>, _missing_model_dependencies: &mut HashSet<OYarn>) {
+        self.validate_qweb(session, module, &xml_data_template.qweb, diagnostics, dependencies);
     }
 
-    fn validate_template(&self, _session: &mut SessionInfo, _module: &Rc<RefCell<Symbol>>, _xml_data_template: &XmlDataTemplate, _diagnostics: &mut Vec<Diagnostic>, _dependencies: &mut Vec<Rc<RefCell<Symbol>>>, _model_dependencies: &mut Vec<Rc<RefCell<Model>>>, _missing_model_dependencies: &mut HashSet<OYarn>) {
+    /* Check that t-call, inherit_id and t-inherit targets exist, and that t-inherit-mode is valid */
+    fn validate_qweb(&self, session: &mut SessionInfo, module: &Rc<RefCell<Symbol>>, qweb: &XmlDataQWeb, diagnostics: &mut Vec<Diagnostic>, dependencies: &mut Vec<Rc<RefCell<Symbol>>>) {
+        if !self.is_in_main_ep {
+            return;
+        }
+        for (targets, code) in [(&qweb.t_calls, DiagnosticCode::OLS05058), (&qweb.inherits, DiagnosticCode::OLS05059)] {
+            for (xml_id, range) in targets.iter() {
+                //None: an invalid xml_id has already been reported by get_xml_ids
+                if self.find_views(session, module, xml_id, range, diagnostics, dependencies) != Some(false) {
+                    continue;
+                }
+                if let Some(diagnostic) = create_diagnostic(session, code, &[xml_id]) {
+                    diagnostics.push(Diagnostic {
+                        range: Range { start: Position::new(range.start as u32, 0), end: Position::new(range.end as u32, 0) },
+                        ..diagnostic.clone()
+                    });
+                }
+            }
+        }
+        for (mode, range) in qweb.inherit_modes.iter() {
+            if !["primary", "extension"].contains(&mode.as_str()) {
+                if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05060, &[mode]) {
+                    diagnostics.push(Diagnostic {
+                        range: Range { start: Position::new(range.start as u32, 0), end: Position::new(range.end as u32, 0) },
+                        ..diagnostic.clone()
+                    });
+                }
+            }
+        }
+    }
+
+    /* Return true if xml_id is a template or an ir.ui.view visible from the module, None if xml_id is invalid */
+    fn find_views(&self, session: &mut SessionInfo, module: &Rc<RefCell<Symbol>>, xml_id: &OYarn, range: &std::ops::Range<usize>, diagnostics: &mut Vec<Diagnostic>, dependencies: &mut Vec<Rc<RefCell<Symbol>>>) -> Option<bool> {
+        let views = self.get_visible_xml_ids(session, module, xml_id, range, diagnostics, dependencies)?;
+        Some(views.iter().any(|data| match data {
+            OdooData::TEMPLATE(_) => true,
+            OdooData::RECORD(record) => record.model.0 == "ir.ui.view",
+            _ => false
+        }))
+    }
+
+    /* Data declared with xml_id in the module or its dependencies, None if xml_id is invalid. <delete> nodes are ignored. Files declaring them are added to dependencies */
+    fn get_visible_xml_ids(&self, session: &mut SessionInfo, module: &Rc<RefCell<Symbol>>, xml_id: &str, range: &std::ops::Range<usize>, diagnostics: &mut Vec<Diagnostic>, dependencies: &mut Vec<Rc<RefCell<Symbol>>>) -> Option<Vec<OdooData>> {
+        let mut result = vec![];
+        for data in SyncOdoo::get_xml_ids(session, &self.xml_symbol, xml_id, range, diagnostics)? {
+            if matches!(data, OdooData::DELETE(_)) {
+                continue;
+            }
+            let Some(file) = data.get_file_symbol().and_then(|f| f.upgrade()) else {
+                continue;
+            };
+            let Some(data_module) = file.borrow().find_module() else {
+                continue;
+            };
+            if ModuleSymbol::is_in_deps(session, module, data_module.borrow().name()) {
+                if !Rc::ptr_eq(&file, &self.xml_symbol) {
+                    dependencies.push(file.clone());
+                }
+                result.push(data);
+            }
+        }
+        Some(result)
+    }
 
+    fn validate_delete(&self, session: &mut SessionInfo, module: &Rc<RefCell<Symbol>>, xml_data_delete: &XmlDataDelete, diagnostics: &mut Vec<Diagnostic>, dependencies: &mut Vec<Rc<RefCell<Symbol>>>, model_dependencies: &mut Vec<Rc<RefCell<Model>>>, missing_model_dependencies: &mut HashSet<OYarn>) {
+        let (model_name, model_range) = &xml_data_delete.model;
+        if !self.is_in_main_ep || model_name.is_empty() {
+            return;
+        }
+        let model = session.sync_odoo.models.get(model_name).cloned();
+        let main_symbols = model.as_ref().map(|m| m.borrow().get_main_symbols(session, Some(module.clone()))).unwrap_or_default();
+        if main_symbols.is_empty() {
+            missing_model_dependencies.insert(model_name.clone());
+            if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05055, &[model_name, module.borrow().name()]) {
+                diagnostics.push(Diagnostic {
+                    range: Range { start: Position::new(model_range.start as u32, 0), end: Position::new(model_range.end as u32, 0) },
+                    data: Some(serde_json::json!({"model": model_name.as_str()})),
+                    ..diagnostic.clone()
+                });
+            }
+            return;
+        }
+        if let Some(model) = model {
+            model_dependencies.push(model);
+        }
+        let Some((xml_id, range)) = xml_data_delete.xml_id.as_ref() else {
+            return;
+        };
+        let Some(records) = self.get_visible_xml_ids(session, module, xml_id, range, diagnostics, dependencies) else {
+            return;
+        };
+        if records.is_empty() {
+            if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05067, &[xml_id]) {
+                diagnostics.push(Diagnostic {
+                    range: Range { start: Position::new(range.start as u32, 0), end: Position::new(range.end as u32, 0) },
+                    ..diagnostic.clone()
+                });
+            }
+            return;
+        }
+        let record_model = records.iter().find_map(|data| match data {
+            OdooData::RECORD(record) => Some(record.model.0.clone()),
+            OdooData::MENUITEM(_) => Some(oyarn!("ir.ui.menu")),
+            OdooData::TEMPLATE(_) => Some(oyarn!("ir.ui.view")),
+            OdooData::DELETE(_) => None,
+        });
+        if let Some(record_model) = record_model {
+            if record_model != *model_name {
+                if let Some(diagnostic) = create_diagnostic(session, DiagnosticCode::OLS05068, &[xml_id, &record_model, model_name]) {
+                    diagnostics.push(Diagnostic {
+                        range: Range { start: Position::new(model_range.start as u32, 0), end: Position::new(model_range.end as u32, 0) },
+                        ..diagnostic.clone()
+                    });
+                }
+            }
+        }
     }
+}
+
+struct FreeNameVisitor<'a> {
+    loaded: Vec<&'a ExprName>,
+    bound: HashSet<&'a str>,
+}
 
-    fn validate_delete(&self, _session: &mut SessionInfo, _module: &Rc<RefCell<Symbol>>, _xml_data_delete: &XmlDataDelete, _diagnostics: &mut Vec<Diagnostic>, _dependencies: &mut Vec<Rc<RefCell<Symbol>>>, _model_dependencies: &mut Vec<Rc<RefCell<Model>>>, _missing_model_dependencies: &mut HashSet<OYarn>) {
+impl<'a> Visitor<'a> for FreeNameVisitor<'a> {
 
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let Expr::Name(name) = expr {
+            if matches!(name.ctx, ExprContext::Load) {
+                self.loaded.push(name);
+            } else {
+                self.bound.insert(name.id.as_str());
+            }
+        }
+        walk_expr(self, expr);
+    }
+
+    fn visit_parameter(&mut self, parameter: &'a Parameter) {
+        self.bound.insert(parameter.name.id.as_str());
+        walk_parameter(self, parameter);
     }
-}
\ No newline at end of file
+}
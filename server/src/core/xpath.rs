@@ -0,0 +1,403 @@
+use crate::S;
+
+/* Syntax check of the XPath 1.0 expressions used by view inheritance. The expression is tokenized and parsed
+following the grammar of the XPath 1.0 recommendation: location paths with their axes, node tests and predicates,
+operators, literals, numbers, variables and function calls. Names of functions and namespace prefixes are not
+checked, as they depend on the evaluation context (lxml adds its own functions). */
+
+const AXES: [&str; 13] = ["ancestor", "ancestor-or-self", "attribute", "child", "descendant", "descendant-or-self", "following",
+    "following-sibling", "namespace", "parent", "preceding", "preceding-sibling", "self"];
+const NODE_TYPES: [&str; 4] = ["comment", "text", "processing-instruction", "node"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Symbol(&'static str), //( ) [ ] . .. @ , :: / // | + - = != < <= > >=
+    Operator(String), //and, or, mod, div and * when they are used as operators
+    Star, //* as name test
+    Name(String), //NCName, QName or prefix:*
+    Variable(String),
+    Literal,
+    Number,
+}
+
+/* As stated by the recommendation, '*' and operator names are operators if there is a preceding token that is
+not '@', '::', '(', '[', ',' or an operator */
+fn is_operator_expected(tokens: &[Token]) -> bool {
+    match tokens.last() {
+        None | Some(Token::Operator(_)) => false,
+        Some(Token::Symbol(s)) => matches!(*s, ")" | "]" | "." | ".."),
+        _ => true,
+    }
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, String> {
+    let chars = expr.chars().collect::<Vec<char>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let symbol = match (c, next) {
+            ('/', Some('/')) => Some("//"),
+            (':', Some(':')) => Some("::"),
+            ('!', Some('=')) => Some("!="),
+            ('<', Some('=')) => Some("<="),
+            ('>', Some('=')) => Some(">="),
+            ('.', Some('.')) => Some(".."),
+            _ => None,
+        };
+        if let Some(symbol) = symbol {
+            tokens.push(Token::Symbol(symbol));
+            i += 2;
+            continue;
+        }
+        match c {
+            '(' | ')' | '[' | ']' | '@' | ',' | '/' | '|' | '+' | '-' | '=' | '<' | '>' => {
+                let symbol = ["(", ")", "[", "]", "@", ",", "/", "|", "+", "-", "=", "<", ">"].into_iter().find(|s| s.starts_with(c)).unwrap();
+                tokens.push(Token::Symbol(symbol));
+                i += 1;
+            },
+            '.' if !next.is_some_and(|n| n.is_ascii_digit()) => {
+                tokens.push(Token::Symbol("."));
+                i += 1;
+            },
+            '*' => {
+                tokens.push(match is_operator_expected(&tokens) {
+                    true => Token::Operator(S!("*")),
+                    false => Token::Star,
+                });
+                i += 1;
+            },
+            '\'' | '"' => {
+                let Some(end) = chars[i + 1..].iter().position(|q| *q == c) else {
+                    return Err(S!("unclosed string"));
+                };
+                tokens.push(Token::Literal);
+                i += end + 2;
+            },
+            '$' => {
+                let (name, end) = read_qname(&chars, i + 1);
+                if name.is_empty() {
+                    return Err(S!("missing variable name after '$'"));
+                }
+                tokens.push(Token::Variable(name));
+                i = end;
+            },
+            c if c.is_ascii_digit() || c == '.' => {
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+                if i < chars.len() && chars[i] == '.' {
+                    i += 1;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+                tokens.push(Token::Number);
+            },
+            c if is_name_start(c) => {
+                let (mut name, end) = read_qname(&chars, i);
+                i = end;
+                //prefix:*
+                if chars.get(i) == Some(&':') && chars.get(i + 1) == Some(&'*') && !name.contains(':') {
+                    name.push_str(":*");
+                    i += 2;
+                }
+                if is_operator_expected(&tokens) && ["and", "or", "mod", "div"].contains(&name.as_str()) {
+                    tokens.push(Token::Operator(name));
+                } else {
+                    tokens.push(Token::Name(name));
+                }
+            },
+            _ => return Err(format!("unexpected character '{}'", c)),
+        }
+    }
+    Ok(tokens)
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '.'
+}
+
+/* Read a NCName or a QName (prefix:local) starting at the index. Return the name and the index after it */
+fn read_qname(chars: &[char], start: usize) -> (String, usize) {
+    let mut i = start;
+    if i >= chars.len() || !is_name_start(chars[i]) {
+        return (String::new(), i);
+    }
+    while i < chars.len() && is_name_char(chars[i]) {
+        i += 1;
+    }
+    if chars.get(i) == Some(&':') && chars.get(i + 1).is_some_and(|c| is_name_start(*c)) {
+        i += 1;
+        while i < chars.len() && is_name_char(chars[i]) {
+            i += 1;
+        }
+    }
+    (chars[start..i].iter().collect(), i)
+}
+
+struct XPathParser {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl XPathParser {
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.index + offset)
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol)
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        if self.is_symbol(symbol) {
+            self.index += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), String> {
+        if self.eat_symbol(symbol) {
+            return Ok(());
+        }
+        Err(match self.peek() {
+            Some(token) => format!("expected '{}', found {}", symbol, XPathParser::describe(token)),
+            None => format!("expected '{}' at the end of the expression", symbol),
+        })
+    }
+
+    fn describe(token: &Token) -> String {
+        match token {
+            Token::Symbol(s) => format!("'{}'", s),
+            Token::Operator(operator) => format!("'{}'", operator),
+            Token::Star => S!("'*'"),
+            Token::Name(name) => format!("'{}'", name),
+            Token::Variable(name) => format!("'${}'", name),
+            Token::Literal => S!("a string"),
+            Token::Number => S!("a number"),
+        }
+    }
+
+    fn unexpected(&self) -> String {
+        match self.peek() {
+            Some(token) => format!("unexpected {}", XPathParser::describe(token)),
+            None => S!("unexpected end of the expression"),
+        }
+    }
+
+    fn eat_operator(&mut self, operators: &[&str]) -> bool {
+        if let Some(Token::Operator(operator)) = self.peek() {
+            if operators.contains(&operator.as_str()) {
+                self.index += 1;
+                return true;
+            }
+        }
+        false
+    }
+
+    fn parse_expr(&mut self) -> Result<(), String> {
+        self.parse_and_expr()?;
+        while self.eat_operator(&["or"]) {
+            self.parse_and_expr()?;
+        }
+        Ok(())
+    }
+
+    fn parse_and_expr(&mut self) -> Result<(), String> {
+        self.parse_comparison_expr()?;
+        while self.eat_operator(&["and"]) {
+            self.parse_comparison_expr()?;
+        }
+        Ok(())
+    }
+
+    /* equality and relational expressions */
+    fn parse_comparison_expr(&mut self) -> Result<(), String> {
+        self.parse_additive_expr()?;
+        while ["=", "!=", "<", "<=", ">", ">="].iter().any(|op| self.eat_symbol(op)) {
+            self.parse_additive_expr()?;
+        }
+        Ok(())
+    }
+
+    fn parse_additive_expr(&mut self) -> Result<(), String> {
+        self.parse_multiplicative_expr()?;
+        while self.eat_symbol("+") || self.eat_symbol("-") {
+            self.parse_multiplicative_expr()?;
+        }
+        Ok(())
+    }
+
+    fn parse_multiplicative_expr(&mut self) -> Result<(), String> {
+        self.parse_unary_expr()?;
+        while self.eat_operator(&["*", "div", "mod"]) {
+            self.parse_unary_expr()?;
+        }
+        Ok(())
+    }
+
+    fn parse_unary_expr(&mut self) -> Result<(), String> {
+        while self.eat_symbol("-") {}
+        self.parse_path_expr()?;
+        while self.eat_symbol("|") {
+            self.parse_path_expr()?;
+        }
+        Ok(())
+    }
+
+    fn parse_path_expr(&mut self) -> Result<(), String> {
+        if self.is_primary_expr_start() {
+            self.parse_primary_expr()?;
+            while self.is_symbol("[") {
+                self.parse_predicate()?;
+            }
+            if self.eat_symbol("/") || self.eat_symbol("//") {
+                self.parse_relative_location_path()?;
+            }
+            return Ok(());
+        }
+        if self.eat_symbol("/") {
+            //a single '/' selects the root node
+            if self.is_step_start() {
+                self.parse_relative_location_path()?;
+            }
+            return Ok(());
+        }
+        if self.eat_symbol("//") {
+            return self.parse_relative_location_path();
+        }
+        self.parse_relative_location_path()
+    }
+
+    fn is_primary_expr_start(&self) -> bool {
+        match self.peek() {
+            Some(Token::Variable(_)) | Some(Token::Literal) | Some(Token::Number) => true,
+            Some(Token::Symbol("(")) => true,
+            Some(Token::Name(name)) => {
+                self.peek_at(1) == Some(&Token::Symbol("(")) && !NODE_TYPES.contains(&name.as_str())
+            },
+            _ => false,
+        }
+    }
+
+    fn parse_primary_expr(&mut self) -> Result<(), String> {
+        match self.peek().cloned() {
+            Some(Token::Variable(_)) | Some(Token::Literal) | Some(Token::Number) => {
+                self.index += 1;
+                Ok(())
+            },
+            Some(Token::Symbol("(")) => {
+                self.index += 1;
+                self.parse_expr()?;
+                self.expect_symbol(")")
+            },
+            Some(Token::Name(_)) => {
+                //function call
+                self.index += 2;
+                if self.eat_symbol(")") {
+                    return Ok(());
+                }
+                self.parse_expr()?;
+                while self.eat_symbol(",") {
+                    self.parse_expr()?;
+                }
+                self.expect_symbol(")")
+            },
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn is_step_start(&self) -> bool {
+        matches!(self.peek(), Some(Token::Name(_)) | Some(Token::Star) | Some(Token::Symbol("@")) | Some(Token::Symbol(".")) | Some(Token::Symbol("..")))
+    }
+
+    fn parse_relative_location_path(&mut self) -> Result<(), String> {
+        self.parse_step()?;
+        while self.eat_symbol("/") || self.eat_symbol("//") {
+            self.parse_step()?;
+        }
+        Ok(())
+    }
+
+    fn parse_step(&mut self) -> Result<(), String> {
+        if self.eat_symbol(".") || self.eat_symbol("..") {
+            return Ok(());
+        }
+        if !self.eat_symbol("@") {
+            if let (Some(Token::Name(name)), Some(Token::Symbol("::"))) = (self.peek().cloned(), self.peek_at(1)) {
+                if !AXES.contains(&name.as_str()) {
+                    return Err(format!("unknown axis '{}'", name));
+                }
+                self.index += 2;
+            }
+        }
+        self.parse_node_test()?;
+        while self.is_symbol("[") {
+            self.parse_predicate()?;
+        }
+        Ok(())
+    }
+
+    fn parse_node_test(&mut self) -> Result<(), String> {
+        match self.peek().cloned() {
+            Some(Token::Star) => {
+                self.index += 1;
+                Ok(())
+            },
+            Some(Token::Name(name)) => {
+                self.index += 1;
+                if self.peek() == Some(&Token::Symbol("::")) {
+                    return Err(S!("missing node test after the axis"));
+                }
+                if NODE_TYPES.contains(&name.as_str()) && self.eat_symbol("(") {
+                    if name == "processing-instruction" && self.peek() == Some(&Token::Literal) {
+                        self.index += 1;
+                    }
+                    return self.expect_symbol(")");
+                }
+                Ok(())
+            },
+            None => Err(S!("missing node test at the end of the expression")),
+            Some(_) => Err(format!("missing node test before {}", XPathParser::describe(self.peek().unwrap()))),
+        }
+    }
+
+    fn parse_predicate(&mut self) -> Result<(), String> {
+        self.expect_symbol("[")?;
+        if self.is_symbol("]") {
+            return Err(S!("empty predicate"));
+        }
+        self.parse_expr()?;
+        self.expect_symbol("]")
+    }
+}
+
+/* Return an error message if the expression is not a valid XPath 1.0 expression */
+pub fn check_xpath_syntax(expr: &str) -> Result<(), String> {
+    let tokens = tokenize(expr)?;
+    if tokens.is_empty() {
+        return Err(S!("empty expression"));
+    }
+    let mut parser = XPathParser { tokens, index: 0 };
+    parser.parse_expr()?;
+    if parser.peek().is_some() {
+        return Err(parser.unexpected());
+    }
+    Ok(())
+}
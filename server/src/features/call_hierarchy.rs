@@ -0,0 +1,291 @@
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
+
+use lsp_types::{CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall, Range, SymbolKind};
+use ruff_python_ast::{visitor::{walk_expr, walk_stmt, Visitor}, Expr, ExprCall, Stmt};
+use ruff_text_size::{Ranged, TextRange};
+use serde_json::{json, Value};
+
+use crate::{constants::{OYarn, SymType}, core::{evaluation::{ContextValue, ExprOrIdent}, file_mgr::FileInfo, odoo::SyncOdoo, symbols::{module_symbol::ModuleSymbol, symbol::Symbol}}, features::{features_utils::FeaturesUtils, implementation::ImplementationFeature, references::ReferenceFeature}, oyarn, threads::SessionInfo};
+
+pub struct CallHierarchyFeature {}
+
+impl CallHierarchyFeature {
+
+    pub fn prepare(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, line: u32, character: u32) -> Option<Vec<CallHierarchyItem>> {
+        let offset = file_info.borrow().position_to_offset(line, character);
+        let targets = ReferenceFeature::get_target_symbols(session, file_symbol, file_info, offset);
+        let items = targets.iter()
+            .filter(|target| target.borrow().typ() == SymType::FUNCTION)
+            .filter_map(|target| CallHierarchyFeature::build_item(session, target))
+            .collect::<Vec<_>>();
+        if items.is_empty() {
+            return None;
+        }
+        Some(items)
+    }
+
+    /* Functions calling the item. A call to a model method enters the chain of overrides by the last one, so it
+    reaches all the overrides. super() calls are only kept if they are resolved to the item */
+    pub fn get_incoming_calls(session: &mut SessionInfo, item: &CallHierarchyItem) -> Option<Vec<CallHierarchyIncomingCall>> {
+        let function = CallHierarchyFeature::get_function(session, item)?;
+        let name = function.borrow().name().clone();
+        let is_model_method = ReferenceFeature::get_model_member_info(&function).is_some();
+        let targets = match is_model_method {
+            true => ImplementationFeature::get_method_overrides(session, &function),
+            false => vec![function.clone()]
+        };
+        let file = function.borrow().get_file()?.upgrade()?;
+        let files = ReferenceFeature::get_files_to_scan(session, &file, &targets);
+        let mut calls: Vec<(Rc<RefCell<Symbol>>, Vec<Range>)> = vec![];
+        for file in files.iter() {
+            if !matches!(file.borrow().typ(), SymType::FILE | SymType::PACKAGE(_)) {
+                continue;
+            }
+            let from_module = CallHierarchyFeature::get_from_module(&file);
+            CallHierarchyFeature::visit_calls(session, &file, Some(name.as_str()), None, &mut |session, file_info, call| {
+                let Some((_, name_range)) = CallHierarchyFeature::get_callee_name(call) else {
+                    return;
+                };
+                let caller = Symbol::get_scope_symbol(file.clone(), call.range().start().to_u32(), false);
+                if caller.borrow().typ() != SymType::FUNCTION {
+                    return;
+                }
+                let is_call = match is_model_method && CallHierarchyFeature::is_super_call(call) {
+                    true => {
+                        let caller_class = caller.borrow().parent().and_then(|p| p.upgrade()).filter(|p| p.borrow().typ() == SymType::CLASS);
+                        caller_class.and_then(|class| CallHierarchyFeature::get_super_method(session, &class, &name))
+                            .map(|super_method| Rc::ptr_eq(&super_method, &function)).unwrap_or(false)
+                    },
+                    false => ReferenceFeature::evaluates_to_target(session, &file, &ExprOrIdent::Expr(call.func.as_ref()), &from_module, &targets)
+                };
+                if is_call {
+                    CallHierarchyFeature::push_call(&mut calls, caller, file_info.borrow().text_range_to_range(&name_range));
+                }
+            });
+        }
+        let result = calls.into_iter().filter_map(|(caller, from_ranges)| {
+            CallHierarchyFeature::build_item(session, &caller).map(|from| CallHierarchyIncomingCall { from, from_ranges })
+        }).collect::<Vec<_>>();
+        if result.is_empty() {
+            return None;
+        }
+        Some(result)
+    }
+
+    /* Functions called by the item. In a model method, super() is resolved with the modules dependencies */
+    pub fn get_outgoing_calls(session: &mut SessionInfo, item: &CallHierarchyItem) -> Option<Vec<CallHierarchyOutgoingCall>> {
+        let function = CallHierarchyFeature::get_function(session, item)?;
+        let file = function.borrow().get_file()?.upgrade()?;
+        let model_class = ReferenceFeature::get_model_member_info(&function).map(|(_, class)| class);
+        let from_module = CallHierarchyFeature::get_from_module(&file);
+        let function_range = *function.borrow().range();
+        let mut calls: Vec<(Rc<RefCell<Symbol>>, Vec<Range>)> = vec![];
+        CallHierarchyFeature::visit_calls(session, &file, None, Some(function_range), &mut |session, file_info, call| {
+            let Some((callee_name, name_range)) = CallHierarchyFeature::get_callee_name(call) else {
+                return;
+            };
+            let callees = match (&model_class, CallHierarchyFeature::is_super_call(call)) {
+                (Some(class), true) => {
+                    CallHierarchyFeature::get_super_method(session, class, &oyarn!("{}", callee_name)).into_iter().collect::<Vec<_>>()
+                },
+                _ => {
+                    let symbols = ReferenceFeature::get_evaluated_symbols(session, &file, &ExprOrIdent::Expr(call.func.as_ref()), &from_module);
+                    symbols.into_iter().filter_map(|symbol| ReferenceFeature::import_chain(session, symbol).pop()).collect()
+                }
+            };
+            for callee in callees.into_iter() {
+                if callee.borrow().typ() == SymType::FUNCTION {
+                    CallHierarchyFeature::push_call(&mut calls, callee, file_info.borrow().text_range_to_range(&name_range));
+                }
+            }
+        });
+        let result = calls.into_iter().filter_map(|(callee, from_ranges)| {
+            CallHierarchyFeature::build_item(session, &callee).map(|to| CallHierarchyOutgoingCall { to, from_ranges })
+        }).collect::<Vec<_>>();
+        if result.is_empty() {
+            return None;
+        }
+        Some(result)
+    }
+
+    /* Return the method that super().name() resolves to from the given model class: the last method with this name
+    in the classes of the model that are loaded before the class, in the modules its module depends on */
+    pub fn get_super_method(session: &mut SessionInfo, class: &Rc<RefCell<Symbol>>, name: &OYarn) -> Option<Rc<RefCell<Symbol>>> {
+        let model_name = class.borrow().as_class_sym()._model.as_ref()?.name.clone();
+        let methods = ImplementationFeature::get_model_methods(session, &model_name, name);
+        let class_key = ImplementationFeature::get_import_order(class);
+        let module = class.borrow().find_module();
+        for method in methods.iter().rev() {
+            let method_module = method.borrow().find_module();
+            let in_deps = match (&module, method_module) {
+                (Some(module), Some(method_module)) if Rc::ptr_eq(module, &method_module) => {
+                    ImplementationFeature::get_import_order(method) < class_key
+                },
+                (Some(module), Some(method_module)) => {
+                    let dir_name = method_module.borrow().as_module_package().dir_name.clone();
+                    ModuleSymbol::is_in_deps(session, module, &dir_name)
+                },
+                _ => true
+            };
+            if in_deps {
+                return Some(method.clone());
+            }
+        }
+        None
+    }
+
+    /* Call the callback on the calls of the file, whose callee has the given name if any. If a range is given,
+    only the calls of the body of the function declared at this range are visited. */
+    fn visit_calls(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, name: Option<&str>, function_range: Option<TextRange>, on_call: &mut dyn FnMut(&mut SessionInfo, &Rc<RefCell<FileInfo>>, &ExprCall)) {
+        let Some(path) = ReferenceFeature::get_python_file_path(file_symbol) else {
+            return;
+        };
+        let Some(file_info) = session.sync_odoo.get_file_mgr().borrow().get_file_info(&path) else {
+            return;
+        };
+        FileInfo::with_ast(&file_info, session, |session| {
+            let file_info_ast = file_info.borrow().file_info_ast.clone();
+            let file_info_ast = file_info_ast.borrow();
+            if let Some(stmts) = file_info_ast.get_stmts() {
+                let mut visitor = CallVisitor { name, function_range, calls: vec![] };
+                for stmt in stmts.iter() {
+                    visitor.visit_stmt(stmt);
+                }
+                for call in visitor.calls.iter() {
+                    on_call(session, &file_info, call);
+                }
+            }
+        });
+    }
+
+    fn get_from_module(file_symbol: &Rc<RefCell<Symbol>>) -> ContextValue {
+        match file_symbol.borrow().find_module() {
+            Some(module) => ContextValue::MODULE(Rc::downgrade(&module)),
+            None => ContextValue::BOOLEAN(false),
+        }
+    }
+
+    /* Name of the called function and its range: "write" in "self.write(vals)" */
+    fn get_callee_name(call: &ExprCall) -> Option<(&str, TextRange)> {
+        match call.func.as_ref() {
+            Expr::Name(name) => Some((name.id.as_str(), name.range)),
+            Expr::Attribute(attr) => Some((attr.attr.id.as_str(), attr.attr.range)),
+            _ => None
+        }
+    }
+
+    fn is_super_call(call: &ExprCall) -> bool {
+        let Expr::Attribute(attr) = call.func.as_ref() else {
+            return false;
+        };
+        let Expr::Call(super_call) = attr.value.as_ref() else {
+            return false;
+        };
+        matches!(super_call.func.as_ref(), Expr::Name(name) if name.id.as_str() == "super")
+    }
+
+    fn push_call(calls: &mut Vec<(Rc<RefCell<Symbol>>, Vec<Range>)>, symbol: Rc<RefCell<Symbol>>, range: Range) {
+        match calls.iter_mut().find(|(s, _)| Rc::ptr_eq(s, &symbol)) {
+            Some((_, ranges)) => {
+                if !ranges.contains(&range) {
+                    ranges.push(range);
+                }
+            },
+            None => calls.push((symbol, vec![range]))
+        }
+    }
+
+    fn build_item(session: &mut SessionInfo, function: &Rc<RefCell<Symbol>>) -> Option<CallHierarchyItem> {
+        let file = function.borrow().get_file()?.upgrade()?;
+        let path = ReferenceFeature::get_python_file_path(&file)?;
+        let location = FeaturesUtils::get_symbol_location(session, function)?;
+        let function_bw = function.borrow();
+        let module_name = function_bw.find_module().map(|m| m.borrow().name().to_string());
+        let (kind, detail) = match function_bw.parent().and_then(|p| p.upgrade()) {
+            Some(parent) if parent.borrow().typ() == SymType::CLASS => {
+                let parent_bw = parent.borrow();
+                let class_name = match parent_bw.as_class_sym()._model.as_ref() {
+                    Some(model_data) => model_data.name.to_string(),
+                    None => parent_bw.name().to_string()
+                };
+                let detail = match module_name {
+                    Some(module_name) => format!("{} ({})", class_name, module_name),
+                    None => class_name
+                };
+                (SymbolKind::METHOD, Some(detail))
+            },
+            _ => (SymbolKind::FUNCTION, module_name)
+        };
+        Some(CallHierarchyItem {
+            name: function_bw.name().to_string(),
+            kind,
+            tags: None,
+            detail,
+            uri: location.uri,
+            range: location.range,
+            selection_range: location.range,
+            data: Some(json!({
+                "path": path,
+                "name": function_bw.name().to_string(),
+                "start": function_bw.range().start().to_u32(),
+            })),
+        })
+    }
+
+    fn get_function(session: &mut SessionInfo, item: &CallHierarchyItem) -> Option<Rc<RefCell<Symbol>>> {
+        let data = item.data.as_ref()?;
+        let path = data.get("path").and_then(Value::as_str)?;
+        let name = data.get("name").and_then(Value::as_str)?;
+        let start = data.get("start").and_then(Value::as_u64)? as u32;
+        let file_symbol = SyncOdoo::get_symbol_of_opened_file(session, &PathBuf::from(path))?;
+        let function = Symbol::get_scope_symbol(file_symbol, start, true);
+        if function.borrow().typ() != SymType::FUNCTION || function.borrow().name().as_str() != name {
+            return None;
+        }
+        Some(function)
+    }
+}
+
+/* Collect the calls of an ast. If a function range is given, only the calls in the body of this function are
+collected, without the ones of the nested functions and classes */
+struct CallVisitor<'a, 'b> {
+    name: Option<&'b str>,
+    function_range: Option<TextRange>,
+    calls: Vec<&'a ExprCall>,
+}
+
+impl<'a, 'b> Visitor<'a> for CallVisitor<'a, 'b> {
+
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        if let Some(function_range) = self.function_range {
+            if stmt.range() == function_range {
+                if let Stmt::FunctionDef(func_def) = stmt {
+                    for body_stmt in func_def.body.iter() {
+                        self.visit_stmt(body_stmt);
+                    }
+                }
+                return;
+            }
+            if !stmt.range().contains_range(function_range) && !function_range.contains_range(stmt.range()) {
+                return;
+            }
+            if function_range.contains_range(stmt.range()) && matches!(stmt, Stmt::FunctionDef(_) | Stmt::ClassDef(_)) {
+                return;
+            }
+        }
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let Expr::Call(call) = expr {
+            let matches_name = match self.name {
+                Some(name) => CallHierarchyFeature::get_callee_name(call).map(|(callee_name, _)| callee_name == name).unwrap_or(false),
+                None => true
+            };
+            if matches_name {
+                self.calls.push(call);
+            }
+        }
+        walk_expr(self, expr);
+    }
+}
@@ -0,0 +1,360 @@
+use std::{cell::RefCell, collections::HashMap, fs, path::PathBuf, rc::Rc};
+
+use lsp_types::{CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionResponse, Diagnostic, NumberOrString, Position, TextEdit, WorkspaceEdit};
+use ruff_python_ast::{visitor::{walk_stmt, Visitor}, Expr, ExprDict, ExprList, Stmt};
+use ruff_python_parser::{Token, TokenKind};
+use ruff_text_size::{Ranged, TextRange, TextSize};
+
+use crate::{constants::OYarn, core::{file_mgr::{FileInfo, FileMgr}, symbols::symbol::Symbol}, oyarn, threads::SessionInfo, utils::PathSanitizer as _};
+
+/* Diagnostics raised when a model or a module is used without being in the dependencies of the current module */
+const MISSING_DEPENDENCY_CODES: [&str; 5] = ["OLS03001", "OLS03003", "OLS03004", "OLS03015", "OLS05055"];
+
+pub struct CodeActionFeature {}
+
+impl CodeActionFeature {
+
+    pub fn get_code_actions(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, path: &String, params: &CodeActionParams) -> Option<CodeActionResponse> {
+        let mut actions = vec![];
+        for diagnostic in params.context.diagnostics.iter() {
+            let Some(NumberOrString::String(code)) = diagnostic.code.as_ref() else {
+                continue;
+            };
+            if !code.starts_with("OLS") {
+                continue;
+            }
+            if MISSING_DEPENDENCY_CODES.contains(&code.as_str()) {
+                CodeActionFeature::add_missing_dependency_actions(session, file_symbol, diagnostic, &mut actions);
+            }
+            if path.ends_with(".py") || path.ends_with(".pyi") {
+                CodeActionFeature::add_noqa_actions(file_info, &params.text_document.uri, diagnostic, code, &mut actions);
+            }
+            CodeActionFeature::add_diagnostic_filter_action(session, path, diagnostic, code, &mut actions);
+        }
+        if actions.is_empty() {
+            return None;
+        }
+        Some(actions)
+    }
+
+    /* Offer to add the module declaring the model (or the imported module) to the depends of the current module */
+    fn add_missing_dependency_actions(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, diagnostic: &Diagnostic, actions: &mut Vec<CodeActionOrCommand>) {
+        let Some(current_module) = file_symbol.borrow().find_module() else {
+            return;
+        };
+        let current_dir_name = current_module.borrow().as_module_package().dir_name.clone();
+        let mut module_names: Vec<OYarn> = vec![];
+        if let Some(module_name) = diagnostic.data.as_ref().and_then(|data| data.get("module")).and_then(|module| module.as_str()) {
+            module_names.push(oyarn!("{}", module_name));
+        }
+        if let Some(model_name) = diagnostic.data.as_ref().and_then(|data| data.get("model")).and_then(|model| model.as_str()) {
+            if let Some(model) = session.sync_odoo.models.get(&oyarn!("{}", model_name)).cloned() {
+                for symbol in model.borrow().get_main_symbols(session, None).iter() {
+                    let Some(module) = symbol.borrow().find_module() else {
+                        continue;
+                    };
+                    let dir_name = module.borrow().as_module_package().dir_name.clone();
+                    if !module_names.contains(&dir_name) {
+                        module_names.push(dir_name);
+                    }
+                }
+            }
+        }
+        module_names.retain(|name| *name != current_dir_name);
+        if module_names.is_empty() {
+            return;
+        }
+        let manifest_path = PathBuf::from(current_module.borrow().as_module_package().path.clone()).join("__manifest__.py").sanitize();
+        let Some(file_info) = session.sync_odoo.get_file_mgr().borrow().get_file_info(&manifest_path) else {
+            return;
+        };
+        FileInfo::with_ast(&file_info, session, |_| {
+            let is_preferred = module_names.len() == 1;
+            for module_name in module_names.iter() {
+                let Some(edit) = CodeActionFeature::get_depends_edit(&file_info, module_name) else {
+                    continue;
+                };
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: format!("Add '{}' to the depends of {}", module_name, current_dir_name),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![diagnostic.clone()]),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(FileMgr::pathname2uri(&manifest_path), vec![edit])])),
+                        ..Default::default()
+                    }),
+                    is_preferred: Some(is_preferred),
+                    ..Default::default()
+                }));
+            }
+        });
+    }
+
+    /* Edit inserting the module in the depends of the manifest, following the quotes and the layout of the existing list */
+    fn get_depends_edit(file_info: &Rc<RefCell<FileInfo>>, module_name: &OYarn) -> Option<TextEdit> {
+        let file_info_ast = file_info.borrow().file_info_ast.clone();
+        let file_info_ast = file_info_ast.borrow();
+        let source = file_info_ast.text_rope.as_ref()?.to_string();
+        let Some(Stmt::Expr(expr)) = file_info_ast.get_stmts()?.first() else {
+            return None;
+        };
+        let Expr::Dict(dict) = expr.value.as_ref() else {
+            return None;
+        };
+        let depends = dict.items.iter().find(|item| matches!(&item.key, Some(Expr::StringLiteral(key)) if key.value.to_str() == "depends"));
+        let (offset, new_text) = match depends.map(|item| &item.value) {
+            Some(Expr::List(list)) => CodeActionFeature::insert_in_list(&source, list, module_name)?,
+            Some(_) => return None,
+            None => CodeActionFeature::insert_depends_key(&source, dict, module_name),
+        };
+        let position = file_info.borrow().offset_to_position(offset.to_usize());
+        Some(TextEdit {
+            range: lsp_types::Range::new(position, position),
+            new_text,
+        })
+    }
+
+    pub fn insert_in_list(source: &str, list: &ExprList, module_name: &OYarn) -> Option<(TextSize, String)> {
+        let mut quote = '\'';
+        for elt in list.elts.iter() {
+            if let Expr::StringLiteral(string) = elt {
+                if string.value.to_str() == module_name.as_str() {
+                    return None;
+                }
+                if source[string.range].starts_with('"') {
+                    quote = '"';
+                }
+            }
+        }
+        let value = format!("{}{}{}", quote, module_name, quote);
+        let Some(last) = list.elts.last() else {
+            return Some((list.range.start() + TextSize::new(1), value));
+        };
+        let is_multiline = source[list.range.start().to_usize()..last.range().start().to_usize()].contains('\n');
+        if !is_multiline {
+            return Some((last.range().end(), format!(", {}", value)));
+        }
+        let indent = CodeActionFeature::get_indent(source, last.range().start());
+        let after_last = &source[last.range().end().to_usize()..list.range.end().to_usize()];
+        match after_last.find(',') {
+            Some(comma) => Some((last.range().end() + TextSize::new(comma as u32 + 1), format!("\n{}{},", indent, value))),
+            None => Some((last.range().end(), format!(",\n{}{}", indent, value))),
+        }
+    }
+
+    pub fn insert_depends_key(source: &str, dict: &ExprDict, module_name: &OYarn) -> (TextSize, String) {
+        let key = format!("'depends': ['{}'],", module_name);
+        let Some(first_key) = dict.items.first().and_then(|item| item.key.as_ref()) else {
+            return (dict.range.start() + TextSize::new(1), key);
+        };
+        let is_multiline = source[dict.range.start().to_usize()..first_key.range().start().to_usize()].contains('\n');
+        if is_multiline {
+            (first_key.range().start(), format!("{}\n{}", key, CodeActionFeature::get_indent(source, first_key.range().start())))
+        } else {
+            (first_key.range().start(), format!("{} ", key))
+        }
+    }
+
+    /* whitespaces between the start of the line and the offset */
+    fn get_indent(source: &str, offset: TextSize) -> String {
+        let before = &source[..offset.to_usize()];
+        let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+        before[line_start..].chars().take_while(|c| c.is_whitespace()).collect()
+    }
+
+    /* Offer to add a noqa comment on the line of the diagnostic, and before the function or class that contains it */
+    fn add_noqa_actions(file_info: &Rc<RefCell<FileInfo>>, uri: &lsp_types::Uri, diagnostic: &Diagnostic, code: &String, actions: &mut Vec<CodeActionOrCommand>) {
+        let file_info_ast = file_info.borrow().file_info_ast.clone();
+        let file_info_ast = file_info_ast.borrow();
+        let (Some(rope), Some(indexed_module)) = (file_info_ast.text_rope.as_ref(), file_info_ast.indexed_module.as_ref()) else {
+            return;
+        };
+        let line = diagnostic.range.start.line as usize;
+        if line >= rope.len_lines() {
+            return;
+        }
+        let source = rope.to_string();
+        let tokens = indexed_module.parsed.tokens();
+        let line_tokens = CodeActionFeature::get_line_tokens(tokens, rope, line);
+        if let Some((offset, new_text)) = CodeActionFeature::get_line_noqa_edit(&source, &line_tokens, rope, line, code) {
+            actions.push(CodeActionFeature::build_noqa_action(format!("Suppress {} on this line", code), file_info, uri, diagnostic, offset, new_text));
+        }
+        let offset = FileInfo::position_to_offset_with_rope(rope, diagnostic.range.start.line, diagnostic.range.start.character);
+        let mut visitor = EnclosingDefVisitor { offset: TextSize::new(offset as u32), found: None };
+        for stmt in indexed_module.parsed.syntax().body.iter() {
+            visitor.visit_stmt(stmt);
+        }
+        let Some((def_range, kind, name)) = visitor.found else {
+            return;
+        };
+        let def_line = rope.byte_to_line(def_range.start().to_usize());
+        let previous_line_tokens = if def_line > 0 { CodeActionFeature::get_line_tokens(tokens, rope, def_line - 1) } else { vec![] };
+        let edit = match previous_line_tokens.as_slice() {
+            [comment] if comment.kind() == TokenKind::Comment && FileInfo::is_noqa_comment(&source[comment.range()]) => {
+                CodeActionFeature::merge_noqa_code(&source[comment.range()], comment.range(), code)
+            },
+            _ => {
+                let line_start = TextSize::new(rope.line_to_byte(def_line) as u32);
+                Some((line_start, format!("{}# noqa: {}\n", CodeActionFeature::get_indent(&source, def_range.start()), code)))
+            }
+        };
+        if let Some((offset, new_text)) = edit {
+            actions.push(CodeActionFeature::build_noqa_action(format!("Suppress {} for {} '{}'", code, kind, name), file_info, uri, diagnostic, offset, new_text));
+        }
+    }
+
+    /* Tokens that are on the given line, without the newlines */
+    fn get_line_tokens<'a>(tokens: &'a [Token], rope: &ropey::Rope, line: usize) -> Vec<&'a Token> {
+        let line_start = TextSize::new(rope.line_to_byte(line) as u32);
+        let line_end = TextSize::new(rope.line_to_byte(line + 1) as u32);
+        tokens.iter().filter(|token|
+            token.start() < line_end && token.end() > line_start
+            && !matches!(token.kind(), TokenKind::Newline | TokenKind::NonLogicalNewline)
+        ).collect()
+    }
+
+    fn get_line_noqa_edit(source: &str, line_tokens: &[&Token], rope: &ropey::Rope, line: usize, code: &String) -> Option<(TextSize, String)> {
+        let last = line_tokens.last()?;
+        if last.kind() == TokenKind::Comment {
+            //a comment alone on its line would be a noqa for the next function or class
+            if line_tokens.len() < 2 {
+                return None;
+            }
+            let text = &source[last.range()];
+            if FileInfo::is_noqa_comment(text) {
+                return CodeActionFeature::merge_noqa_code(text, last.range(), code);
+            }
+            //the noqa has to be at the start of the comment
+            return Some((last.start(), format!("# noqa: {} ", code)));
+        }
+        if last.end().to_usize() > rope.line_to_byte(line + 1) {
+            return None; //the line ends in a multiline string
+        }
+        Some((last.end(), format!("  # noqa: {}", code)))
+    }
+
+    /* Add the code at the end of an existing noqa comment, if it does not already suppress it */
+    pub fn merge_noqa_code(text: &str, range: TextRange, code: &String) -> Option<(TextSize, String)> {
+        let after_noqa = text.split("noqa").nth(1)?;
+        let codes: Vec<&str> = after_noqa.split(|c: char| c == ',' || c.is_whitespace() || c == ':').filter(|c| !c.is_empty()).collect();
+        if codes.is_empty() || codes.contains(&code.as_str()) {
+            return None;
+        }
+        Some((range.start() + TextSize::new(text.trim_end().len() as u32), format!(", {}", code)))
+    }
+
+    fn build_noqa_action(title: String, file_info: &Rc<RefCell<FileInfo>>, uri: &lsp_types::Uri, diagnostic: &Diagnostic, offset: TextSize, new_text: String) -> CodeActionOrCommand {
+        let position = file_info.borrow().offset_to_position(offset.to_usize());
+        CodeActionOrCommand::CodeAction(CodeAction {
+            title,
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diagnostic.clone()]),
+            edit: Some(WorkspaceEdit {
+                changes: Some(HashMap::from([(uri.clone(), vec![TextEdit { range: lsp_types::Range::new(position, position), new_text }])])),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+    }
+
+    /* Offer to add a diagnostic_filters entry for the file in the odools.toml of its workspace folder, or in the
+    config file given to the server. The path is written relatively to the workspace folder so the entry can be shared */
+    fn add_diagnostic_filter_action(session: &mut SessionInfo, path: &String, diagnostic: &Diagnostic, code: &String, actions: &mut Vec<CodeActionOrCommand>) {
+        let workspace_folder = session.sync_odoo.get_file_mgr().borrow().get_workspace_folders().values()
+            .filter(|folder| path.starts_with(folder.as_str()))
+            .max_by_key(|folder| folder.len())
+            .cloned();
+        let Some(workspace_folder) = workspace_folder else {
+            return;
+        };
+        let toml_path = PathBuf::from(&workspace_folder).join("odools.toml");
+        let toml_path = if toml_path.is_file() {
+            toml_path.sanitize()
+        } else {
+            match session.sync_odoo.config_path.as_ref().filter(|config_path| PathBuf::from(config_path).is_file()) {
+                Some(config_path) => PathBuf::from(config_path).sanitize(),
+                None => return,
+            }
+        };
+        let Ok(relative_path) = PathBuf::from(path).strip_prefix(&workspace_folder).map(|p| p.sanitize()) else {
+            return;
+        };
+        let Ok(content) = fs::read_to_string(&toml_path) else {
+            return;
+        };
+        let profile = session.sync_odoo.config.name.clone();
+        let filter = format!("[[config.diagnostic_filters]]\npaths = ['**/{}']\ncodes = ['{}']\n", glob::Pattern::escape(&relative_path), code);
+        let new_profile = format!("[[config]]\nname = \"{}\"\n\n{}", profile, filter);
+        let Some((position, new_text)) = CodeActionFeature::get_diagnostic_filter_edit(&content, &profile, &filter, &new_profile) else {
+            return;
+        };
+        let edit = WorkspaceEdit {
+            changes: Some(HashMap::from([(FileMgr::pathname2uri(&toml_path), vec![TextEdit { range: lsp_types::Range::new(position, position), new_text }])])),
+            ..Default::default()
+        };
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: format!("Suppress {} for this path in {}", code, toml_path.rsplit('/').next().unwrap_or("odools.toml")),
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diagnostic.clone()]),
+            edit: Some(edit),
+            ..Default::default()
+        }));
+    }
+
+    /* Insert the filter at the end of the [[config]] table of the current profile, or add the profile at the end of the file */
+    fn get_diagnostic_filter_edit(content: &str, profile: &str, filter: &str, new_profile: &str) -> Option<(Position, String)> {
+        let lines: Vec<&str> = content.lines().collect();
+        let headers: Vec<usize> = lines.iter().enumerate().filter(|(_, line)| line.trim() == "[[config]]").map(|(index, _)| index).collect();
+        for (index, header) in headers.iter().enumerate() {
+            let end = headers.get(index + 1).copied().unwrap_or(lines.len());
+            let is_profile = lines[*header..end].iter().any(|line| {
+                let Some((key, value)) = line.split_once('=') else {
+                    return false;
+                };
+                key.trim() == "name" && value.trim().trim_matches(|c: char| c == '"' || c == '\'') == profile
+            });
+            if !is_profile {
+                continue;
+            }
+            if lines[*header..end].iter().any(|line| line.trim_start().starts_with("diagnostic_filters")) {
+                return None; //an inline array can't be extended with a table
+            }
+            if end < lines.len() {
+                return Some((Position::new(end as u32, 0), format!("{}\n", filter)));
+            }
+            return Some(CodeActionFeature::append_to_content(content, &lines, filter));
+        }
+        Some(CodeActionFeature::append_to_content(content, &lines, new_profile))
+    }
+
+    fn append_to_content(content: &str, lines: &[&str], text: &str) -> (Position, String) {
+        if content.is_empty() {
+            return (Position::new(0, 0), text.to_string());
+        }
+        if content.ends_with('\n') {
+            return (Position::new(lines.len() as u32, 0), format!("\n{}", text));
+        }
+        let last_line = lines.last().map(|line| line.encode_utf16().count()).unwrap_or(0);
+        (Position::new(lines.len().saturating_sub(1) as u32, last_line as u32), format!("\n\n{}", text))
+    }
+}
+
+/* Find the innermost function or class that contains the offset */
+struct EnclosingDefVisitor {
+    offset: TextSize,
+    found: Option<(TextRange, &'static str, String)>,
+}
+
+impl<'a> Visitor<'a> for EnclosingDefVisitor {
+
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        if !stmt.range().contains(self.offset) {
+            return;
+        }
+        match stmt {
+            Stmt::FunctionDef(func_def) => self.found = Some((func_def.range, "function", func_def.name.id.to_string())),
+            Stmt::ClassDef(class_def) => self.found = Some((class_def.range, "class", class_def.name.id.to_string())),
+            _ => {}
+        }
+        walk_stmt(self, stmt);
+    }
+}
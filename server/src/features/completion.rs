@@ -2,7 +2,7 @@ use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
 use std::{cell::RefCell, rc::Rc};
 use itertools::Itertools;
-use lsp_types::{CompletionItem, CompletionItemKind, CompletionItemLabelDetails, CompletionList, CompletionResponse, MarkupContent};
+use lsp_types::{CompletionItem, CompletionItemKind, CompletionItemLabelDetails, CompletionList, CompletionResponse, CompletionTextEdit, InsertTextFormat, MarkupContent, TextEdit};
 use ruff_python_ast::{Decorator, ExceptHandler, Expr, ExprAttribute, ExprIf, ExprName, ExprSubscript, ExprYield, Stmt, StmtGlobal, StmtImport, StmtImportFrom, StmtNonlocal};
 use ruff_text_size::{Ranged, TextSize};
 
@@ -11,6 +11,8 @@ use crate::core::evaluation::{Context, ContextValue, Evaluation, EvaluationSymbo
 use crate::core::import_resolver;
 use crate::core::odoo::SyncOdoo;
 use crate::core::symbols::module_symbol::ModuleSymbol;
+use crate::core::xml_arch_builder::XmlArchBuilder;
+use crate::core::xml_data::OdooData;
 use crate::threads::SessionInfo;
 use crate::utils::compare_semver;
 use crate::{oyarn, Sy, S};
@@ -51,7 +53,141 @@ impl CompletionFeature {
         let file_info_ast = file_info.borrow().file_info_ast.clone();
         let file_info_ast = file_info_ast.borrow();
         let ast = file_info_ast.get_stmts().unwrap();
-        complete_vec_stmt(ast, session, file_symbol, offset).or_else(|| complete_name(session, file_symbol, offset, false, &S!("")))
+        let result = complete_vec_stmt(ast, session, file_symbol, offset).or_else(|| complete_name(session, file_symbol, offset, false, &S!("")));
+        if session.is_request_cancelled() {
+            return None;
+        }
+        result
+    }
+
+    pub fn autocomplete_xml(session: &mut SessionInfo,
+        file_symbol: &Rc<RefCell<Symbol>>,
+        file_info: &Rc<RefCell<FileInfo>>,
+        line: u32,
+        character: u32
+    ) -> Option<CompletionResponse> {
+        let offset = file_info.borrow().position_to_offset(line, character);
+        let data = file_info.borrow().file_info_ast.borrow().text_rope.as_ref()?.to_string();
+        let context = get_xml_completion_context(data.get(..offset)?)?;
+        let current_module = file_symbol.borrow().find_module()?;
+        let mut items = vec![];
+        match context {
+            XmlCompletionContext::TAG_NAME(parent) => {
+                for tag in XmlArchBuilder::rng_allowed_children(parent.as_ref().map(|p| p.name.as_str())) {
+                    items.push(CompletionItem {
+                        label: tag.to_string(),
+                        kind: Some(CompletionItemKind::KEYWORD),
+                        ..Default::default()
+                    });
+                }
+            },
+            XmlCompletionContext::ATTRIBUTE_NAME(tag) => {
+                for attribute in XmlArchBuilder::rng_allowed_attributes(&tag.name) {
+                    if tag.attributes.iter().any(|(name, _)| name == attribute) {
+                        continue;
+                    }
+                    items.push(CompletionItem {
+                        label: attribute.to_string(),
+                        kind: Some(CompletionItemKind::PROPERTY),
+                        insert_text: Some(format!("{}=\"$1\"", attribute)),
+                        insert_text_format: Some(InsertTextFormat::SNIPPET),
+                        ..Default::default()
+                    });
+                }
+            },
+            XmlCompletionContext::ATTRIBUTE_VALUE(tag, attribute, value_start, parents) => {
+                let mut value_start = value_start;
+                if attribute == "groups" {
+                    //comma separated list, only complete the last group
+                    let value = &data[value_start..offset];
+                    let last = value.rsplit(',').next().unwrap_or("");
+                    let last_trimmed = last.trim_start_matches(|c: char| c.is_whitespace() || c == '-' || c == '!');
+                    value_start = offset - last_trimmed.len();
+                }
+                let range = file_info.borrow().std_range_to_range(&(value_start..offset));
+                let prefix = data[value_start..offset].to_string();
+                match (tag.name.as_str(), attribute.as_str()) {
+                    ("record" | "field" | "value" | "function" | "delete", "model") => {
+                        complete_xml_model_names(session, &current_module, &prefix, range, &mut items);
+                    },
+                    ("field", "name") => {
+                        let record_model = parents.iter().rev().find(|t| t.name == "record")
+                            .and_then(|t| t.attributes.iter().find(|(name, _)| name == "model").map(|(_, value)| value.clone()));
+                        if let Some(record_model) = record_model {
+                            complete_xml_field_names(session, &current_module, &record_model, &prefix, range, &mut items);
+                        }
+                    },
+                    ("field", "ref") | ("menuitem", "parent") | ("menuitem", "action") | (_, "groups") | ("template", "inherit_id") => {
+                        let accepts = |data: &OdooData| match (attribute.as_str(), data) {
+                            ("action", OdooData::RECORD(record)) => record.model.0.starts_with("ir.actions"),
+                            ("parent", OdooData::MENUITEM(_)) => true,
+                            ("groups", OdooData::RECORD(record)) => record.model.0 == "res.groups",
+                            ("inherit_id", OdooData::TEMPLATE(_)) => true,
+                            ("inherit_id", OdooData::RECORD(record)) => record.model.0 == "ir.ui.view",
+                            ("ref", OdooData::DELETE(_)) => false,
+                            ("ref", _) => true,
+                            _ => false
+                        };
+                        complete_xml_ids(session, &current_module, &accepts, range, &mut items);
+                    },
+                    _ => {}
+                }
+            }
+        }
+        Some(CompletionResponse::List(CompletionList {
+            is_incomplete: false,
+            items
+        }))
+    }
+
+    pub fn autocomplete_csv(session: &mut SessionInfo,
+        file_symbol: &Rc<RefCell<Symbol>>,
+        file_info: &Rc<RefCell<FileInfo>>,
+        line: u32,
+        character: u32
+    ) -> Option<CompletionResponse> {
+        let offset = file_info.borrow().position_to_offset(line, character);
+        let data = file_info.borrow().file_info_ast.borrow().text_rope.as_ref()?.to_string();
+        let current_module = file_symbol.borrow().find_module()?;
+        let model_name = file_symbol.borrow().as_csv_file_sym().model_name.clone();
+        let model = session.sync_odoo.models.get(&model_name).cloned()?;
+        let main_symbol = model.borrow().get_main_symbols(session, Some(current_module.clone())).first().cloned()?;
+        let (column, value_start) = get_csv_cell_at(data.get(..offset)?);
+        let range = file_info.borrow().std_range_to_range(&(value_start..offset));
+        let headers = file_symbol.borrow().as_csv_file_sym().headers.clone();
+        //the header record ends with the line of its last value, that can be followed by a header being typed
+        let in_header = match headers.last() {
+            Some((_, last_range)) => {
+                let line_end = data.get(last_range.end..).and_then(|rest| rest.find('\n')).map(|i| i + last_range.end).unwrap_or(data.len());
+                offset <= line_end
+            },
+            None => true,
+        };
+        let mut items = vec![];
+        if in_header {
+            complete_csv_headers(session, &current_module, &main_symbol, &data[value_start..offset], range, &mut items);
+        } else {
+            let header = headers.get(column)?.0.to_string();
+            let field_name = header.strip_suffix(":id").or_else(|| header.strip_suffix("/id"))?;
+            let (fields, _) = main_symbol.borrow().get_member_symbol(session, &field_name.to_string(), Some(current_module.clone()), false, true, false, false);
+            let mut comodels = HashSet::new();
+            for field in fields.iter().filter(|f| f.borrow().typ() == SymType::VARIABLE) {
+                for comodel_class in field.borrow().as_variable().get_relational_model(session, Some(current_module.clone())) {
+                    if let Some(comodel) = comodel_class.borrow().as_class_sym()._model.as_ref() {
+                        comodels.insert(comodel.name.clone());
+                    }
+                }
+            }
+            if comodels.is_empty() {
+                return None;
+            }
+            let accepts = |data: &OdooData| matches!(data, OdooData::RECORD(record) if comodels.contains(&record.model.0));
+            complete_xml_ids(session, &current_module, &accepts, range, &mut items);
+        }
+        Some(CompletionResponse::List(CompletionList {
+            is_incomplete: false,
+            items
+        }))
     }
 }
 
@@ -791,6 +927,9 @@ fn complete_attribut(session: &mut SessionInfo, file: &Rc<RefCell<Symbol>>, attr
 
         let from_module = file.borrow().find_module().clone();
         for parent_eval in parent.iter() {
+            if session.is_request_cancelled() {
+                break;
+            }
             //TODO shouldn't we set and clean context here?
             let parent_sym_eval = parent_eval.symbol.get_symbol(session, &mut None, &mut vec![], Some(scope.clone()));
             if !parent_sym_eval.is_expired_if_weak() {
@@ -848,11 +987,16 @@ fn complete_name_expression(session: &mut SessionInfo, file: &Rc<RefCell<Symbol>
 fn complete_name(session: &mut SessionInfo, file: &Rc<RefCell<Symbol>>, offset: usize, is_param: bool, name: &String) -> Option<CompletionResponse> {
     let scope = Symbol::get_scope_symbol(file.clone(), offset as u32, is_param);
     let symbols = Symbol::get_all_inferred_names(&scope, name, offset as u32);
+    let mut items = vec![];
+    for (_symbol_name, symbols) in symbols {
+        if session.is_request_cancelled() {
+            break;
+        }
+        items.push(build_completion_item_from_symbol(session, symbols, HashMap::new()));
+    }
     Some(CompletionResponse::List(CompletionList {
         is_incomplete: false,
-        items: symbols.into_iter().map(|(_symbol_name, symbols)| {
-            build_completion_item_from_symbol(session, symbols, HashMap::new())
-        }).collect::<Vec<_>>(),
+        items,
     }))
 }
 
@@ -1037,6 +1181,9 @@ fn add_model_attributes(
 ){
     let all_symbols = Symbol::all_members(&parent_sym, session, true, only_fields, only_methods, from_module.clone(), is_super);
     for (_symbol_name, symbols) in all_symbols {
+        if session.is_request_cancelled() {
+            return;
+        }
         //we could use symbol_name to remove duplicated names, but it would hide functions vs variables
         let Some((final_sym, _dep)) = symbols.first() else {
             continue;
@@ -1143,4 +1290,268 @@ fn get_completion_item_kind(symbol: &Rc<RefCell<Symbol>>) -> CompletionItemKind
         SymType::XML_FILE => CompletionItemKind::FILE,
         SymType::CSV_FILE => CompletionItemKind::FILE,
     }
-}
\ No newline at end of file
+}
+
+/* **********************************************************************
+******************************** XML ************************************
+*********************************************************************** */
+
+/* Element opened before the cursor in an xml file */
+struct XmlOpenTag {
+    name: String,
+    attributes: Vec<(String, String)>,
+}
+
+#[allow(non_camel_case_types)]
+enum XmlCompletionContext {
+    TAG_NAME(Option<XmlOpenTag>), //parent element
+    ATTRIBUTE_NAME(XmlOpenTag), //element being written
+    ATTRIBUTE_VALUE(XmlOpenTag, String, usize, Vec<XmlOpenTag>), //element being written, attribute name, start of the value, parents
+}
+
+/* Find what is written at the end of text. The document is usually invalid while typing, so it is
+scanned as text instead of being parsed by roxmltree */
+fn get_xml_completion_context(text: &str) -> Option<XmlCompletionContext> {
+    let mut parents: Vec<XmlOpenTag> = vec![];
+    let mut index = 0;
+    let mut current_tag = None;
+    while let Some(lt) = text[index..].find('<') {
+        let start = index + lt;
+        let rest = &text[start..];
+        if rest.starts_with("<!--") {
+            index = start + rest.find("-->")? + 3;
+            continue;
+        }
+        if rest.starts_with("<?") || rest.starts_with("<!") {
+            index = start + rest.find('>')? + 1;
+            continue;
+        }
+        let Some(end) = find_xml_tag_end(rest) else {
+            current_tag = Some(rest);
+            break;
+        };
+        let tag_text = &rest[..=end];
+        if let Some(closing) = tag_text.strip_prefix("</") {
+            let name = closing.trim_end_matches('>').trim();
+            if let Some(position) = parents.iter().rposition(|t| t.name == name) {
+                parents.truncate(position);
+            }
+        } else if !tag_text.ends_with("/>") {
+            parents.push(parse_xml_open_tag(tag_text));
+        }
+        index = start + end + 1;
+    }
+    let current_tag = current_tag?;
+    let tag_name = &current_tag[1..];
+    if tag_name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.' || c == ':') {
+        return Some(XmlCompletionContext::TAG_NAME(parents.pop()));
+    }
+    if tag_name.starts_with('/') {
+        return None;
+    }
+    //inside the tag: check if the cursor is in a quoted value
+    let mut quote = None;
+    let mut quote_start = 0;
+    for (i, c) in current_tag.char_indices() {
+        match quote {
+            Some(q) if q == c => quote = None,
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                quote_start = i;
+            },
+            _ => {}
+        }
+    }
+    let tag = parse_xml_open_tag(current_tag);
+    if quote.is_some() {
+        let attribute = current_tag[..quote_start].trim_end().strip_suffix('=')?.trim_end()
+            .rsplit(|c: char| c.is_whitespace()).next()?.to_string();
+        let value_start = text.len() - current_tag.len() + quote_start + 1;
+        return Some(XmlCompletionContext::ATTRIBUTE_VALUE(tag, attribute, value_start, parents));
+    }
+    let before_word = current_tag.trim_end_matches(|c: char| c.is_alphanumeric() || c == '_' || c == '-' || c == ':');
+    if before_word.ends_with(char::is_whitespace) {
+        return Some(XmlCompletionContext::ATTRIBUTE_NAME(tag));
+    }
+    None
+}
+
+/* Return the index of the '>' closing the tag starting at the beginning of text, ignoring quoted values */
+fn find_xml_tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match quote {
+            Some(q) if q == c => quote = None,
+            Some(_) => {},
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '>' => return Some(i),
+            None => {}
+        }
+    }
+    None
+}
+
+fn parse_xml_open_tag(tag_text: &str) -> XmlOpenTag {
+    let content = tag_text.trim_start_matches('<').trim_end_matches('>').trim_end_matches('/');
+    let name = content.split(|c: char| c.is_whitespace()).next().unwrap_or("").to_string();
+    let mut attributes = vec![];
+    let mut rest = &content[name.len()..];
+    while let Some(equal) = rest.find('=') {
+        let attribute_name = rest[..equal].trim().to_string();
+        let after = rest[equal + 1..].trim_start();
+        let Some(quote) = after.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            break;
+        };
+        let value_end = after[1..].find(quote).map(|i| i + 1).unwrap_or(after.len());
+        attributes.push((attribute_name, after[1..value_end].to_string()));
+        rest = &after[(value_end + 1).min(after.len())..];
+    }
+    XmlOpenTag { name, attributes }
+}
+
+fn complete_xml_model_names(session: &mut SessionInfo, current_module: &Rc<RefCell<Symbol>>, prefix: &str, range: lsp_types::Range, items: &mut Vec<CompletionItem>) {
+    let models = session.sync_odoo.models.iter()
+        .filter(|(model_name, _)| model_name.starts_with(prefix) && model_name.as_str() != "_unknown")
+        .map(|(model_name, model)| (model_name.clone(), model.clone()))
+        .collect::<Vec<_>>();
+    for (model_name, model) in models.iter() {
+        //same filtering than XmlAstUtils::get_symbols: the model must be declared in the dependencies
+        if !model.borrow().all_symbols(session, Some(current_module.clone()), false).iter().any(|s| s.1.is_none()) {
+            continue;
+        }
+        items.push(CompletionItem {
+            label: model_name.to_string(),
+            kind: Some(CompletionItemKind::CLASS),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit { range, new_text: model_name.to_string() })),
+            ..Default::default()
+        });
+    }
+}
+
+fn complete_xml_field_names(session: &mut SessionInfo, current_module: &Rc<RefCell<Symbol>>, model_name: &str, prefix: &str, range: lsp_types::Range, items: &mut Vec<CompletionItem>) {
+    let Some(model) = session.sync_odoo.models.get(&oyarn!("{}", model_name)).cloned() else {
+        return;
+    };
+    let main_symbols = model.borrow().get_main_symbols(session, Some(current_module.clone()));
+    let Some(main_symbol) = main_symbols.first() else {
+        return;
+    };
+    let mut field_items = vec![];
+    add_model_attributes(session, &mut field_items, Some(current_module.clone()), main_symbol.clone(), false, true, false, prefix, &None);
+    for mut item in field_items {
+        item.text_edit = Some(CompletionTextEdit::Edit(TextEdit { range, new_text: item.label.clone() }));
+        items.push(item);
+    }
+}
+
+/* Complete the xml_ids declared in the dependencies of the module, if one of their data is accepted */
+fn complete_xml_ids(session: &mut SessionInfo, current_module: &Rc<RefCell<Symbol>>, accepts: &dyn Fn(&OdooData) -> bool, range: lsp_types::Range, items: &mut Vec<CompletionItem>) {
+    let current_module_name = current_module.borrow().as_module_package().dir_name.clone();
+    let modules = session.sync_odoo.modules.values().filter_map(|m| m.upgrade()).collect::<Vec<_>>();
+    for module in modules.iter() {
+        let module_name = module.borrow().as_module_package().dir_name.clone();
+        if !ModuleSymbol::is_in_deps(session, current_module, &module_name) {
+            continue;
+        }
+        let module_ref = module.borrow();
+        let module_sym = module_ref.as_module_package();
+        let xml_ids = module_sym.xml_id_locations.keys().chain(module_sym.xml_ids.keys()).collect::<HashSet<_>>();
+        for xml_id in xml_ids {
+            let mut data = module_sym.get_xml_id(xml_id);
+            data.extend(module_sym.xml_ids.get(xml_id).cloned().unwrap_or_default());
+            let Some(model) = data.iter().find(|d| accepts(d)).map(|d| match d {
+                OdooData::RECORD(record) => record.model.0.to_string(),
+                OdooData::MENUITEM(_) => S!("menuitem"),
+                OdooData::TEMPLATE(_) => S!("template"),
+                OdooData::DELETE(_) => S!(""),
+            }) else {
+                continue;
+            };
+            let full_id = if module_name == current_module_name {
+                xml_id.to_string()
+            } else {
+                format!("{}.{}", module_name, xml_id)
+            };
+            items.push(CompletionItem {
+                label: full_id.clone(),
+                kind: Some(CompletionItemKind::REFERENCE),
+                label_details: Some(CompletionItemLabelDetails {
+                    detail: None,
+                    description: Some(model),
+                }),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit { range, new_text: full_id })),
+                ..Default::default()
+            });
+        }
+    }
+}
+
+/* **********************************************************************
+******************************** CSV ************************************
+*********************************************************************** */
+
+/* Return the index of the column at the end of text, and the start of the value being written in this column.
+Commas in quoted cells are not separators, but a list of xml_ids in a quoted cell is completed one by one */
+fn get_csv_cell_at(text: &str) -> (usize, usize) {
+    let line_start = text.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let mut column = 0;
+    let mut cell_start = line_start;
+    let mut value_start = line_start;
+    let mut in_quotes = false;
+    for (i, c) in text[line_start..].char_indices() {
+        let i = line_start + i;
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                if i == cell_start {
+                    value_start = i + 1;
+                }
+            },
+            ',' if !in_quotes => {
+                column += 1;
+                cell_start = i + 1;
+                value_start = i + 1;
+            },
+            ',' => value_start = i + 1,
+            _ => {}
+        }
+    }
+    let value = &text[value_start..];
+    (column, value_start + value.len() - value.trim_start().len())
+}
+
+/* Complete the fields of the model, with the suffix used to import relational fields by xml_id */
+fn complete_csv_headers(session: &mut SessionInfo, current_module: &Rc<RefCell<Symbol>>, main_symbol: &Rc<RefCell<Symbol>>, prefix: &str, range: lsp_types::Range, items: &mut Vec<CompletionItem>) {
+    if "id".starts_with(prefix) {
+        items.push(CompletionItem {
+            label: S!("id"),
+            kind: Some(CompletionItemKind::FIELD),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit { range, new_text: S!("id") })),
+            ..Default::default()
+        });
+    }
+    let all_fields = Symbol::all_members(main_symbol, session, true, true, false, Some(current_module.clone()), false);
+    for (field_name, symbols) in all_fields {
+        if session.is_request_cancelled() {
+            return;
+        }
+        let Some((field, _dep)) = symbols.first() else {
+            continue;
+        };
+        if !field_name.starts_with(prefix) {
+            continue;
+        }
+        let suffix = if field.borrow().is_specific_field(session, &["Many2one"]) {
+            ":id"
+        } else if field.borrow().is_specific_field(session, &["One2many", "Many2many"]) {
+            "/id"
+        } else {
+            ""
+        };
+        let context_of_symbol = HashMap::from([(S!("base_attr"), ContextValue::SYMBOL(Rc::downgrade(main_symbol)))]);
+        let mut item = build_completion_item_from_symbol(session, vec![field.clone()], context_of_symbol);
+        item.label = format!("{}{}", field_name, suffix);
+        item.text_edit = Some(CompletionTextEdit::Edit(TextEdit { range, new_text: item.label.clone() }));
+        items.push(item);
+    }
+}
@@ -0,0 +1,76 @@
+use std::{cell::RefCell, ops::Range, rc::Rc};
+
+use crate::{core::{csv_arch_builder::CsvArchBuilder, symbols::symbol::Symbol, xml_id_usages::{XmlIdOccurrence, XmlIdUsages}}, features::xml_ast_utils::{XmlAstResult, XmlAstUtils}, threads::SessionInfo};
+
+/* Element of a csv data file under the cursor */
+pub enum CsvAstElement {
+    HEADER(String, Range<usize>), //field name of the header (without ":id" or "/id") and its range
+    #[allow(non_camel_case_types)]
+    XML_ID(XmlIdOccurrence), //value of the id column or of a ":id"/"/id" column
+}
+
+pub struct CsvAstUtils {}
+
+impl CsvAstUtils {
+
+    pub fn get_element(content: &str, offset: usize) -> Option<CsvAstElement> {
+        let mut rdr = csv::Reader::from_reader(content.as_bytes());
+        let headers = rdr.headers().ok()?.clone();
+        let header_end = rdr.position().byte() as usize;
+        if offset <= header_end {
+            for (header, range) in headers.iter().zip(CsvArchBuilder::get_value_ranges(content, &headers)) {
+                let start = range.start;
+                let field_name = header.split(|c| c == ':' || c == '/').next().unwrap_or("");
+                if !field_name.is_empty() && start <= offset && offset <= start + field_name.len() {
+                    return Some(CsvAstElement::HEADER(field_name.to_string(), start..start + field_name.len()));
+                }
+            }
+            return None;
+        }
+        XmlIdUsages::collect_csv(content).into_iter()
+            .find(|o| o.range.start <= offset && offset <= o.range.end)
+            .map(|o| CsvAstElement::XML_ID(o))
+    }
+
+    /* Return the fields or the xml data under the cursor, and the range of the element */
+    pub fn get_symbols(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, content: &str, offset: usize, on_dep_only: bool) -> (Vec<XmlAstResult>, Option<Range<usize>>) {
+        let mut results = (vec![], None);
+        let Some(from_module) = file_symbol.borrow().find_module() else {
+            return results;
+        };
+        match CsvAstUtils::get_element(content, offset) {
+            Some(CsvAstElement::HEADER(field_name, range)) => {
+                for field in CsvAstUtils::get_header_fields(session, file_symbol, &field_name, on_dep_only) {
+                    results.0.push(XmlAstResult::SYMBOL(field));
+                }
+                results.1 = Some(range);
+            },
+            Some(CsvAstElement::XML_ID(occurrence)) => {
+                XmlAstUtils::add_xml_id_result(session, &occurrence.xml_id, &from_module, occurrence.range.clone(), &mut results, on_dep_only);
+                results.1 = Some(occurrence.range);
+            },
+            None => {}
+        }
+        results
+    }
+
+    /* Fields named field_name on the model of the csv file */
+    pub fn get_header_fields(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, field_name: &str, on_dep_only: bool) -> Vec<Rc<RefCell<Symbol>>> {
+        let mut fields = vec![];
+        let model_name = file_symbol.borrow().as_csv_file_sym().model_name.clone();
+        let Some(model) = session.sync_odoo.models.get(&model_name).cloned() else {
+            return fields;
+        };
+        let from_module = match on_dep_only {
+            true => file_symbol.borrow().find_module(),
+            false => None,
+        };
+        for symbol in model.borrow().all_symbols(session, from_module, true) {
+            if symbol.1.is_none() {
+                let content = symbol.0.borrow().get_content_symbol(field_name, u32::MAX);
+                fields.extend(content.symbols.iter().cloned());
+            }
+        }
+        fields
+    }
+}
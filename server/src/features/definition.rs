@@ -10,6 +10,7 @@ use crate::core::file_mgr::{FileInfo, FileMgr};
 use crate::core::odoo::SyncOdoo;
 use crate::core::symbols::symbol::Symbol;
 use crate::features::ast_utils::AstUtils;
+use crate::features::csv_ast_utils::CsvAstUtils;
 use crate::features::features_utils::FeaturesUtils;
 use crate::features::xml_ast_utils::{XmlAstResult, XmlAstUtils};
 use crate::oyarn;
@@ -101,7 +102,7 @@ impl DefinitionFeature {
             return  false;
         };
         let mut xml_found = false;
-        let xml_ids = SyncOdoo::get_xml_ids(session, file_symbol, value.as_str(), &std::ops::Range{start: 0, end: 0}, &mut vec![]);
+        let xml_ids = SyncOdoo::get_xml_ids(session, file_symbol, value.as_str(), &std::ops::Range{start: 0, end: 0}, &mut vec![]).unwrap_or_default();
         for xml_id in xml_ids {
             let file = xml_id.get_file_symbol();
             if let Some(file) = file {
@@ -223,19 +224,68 @@ impl DefinitionFeature {
             if symbols.is_empty() {
                 return None;
             }
-            let mut links = vec![];
-            for xml_result in symbols.iter() {
-                match xml_result {
-                    crate::features::xml_ast_utils::XmlAstResult::SYMBOL(s) => {
-                        if let Some(file) = s.borrow().get_file() {
-                            for path in file.upgrade().unwrap().borrow().paths().iter() {
-                                let full_path = match file.upgrade().unwrap().borrow().typ() {
-                                    SymType::PACKAGE(_) => PathBuf::from(path).join(format!("__init__.py{}", file.upgrade().unwrap().borrow().as_package().i_ext())).sanitize(),
+            return Some(GotoDefinitionResponse::Link(DefinitionFeature::data_results_to_links(session, file_symbol, &symbols, &link_range)));
+        }
+        None
+    }
+
+    pub fn get_location_csv(session: &mut SessionInfo,
+        file_symbol: &Rc<RefCell<Symbol>>,
+        file_info: &Rc<RefCell<FileInfo>>,
+        line: u32,
+        character: u32
+    ) -> Option<GotoDefinitionResponse> {
+        let offset = file_info.borrow().position_to_offset(line, character);
+        let data = file_info.borrow().file_info_ast.borrow().text_rope.as_ref()?.to_string();
+        let (symbols, link_range) = CsvAstUtils::get_symbols(session, file_symbol, &data, offset, true);
+        if symbols.is_empty() {
+            return None;
+        }
+        Some(GotoDefinitionResponse::Link(DefinitionFeature::data_results_to_links(session, file_symbol, &symbols, &link_range)))
+    }
+
+    /* Build the links to the symbols and xml data found in a data file (xml or csv) */
+    fn data_results_to_links(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, symbols: &Vec<XmlAstResult>, link_range: &Option<std::ops::Range<usize>>) -> Vec<LocationLink> {
+        let mut links = vec![];
+        for xml_result in symbols.iter() {
+            match xml_result {
+                XmlAstResult::SYMBOL(s) => {
+                    if let Some(file) = s.borrow().get_file() {
+                        for path in file.upgrade().unwrap().borrow().paths().iter() {
+                            let full_path = match file.upgrade().unwrap().borrow().typ() {
+                                SymType::PACKAGE(_) => PathBuf::from(path).join(format!("__init__.py{}", file.upgrade().unwrap().borrow().as_package().i_ext())).sanitize(),
+                                _ => path.clone()
+                            };
+                            let range = match s.borrow().typ() {
+                                SymType::PACKAGE(_) | SymType::FILE | SymType::NAMESPACE | SymType::DISK_DIR => Range::default(),
+                                _ => session.sync_odoo.get_file_mgr().borrow().text_range_to_range(session, &full_path, &s.borrow().range()),
+                            };
+                            let link_range = if link_range.is_some() {
+                                Some(session.sync_odoo.get_file_mgr().borrow().std_range_to_range(session, file_symbol.borrow().paths().first().as_ref().unwrap(), link_range.as_ref().unwrap()))
+                            } else {
+                                None
+                            };
+                            links.push(LocationLink{
+                                origin_selection_range: link_range,
+                                target_uri: FileMgr::pathname2uri(&full_path),
+                                target_range: range,
+                                target_selection_range: range
+                            });
+                        }
+                    }
+                },
+                XmlAstResult::XML_DATA(xml_file_symbol, range) => {
+                    let file = xml_file_symbol.borrow().get_file(); //in case of XML_DATA coming from a python class
+                    if let Some(file) = file {
+                        if let Some(file) = file.upgrade() {
+                            for path in file.borrow().paths().iter() {
+                                let full_path = match file.borrow().typ() {
+                                    SymType::PACKAGE(_) => PathBuf::from(path).join(format!("__init__.py{}", file.borrow().as_package().i_ext())).sanitize(),
                                     _ => path.clone()
                                 };
-                                let range = match s.borrow().typ() {
+                                let range = match file.borrow().typ() {
                                     SymType::PACKAGE(_) | SymType::FILE | SymType::NAMESPACE | SymType::DISK_DIR => Range::default(),
-                                    _ => session.sync_odoo.get_file_mgr().borrow().text_range_to_range(session, &full_path, &s.borrow().range()),
+                                    _ => session.sync_odoo.get_file_mgr().borrow().std_range_to_range(session, &full_path, &range),
                                 };
                                 let link_range = if link_range.is_some() {
                                     Some(session.sync_odoo.get_file_mgr().borrow().std_range_to_range(session, file_symbol.borrow().paths().first().as_ref().unwrap(), link_range.as_ref().unwrap()))
@@ -250,49 +300,11 @@ impl DefinitionFeature {
                                 });
                             }
                         }
-                    },
-                    XmlAstResult::XML_DATA(xml_file_symbol, range) => {
-                        let file = xml_file_symbol.borrow().get_file(); //in case of XML_DATA coming from a python class
-                        if let Some(file) = file {
-                            if let Some(file) = file.upgrade() {
-                                for path in file.borrow().paths().iter() {
-                                    let full_path = match file.borrow().typ() {
-                                        SymType::PACKAGE(_) => PathBuf::from(path).join(format!("__init__.py{}", file.borrow().as_package().i_ext())).sanitize(),
-                                        _ => path.clone()
-                                    };
-                                    let range = match file.borrow().typ() {
-                                        SymType::PACKAGE(_) | SymType::FILE | SymType::NAMESPACE | SymType::DISK_DIR => Range::default(),
-                                        _ => session.sync_odoo.get_file_mgr().borrow().std_range_to_range(session, &full_path, &range),
-                                    };
-                                    let link_range = if link_range.is_some() {
-                                        Some(session.sync_odoo.get_file_mgr().borrow().std_range_to_range(session, file_symbol.borrow().paths().first().as_ref().unwrap(), link_range.as_ref().unwrap()))
-                                    } else {
-                                        None
-                                    };
-                                    links.push(LocationLink{
-                                        origin_selection_range: link_range,
-                                        target_uri: FileMgr::pathname2uri(&full_path),
-                                        target_range: range,
-                                        target_selection_range: range
-                                    });
-                                }
-                            }
-                        }
                     }
                 }
             }
-            return Some(GotoDefinitionResponse::Link(links));
         }
-        None
-    }
-
-    pub fn get_location_csv(_session: &mut SessionInfo,
-        _file_symbol: &Rc<RefCell<Symbol>>,
-        _file_info: &Rc<RefCell<FileInfo>>,
-        _line: u32,
-        _character: u32
-    ) -> Option<GotoDefinitionResponse> {
-        None
+        links
     }
 
 }
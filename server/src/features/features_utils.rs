@@ -1,4 +1,5 @@
 use itertools::Itertools;
+use lsp_types::Location;
 use ruff_python_ast::{Expr, ExprCall, Keyword};
 use ruff_text_size::{Ranged, TextRange, TextSize};
 use crate::core::file_mgr::FileMgr;
@@ -15,6 +16,7 @@ use crate::constants::SymType;
 use crate::constants::OYarn;
 use crate::core::evaluation::{Context, ContextValue, Evaluation, EvaluationSymbolPtr, EvaluationSymbolWeak, EvaluationValue};
 use crate::core::symbols::symbol::Symbol;
+use crate::features::references::ReferenceFeature;
 use crate::threads::SessionInfo;
 use crate::{oyarn, Sy, S};
 
@@ -676,6 +678,14 @@ impl FeaturesUtils {
         documentation_block
     }
 
+    /* Location of the declaration of a python symbol */
+    pub fn get_symbol_location(session: &mut SessionInfo, symbol: &Rc<RefCell<Symbol>>) -> Option<Location> {
+        let file = symbol.borrow().get_file()?.upgrade()?;
+        let path = ReferenceFeature::get_python_file_path(&file)?;
+        let range = session.sync_odoo.get_file_mgr().borrow().text_range_to_range(session, &path, symbol.borrow().range());
+        Some(Location { uri: FileMgr::pathname2uri(&path), range })
+    }
+
     pub fn get_line_break(session: &mut SessionInfo<'_>) -> &'static str {
         if session.sync_odoo.capabilities.general.is_none() ||
         session.sync_odoo.capabilities.general.as_ref().unwrap().markdown.is_none() {
@@ -1,6 +1,7 @@
 use lsp_types::{Hover, HoverContents, MarkupContent};
 use crate::core::evaluation::Evaluation;
 use crate::core::file_mgr::FileInfo;
+use crate::features::csv_ast_utils::CsvAstUtils;
 use crate::features::xml_ast_utils::{XmlAstResult, XmlAstUtils};
 use crate::threads::SessionInfo;
 use std::rc::Rc;
@@ -18,7 +19,7 @@ impl HoverFeature {
         let offset = file_info.borrow().position_to_offset(line, character);
         let (analyse_ast_result, range, call_expr) = AstUtils::get_symbols(session, file_symbol, file_info, offset as u32);
         let evals = analyse_ast_result.evaluations;
-        if evals.is_empty() {
+        if evals.is_empty() || session.is_request_cancelled() {
             return None;
         };
         let range = Some(file_info.borrow().text_range_to_range(&range.unwrap()));
@@ -41,6 +42,9 @@ impl HoverFeature {
             let range = range.map(|r| (file_info.borrow().std_range_to_range(&r)));
             let evals = symbols.iter().filter(|s| matches!(s, XmlAstResult::SYMBOL(_)))
                 .map(|s| Evaluation::eval_from_symbol(&Rc::downgrade(&s.as_symbol()), Some(false))).collect::<Vec<Evaluation>>();
+            if session.is_request_cancelled() {
+                return None;
+            }
             return Some(Hover { contents:
                 HoverContents::Markup(MarkupContent {
                     kind: lsp_types::MarkupKind::Markdown,
@@ -52,7 +56,22 @@ impl HoverFeature {
         None
     }
 
-    pub fn hover_csv(_session: &mut SessionInfo, _file_symbol: &Rc<RefCell<Symbol>>, _file_info: &Rc<RefCell<FileInfo>>, _line: u32, _character: u32) -> Option<Hover> {
-        None
+    pub fn hover_csv(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, line: u32, character: u32) -> Option<Hover> {
+        let offset = file_info.borrow().position_to_offset(line, character);
+        let data = file_info.borrow().file_info_ast.borrow().text_rope.as_ref()?.to_string();
+        let (symbols, range) = CsvAstUtils::get_symbols(session, file_symbol, &data, offset, true);
+        let evals = symbols.iter().filter(|s| matches!(s, XmlAstResult::SYMBOL(_)))
+            .map(|s| Evaluation::eval_from_symbol(&Rc::downgrade(&s.as_symbol()), Some(false))).collect::<Vec<Evaluation>>();
+        if evals.is_empty() {
+            return None;
+        }
+        let range = range.map(|r| (file_info.borrow().std_range_to_range(&r)));
+        Some(Hover { contents:
+            HoverContents::Markup(MarkupContent {
+                kind: lsp_types::MarkupKind::Markdown,
+                value: FeaturesUtils::build_markdown_description(session, Some(file_symbol.clone()), &evals, &None, Some(offset))
+            }),
+            range: range
+        })
     }
 }
\ No newline at end of file
@@ -0,0 +1,130 @@
+use std::{cell::RefCell, rc::Rc};
+
+use lsp_types::{request::GotoImplementationResponse, Location};
+
+use crate::{constants::{OYarn, SymType}, core::{file_mgr::FileInfo, symbols::{module_symbol::ModuleSymbol, package_symbol::PackageSymbol, symbol::Symbol}}, features::{features_utils::FeaturesUtils, references::ReferenceFeature}, threads::SessionInfo};
+
+
+pub struct ImplementationFeature {}
+
+impl ImplementationFeature {
+
+    pub fn get_implementations(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, line: u32, character: u32) -> Option<GotoImplementationResponse> {
+        let offset = file_info.borrow().position_to_offset(line, character);
+        let targets = ReferenceFeature::get_target_symbols(session, file_symbol, file_info, offset);
+        let mut locations: Vec<Location> = vec![];
+        for target in targets.iter() {
+            for method in ImplementationFeature::get_method_overrides(session, target).iter() {
+                let Some(location) = FeaturesUtils::get_symbol_location(session, method) else {
+                    continue;
+                };
+                if !locations.contains(&location) {
+                    locations.push(location);
+                }
+            }
+        }
+        if locations.is_empty() {
+            return None;
+        }
+        Some(GotoImplementationResponse::Array(locations))
+    }
+
+    /* Return all the methods with the same name than the given model method, in all the classes of its model.
+    Methods are ordered by module dependency: a method comes after the methods of the modules its module depends on */
+    pub fn get_method_overrides(session: &mut SessionInfo, method: &Rc<RefCell<Symbol>>) -> Vec<Rc<RefCell<Symbol>>> {
+        if method.borrow().typ() != SymType::FUNCTION {
+            return vec![];
+        }
+        let Some((model_name, _)) = ReferenceFeature::get_model_member_info(method) else {
+            return vec![];
+        };
+        let name = method.borrow().name().clone();
+        ImplementationFeature::get_model_methods(session, &model_name, &name)
+    }
+
+    /* Return the methods with the given name in all the classes of the model, ordered by module dependency */
+    pub fn get_model_methods(session: &mut SessionInfo, model_name: &OYarn, name: &OYarn) -> Vec<Rc<RefCell<Symbol>>> {
+        let Some(model) = session.sync_odoo.models.get(model_name).cloned() else {
+            return vec![];
+        };
+        let classes = model.borrow().get_symbols(session, None);
+        let mut overrides = vec![];
+        for class in classes.iter() {
+            let members = class.borrow().get_content_symbol(name.as_str(), u32::MAX).symbols;
+            for member in members {
+                if member.borrow().typ() == SymType::FUNCTION {
+                    overrides.push(member);
+                }
+            }
+        }
+        ImplementationFeature::sort_by_dependencies(session, overrides)
+    }
+
+    /* Order the symbols like Odoo loads them: a module comes after all the modules it depends on, and in a module,
+    symbols follow the import order of the python files */
+    pub(crate) fn sort_by_dependencies(session: &mut SessionInfo, symbols: Vec<Rc<RefCell<Symbol>>>) -> Vec<Rc<RefCell<Symbol>>> {
+        let mut groups: Vec<(Option<Rc<RefCell<Symbol>>>, Vec<(Vec<u32>, Rc<RefCell<Symbol>>)>)> = vec![];
+        for symbol in symbols {
+            let module = symbol.borrow().find_module();
+            let key = ImplementationFeature::get_import_order(&symbol);
+            let group = groups.iter_mut().find(|(group_module, _)| match (group_module, &module) {
+                (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+                (None, None) => true,
+                _ => false
+            });
+            match group {
+                Some((_, members)) => members.push((key, symbol)),
+                None => groups.push((module, vec![(key, symbol)])),
+            }
+        }
+        for (_, members) in groups.iter_mut() {
+            members.sort_by(|(a, _), (b, _)| a.cmp(b));
+        }
+        groups.sort_by_key(|(module, _)| module.as_ref().map(|m| m.borrow().as_module_package().dir_name.clone()));
+        let mut result = vec![];
+        while !groups.is_empty() {
+            /* take the first module that doesn't depend on any remaining one. If there is a cycle, take the first one */
+            let index = (0..groups.len()).find(|&i| {
+                let Some(module) = groups[i].0.clone() else {
+                    return true;
+                };
+                !groups.iter().enumerate().any(|(j, (other, _))| {
+                    j != i && other.as_ref().is_some_and(|other| {
+                        let dir_name = other.borrow().as_module_package().dir_name.clone();
+                        ModuleSymbol::is_in_deps(session, &module, &dir_name)
+                    })
+                })
+            }).unwrap_or(0);
+            let (_, members) = groups.remove(index);
+            result.extend(members.into_iter().map(|(_, symbol)| symbol));
+        }
+        result
+    }
+
+    /* Position of the symbol in the import order of its module: for each package from the module to the file of
+    the symbol, the offset where the next package or file is imported, followed by the offset of the symbol.
+    Files that are not imported by their parent package come last */
+    pub(crate) fn get_import_order(symbol: &Rc<RefCell<Symbol>>) -> Vec<u32> {
+        let symbol_bw = symbol.borrow();
+        let mut key = vec![if symbol_bw.has_range() { symbol_bw.range().start().to_u32() } else { 0 }];
+        let mut current = symbol_bw.get_file().and_then(|f| f.upgrade());
+        drop(symbol_bw);
+        while let Some(file) = current {
+            if matches!(*file.borrow(), Symbol::Package(PackageSymbol::Module(_))) {
+                break;
+            }
+            let name = file.borrow().name().clone();
+            let parent = file.borrow().parent().and_then(|p| p.upgrade());
+            if let Some(parent) = parent.as_ref() {
+                let position = parent.borrow().get_content_symbol(name.as_str(), u32::MAX).symbols.iter()
+                    .filter(|s| s.borrow().has_range())
+                    .map(|s| s.borrow().range().start().to_u32())
+                    .min()
+                    .unwrap_or(u32::MAX);
+                key.insert(0, position);
+            }
+            current = parent;
+        }
+        key
+    }
+}
@@ -0,0 +1,132 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use lsp_types::{InlayHint, InlayHintKind, InlayHintLabel, Range};
+use ruff_python_ast::{visitor::{walk_stmt, Visitor}, Expr, Stmt};
+use ruff_text_size::{Ranged, TextRange, TextSize};
+
+use crate::{constants::SymType, core::{evaluation::{Context, ContextValue, Evaluation, ExprOrIdent}, file_mgr::FileInfo, symbols::symbol::Symbol}, threads::SessionInfo, S};
+
+pub struct InlayHintFeature {}
+
+impl InlayHintFeature {
+
+    /* Show the model of the variables assigned in the given range, like "partner: res.partner".
+    Each category of hints can be disabled in the configuration */
+    pub fn get_inlay_hints(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, range: &Range) -> Option<Vec<InlayHint>> {
+        let variables = session.sync_odoo.config.inlay_hints_variables;
+        let loop_variables = session.sync_odoo.config.inlay_hints_loop_variables;
+        if !variables && !loop_variables {
+            return None;
+        }
+        let start = file_info.borrow().position_to_offset(range.start.line, range.start.character);
+        let end = file_info.borrow().position_to_offset(range.end.line, range.end.character);
+        let from_module = match file_symbol.borrow().find_module() {
+            Some(module) => ContextValue::MODULE(Rc::downgrade(&module)),
+            None => ContextValue::BOOLEAN(false),
+        };
+        let file_info_ast = file_info.borrow().file_info_ast.clone();
+        let file_info_ast = file_info_ast.borrow();
+        let stmts = file_info_ast.get_stmts()?;
+        let mut visitor = HintTargetVisitor {
+            range: TextRange::new(TextSize::new(start as u32), TextSize::new(end as u32)),
+            variables,
+            loop_variables,
+            targets: vec![],
+        };
+        for stmt in stmts.iter() {
+            visitor.visit_stmt(stmt);
+        }
+        let mut hints = vec![];
+        for target in visitor.targets.iter() {
+            let model_names = InlayHintFeature::get_model_names(session, file_symbol, target, &from_module);
+            if model_names.is_empty() {
+                continue;
+            }
+            hints.push(InlayHint {
+                position: file_info.borrow().offset_to_position(target.range().end().to_usize()),
+                label: InlayHintLabel::String(format!(": {}", model_names.join(" | "))),
+                kind: Some(InlayHintKind::TYPE),
+                text_edits: None,
+                tooltip: None,
+                padding_left: None,
+                padding_right: None,
+                data: None,
+            });
+        }
+        Some(hints)
+    }
+
+    /* Evaluate the variable like the hover does, and return the names of the models it can be a recordset of */
+    fn get_model_names(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, expr: &Expr, from_module: &ContextValue) -> Vec<String> {
+        let parent_symbol = Symbol::get_scope_symbol(file_symbol.clone(), expr.range().start().to_u32(), false);
+        let mut context: Option<Context> = Some(HashMap::from([
+            (S!("module"), from_module.clone()),
+            (S!("range"), ContextValue::RANGE(expr.range()))
+        ]));
+        let analyse_ast_result = Evaluation::analyze_ast(session, &ExprOrIdent::Expr(expr), parent_symbol, &expr.range().end(), &mut context, false, &mut vec![]);
+        let mut model_names = vec![];
+        for eval in analyse_ast_result.evaluations.iter() {
+            if eval.value.is_some() {
+                continue;
+            }
+            let eval_symbol = eval.symbol.get_symbol(session, &mut None, &mut vec![], None);
+            if eval_symbol.upgrade_weak().is_none() {
+                continue;
+            }
+            let mut context = Some(eval_symbol.as_weak().context.clone());
+            for eval_ptr in Symbol::follow_ref(&eval_symbol, session, &mut context, false, false, None).iter() {
+                let Some(symbol) = eval_ptr.upgrade_weak() else {
+                    continue;
+                };
+                let symbol = symbol.borrow();
+                if symbol.typ() != SymType::CLASS {
+                    continue;
+                }
+                if let Some(model_data) = symbol.as_class_sym()._model.as_ref() {
+                    let model_name = model_data.name.to_string();
+                    if !model_names.contains(&model_name) {
+                        model_names.push(model_name);
+                    }
+                }
+            }
+        }
+        model_names
+    }
+}
+
+/* Collect the names that are assigned in the range: targets of assignments and of for loops */
+struct HintTargetVisitor<'a> {
+    range: TextRange,
+    variables: bool,
+    loop_variables: bool,
+    targets: Vec<&'a Expr>,
+}
+
+impl<'a> HintTargetVisitor<'a> {
+    fn add_target(&mut self, target: &'a Expr) {
+        if matches!(target, Expr::Name(_)) && self.range.contains_range(target.range()) {
+            self.targets.push(target);
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for HintTargetVisitor<'a> {
+
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        if stmt.range().intersect(self.range).is_none() {
+            return;
+        }
+        match stmt {
+            Stmt::Assign(assign) if self.variables => {
+                for target in assign.targets.iter() {
+                    self.add_target(target);
+                }
+            },
+            Stmt::For(for_stmt) if self.loop_variables => {
+                self.add_target(&for_stmt.target);
+            },
+            _ => {}
+        }
+        walk_stmt(self, stmt);
+    }
+}
@@ -1,9 +1,18 @@
 pub mod ast_utils;
+pub mod call_hierarchy;
+pub mod code_actions;
 pub mod completion;
+pub mod csv_ast_utils;
 pub mod definition;
 pub mod document_symbols;
 pub mod features_utils;
 pub mod hover;
+pub mod implementation;
+pub mod inlay_hints;
 pub mod node_index_ast;
 pub mod references;
+pub mod rename;
+pub mod semantic_tokens;
+pub mod type_hierarchy;
+pub mod workspace_symbols;
 pub mod xml_ast_utils;
\ No newline at end of file
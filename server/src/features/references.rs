@@ -1,8 +1,11 @@
-use std::{cell::RefCell, path::PathBuf, rc::Rc};
+use std::{cell::RefCell, collections::{HashMap, HashSet}, ops::Range as StdRange, path::PathBuf, rc::{Rc, Weak}};
 
 use lsp_types::{Location, Range};
+use ruff_python_ast::{visitor::{walk_expr, walk_parameter, walk_stmt, Visitor}, Expr, ExprCall, ExprStringLiteral, Identifier, Parameter, Stmt};
+use ruff_text_size::{Ranged, TextRange, TextSize};
+use weak_table::PtrWeakHashSet;
 
-use crate::{constants::SymType, core::{file_mgr::{FileInfo, FileMgr}, symbols::symbol::Symbol}, features::xml_ast_utils::{XmlAstResult, XmlAstUtils}, threads::SessionInfo, utils::PathSanitizer};
+use crate::{constants::{OYarn, SymType}, core::{evaluation::{Context, ContextValue, Evaluation, EvaluationValue, ExprOrIdent}, file_mgr::{FileInfo, FileMgr}, symbols::symbol::Symbol, xml_data::OdooData, xml_id_usages::XmlIdUsages}, features::{ast_utils::AstUtils, csv_ast_utils::{CsvAstElement, CsvAstUtils}, features_utils::FeaturesUtils, rename::RenameFeature, xml_ast_utils::{XmlAstResult, XmlAstUtils}}, threads::SessionInfo, utils::PathSanitizer, S};
 
 
 
@@ -11,9 +14,423 @@ pub struct ReferenceFeature {
 }
 
 impl ReferenceFeature {
-    pub fn get_references(_session: &mut SessionInfo, _file_symbol: &Rc<RefCell<Symbol>>, _file_info: &Rc<RefCell<FileInfo>>, _line: u32, _character: u32) -> Option<Vec<Location>> {
-        // Implementation for getting references
-        None
+    pub fn get_references(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, line: u32, character: u32) -> Option<Vec<Location>> {
+        let offset = file_info.borrow().position_to_offset(line, character);
+        let targets = ReferenceFeature::get_target_symbols(session, file_symbol, file_info, offset);
+        if targets.is_empty() {
+            return None;
+        }
+        Some(ReferenceFeature::find_symbol_references(session, file_symbol, targets))
+    }
+
+    /* Return all the locations where one of the targets, or an override of it, is used or declared, in python
+    files and in data files */
+    pub(crate) fn find_symbol_references(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, targets: Vec<Rc<RefCell<Symbol>>>) -> Vec<Location> {
+        let targets = ReferenceFeature::add_model_overrides(session, targets);
+        let mut locations = vec![];
+        ReferenceFeature::find_python_references(session, file_symbol, &targets, &mut locations);
+        ReferenceFeature::find_data_references(session, &targets, &mut locations);
+        locations
+    }
+
+    /* Return the symbols that are referenced at the given offset. Import variables are followed to
+    the symbol they import, so that references are searched on the declaration. */
+    pub(crate) fn get_target_symbols(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, offset: usize) -> Vec<Rc<RefCell<Symbol>>> {
+        let mut targets: Vec<Rc<RefCell<Symbol>>> = vec![];
+        let (analyse_ast_result, _range, call_expr) = AstUtils::get_symbols(session, file_symbol, file_info, offset as u32);
+        for eval in analyse_ast_result.evaluations.iter() {
+            if let (Some(EvaluationValue::CONSTANT(Expr::StringLiteral(expr))), Some(call_expr)) = (eval.value.as_ref(), call_expr.as_ref()) {
+                //fields in strings, like in api.depends or related arguments
+                let scope = Symbol::get_scope_symbol(file_symbol.clone(), offset as u32, false);
+                let from_module = file_symbol.borrow().find_module();
+                for (symbol, _) in FeaturesUtils::find_argument_symbols(session, scope, from_module, &expr.value.to_string(), call_expr, offset, expr.range) {
+                    if !targets.iter().any(|t| Rc::ptr_eq(t, &symbol)) {
+                        targets.push(symbol);
+                    }
+                }
+                continue;
+            }
+            if eval.value.is_some() {
+                //constants like strings or numbers: we don't want references of the builtin classes
+                continue;
+            }
+            let Some(symbol) = eval.symbol.get_symbol_as_weak(session, &mut None, &mut vec![], None).weak.upgrade() else {
+                continue;
+            };
+            let symbol = ReferenceFeature::import_chain(session, symbol).pop().unwrap();
+            if !targets.iter().any(|t| Rc::ptr_eq(t, &symbol)) {
+                targets.push(symbol);
+            }
+        }
+        if targets.is_empty() {
+            //the cursor can be on the name of a class or a function, that is not an expression
+            let file_info_ast = file_info.borrow().file_info_ast.clone();
+            let file_info_ast = file_info_ast.borrow();
+            let Some(stmts) = file_info_ast.get_stmts() else {
+                return targets;
+            };
+            for stmt in stmts.iter() {
+                if let Some(name) = DeclarationFinderVisitor::find_declaration_at(stmt, offset as u32) {
+                    if let Some(symbol) = ReferenceFeature::get_declared_symbol(file_symbol, name) {
+                        targets.push(symbol);
+                    }
+                    break;
+                }
+            }
+        }
+        targets
+    }
+
+    /* Return the class or function symbol declared with the given name identifier */
+    fn get_declared_symbol(file_symbol: &Rc<RefCell<Symbol>>, name: &Identifier) -> Option<Rc<RefCell<Symbol>>> {
+        let symbol = Symbol::get_scope_symbol(file_symbol.clone(), name.range().start().to_u32(), true);
+        if !matches!(symbol.borrow().typ(), SymType::CLASS | SymType::FUNCTION) || symbol.borrow().name().as_str() != name.id.as_str() {
+            return None;
+        }
+        Some(symbol)
+    }
+
+    /* Return the symbol, followed by all the symbols reached by following it if it is an import variable */
+    pub(crate) fn import_chain(session: &mut SessionInfo, symbol: Rc<RefCell<Symbol>>) -> Vec<Rc<RefCell<Symbol>>> {
+        let mut chain = vec![symbol.clone()];
+        let mut current = symbol;
+        loop {
+            let is_import = current.borrow().typ() == SymType::VARIABLE && current.borrow().as_variable().is_import_variable;
+            if !is_import {
+                break;
+            }
+            let next_refs = Symbol::next_refs(session, current.clone(), &mut None, &HashMap::new(), true, &mut vec![]);
+            let Some(next) = next_refs.iter().find_map(|next_ref| next_ref.upgrade_weak()) else {
+                break;
+            };
+            if chain.iter().any(|s| Rc::ptr_eq(s, &next)) {
+                break;
+            }
+            chain.push(next.clone());
+            current = next;
+        }
+        chain
+    }
+
+    /* If a target is a field or a method of a model, add the members with the same name in all the classes
+    of the model, and in the classes of the models it inherits from (_inherit). */
+    fn add_model_overrides(session: &mut SessionInfo, targets: Vec<Rc<RefCell<Symbol>>>) -> Vec<Rc<RefCell<Symbol>>> {
+        let mut result = targets.clone();
+        for target in targets.iter() {
+            let Some((model_name, _)) = ReferenceFeature::get_model_member_info(target) else {
+                continue;
+            };
+            let name = target.borrow().name().clone();
+            let mut classes: Vec<Rc<RefCell<Symbol>>> = vec![];
+            let mut model_names = vec![model_name];
+            let mut index = 0;
+            while index < model_names.len() {
+                let model = session.sync_odoo.models.get(&model_names[index]).cloned();
+                index += 1;
+                let Some(model) = model else {
+                    continue;
+                };
+                for (class, _) in model.borrow().all_symbols(session, None, false) {
+                    if let Some(class_model) = class.borrow().as_class_sym()._model.as_ref() {
+                        for inherit in class_model.inherit.iter() {
+                            if !model_names.contains(inherit) {
+                                model_names.push(inherit.clone());
+                            }
+                        }
+                    }
+                    if !classes.iter().any(|c| Rc::ptr_eq(c, &class)) {
+                        classes.push(class);
+                    }
+                }
+            }
+            for class in classes.iter() {
+                let members = class.borrow().get_content_symbol(name.as_str(), u32::MAX).symbols;
+                for member in members {
+                    if !result.iter().any(|r| Rc::ptr_eq(r, &member)) {
+                        result.push(member);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /* If the symbol is a member of a model class, return the model name and the class symbol */
+    pub(crate) fn get_model_member_info(symbol: &Rc<RefCell<Symbol>>) -> Option<(OYarn, Rc<RefCell<Symbol>>)> {
+        if !matches!(symbol.borrow().typ(), SymType::FUNCTION | SymType::VARIABLE) {
+            return None;
+        }
+        let parent = symbol.borrow().parent()?.upgrade()?;
+        if parent.borrow().typ() != SymType::CLASS {
+            return None;
+        }
+        let model_name = parent.borrow().as_class_sym()._model.as_ref()?.name.clone();
+        Some((model_name, parent))
+    }
+
+    fn is_target(symbol: &Rc<RefCell<Symbol>>, targets: &Vec<Rc<RefCell<Symbol>>>) -> bool {
+        targets.iter().any(|target| {
+            if Rc::ptr_eq(target, symbol) {
+                return true;
+            }
+            //variables assigned multiple times in the same scope are different symbols
+            let target = target.borrow();
+            let symbol = symbol.borrow();
+            if target.typ() != SymType::VARIABLE || symbol.typ() != SymType::VARIABLE || target.name() != symbol.name() {
+                return false;
+            }
+            match (target.parent().and_then(|p| p.upgrade()), symbol.parent().and_then(|p| p.upgrade())) {
+                (Some(target_parent), Some(symbol_parent)) => Rc::ptr_eq(&target_parent, &symbol_parent),
+                _ => false
+            }
+        })
+    }
+
+    pub(crate) fn get_python_file_path(file_symbol: &Rc<RefCell<Symbol>>) -> Option<String> {
+        let file = file_symbol.borrow();
+        let path = file.paths().first()?.clone();
+        Some(match file.typ() {
+            SymType::PACKAGE(_) => PathBuf::from(path).join(format!("__init__.py{}", file.as_package().i_ext())).sanitize(),
+            _ => path
+        })
+    }
+
+    /* Collect the python files that can contain a reference to one of the targets: the files of the targets,
+    their dependents, and the dependents of the models of the targets */
+    pub(crate) fn get_files_to_scan(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, targets: &Vec<Rc<RefCell<Symbol>>>) -> PtrWeakHashSet<Weak<RefCell<Symbol>>> {
+        let mut files = PtrWeakHashSet::new();
+        files.insert(file_symbol.clone());
+        for target in targets.iter() {
+            let Some(file) = target.borrow().get_file().and_then(|f| f.upgrade()) else {
+                continue;
+            };
+            if !matches!(file.borrow().typ(), SymType::FILE | SymType::PACKAGE(_)) {
+                continue;
+            }
+            files.insert(file.clone());
+            for level in file.borrow().dependents().iter() {
+                for dependents in level.iter().flatten() {
+                    files.extend(dependents.iter());
+                }
+            }
+            if let Some((model_name, _)) = ReferenceFeature::get_model_member_info(target) {
+                if let Some(model) = session.sync_odoo.models.get(&model_name).cloned() {
+                    for dependent in model.borrow().dependents.iter() {
+                        if let Some(dep_file) = dependent.borrow().get_file().and_then(|f| f.upgrade()) {
+                            files.insert(dep_file);
+                        }
+                    }
+                }
+            }
+        }
+        files
+    }
+
+    fn find_python_references(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, targets: &Vec<Rc<RefCell<Symbol>>>, locations: &mut Vec<Location>) {
+        let names: HashSet<String> = targets.iter().map(|t| t.borrow().name().to_string()).collect();
+        let files = ReferenceFeature::get_files_to_scan(session, file_symbol, targets);
+        let mut scanned_files = PtrWeakHashSet::new();
+        for file in files.iter() {
+            if !matches!(file.borrow().typ(), SymType::FILE | SymType::PACKAGE(_)) {
+                continue;
+            }
+            if ReferenceFeature::find_references_in_file(session, &file, &names, targets, locations) {
+                scanned_files.insert(file.clone());
+            }
+        }
+        //declarations in files that were not scanned (not in cache)
+        for target in targets.iter() {
+            let Some(file) = target.borrow().get_file().and_then(|f| f.upgrade()) else {
+                continue;
+            };
+            if scanned_files.contains(&file) || !target.borrow().has_range() {
+                continue;
+            }
+            let Some(path) = ReferenceFeature::get_python_file_path(&file) else {
+                continue;
+            };
+            let range = session.sync_odoo.get_file_mgr().borrow().text_range_to_range(session, &path, target.borrow().range());
+            ReferenceFeature::push_location(locations, Location { uri: FileMgr::pathname2uri(&path), range });
+        }
+    }
+
+    /* Search references to the targets in a python file. Return false if the file could not be scanned */
+    fn find_references_in_file(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, names: &HashSet<String>, targets: &Vec<Rc<RefCell<Symbol>>>, locations: &mut Vec<Location>) -> bool {
+        let Some(path) = ReferenceFeature::get_python_file_path(file_symbol) else {
+            return false;
+        };
+        let Some(file_info) = session.sync_odoo.get_file_mgr().borrow().get_file_info(&path) else {
+            return false;
+        };
+        let from_module = match file_symbol.borrow().find_module() {
+            Some(module) => ContextValue::MODULE(Rc::downgrade(&module)),
+            None => ContextValue::BOOLEAN(false),
+        };
+        FileInfo::with_ast(&file_info, session, |session| {
+            let file_info_ast = file_info.borrow().file_info_ast.clone();
+            let file_info_ast = file_info_ast.borrow();
+            if let Some(stmts) = file_info_ast.get_stmts() {
+                let mut visitor = NameOccurrenceVisitor { names, occurrences: vec![], calls: vec![] };
+                for stmt in stmts.iter() {
+                    visitor.visit_stmt(stmt);
+                }
+                for occurrence in visitor.occurrences.iter() {
+                    let found_ranges = match occurrence {
+                        NameOccurrence::Declaration(name) => {
+                            ReferenceFeature::get_declared_symbol(file_symbol, name)
+                                .filter(|symbol| ReferenceFeature::is_target(symbol, targets))
+                                .map(|_| vec![name.range()]).unwrap_or_default()
+                        },
+                        NameOccurrence::Expr(expr, name_range) => {
+                            let expr = ExprOrIdent::Expr(*expr);
+                            match ReferenceFeature::evaluates_to_target(session, file_symbol, &expr, &from_module, targets) {
+                                true => vec![*name_range],
+                                false => vec![]
+                            }
+                        },
+                        NameOccurrence::Parameter(parameter) => {
+                            let expr = ExprOrIdent::Parameter(*parameter);
+                            match ReferenceFeature::evaluates_to_target(session, file_symbol, &expr, &from_module, targets) {
+                                true => vec![parameter.name.range()],
+                                false => vec![]
+                            }
+                        },
+                        NameOccurrence::String(string, call_expr) => {
+                            ReferenceFeature::find_targets_in_string(session, file_symbol, string, call_expr, names, targets)
+                        }
+                    };
+                    for name_range in found_ranges.iter() {
+                        let range = file_info.borrow().text_range_to_range(name_range);
+                        ReferenceFeature::push_location(locations, Location { uri: FileMgr::pathname2uri(&path), range });
+                    }
+                }
+                true
+            } else {
+                false
+            }
+        })
+    }
+
+    pub(crate) fn evaluates_to_target(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, expr: &ExprOrIdent, from_module: &ContextValue, targets: &Vec<Rc<RefCell<Symbol>>>) -> bool {
+        for symbol in ReferenceFeature::get_evaluated_symbols(session, file_symbol, expr, from_module).into_iter() {
+            for symbol in ReferenceFeature::import_chain(session, symbol).iter() {
+                if ReferenceFeature::is_target(symbol, targets) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /* Evaluate an expression of the file, and return the symbols it evaluates to */
+    pub(crate) fn get_evaluated_symbols(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, expr: &ExprOrIdent, from_module: &ContextValue) -> Vec<Rc<RefCell<Symbol>>> {
+        let is_param = matches!(expr, ExprOrIdent::Parameter(_));
+        let parent_symbol = Symbol::get_scope_symbol(file_symbol.clone(), expr.range().start().to_u32(), is_param);
+        let mut context: Option<Context> = Some(HashMap::from([
+            (S!("module"), from_module.clone()),
+            (S!("range"), ContextValue::RANGE(expr.range()))
+        ]));
+        let analyse_ast_result = Evaluation::analyze_ast(session, expr, parent_symbol, &expr.range().end(), &mut context, false, &mut vec![]);
+        analyse_ast_result.evaluations.iter().filter_map(|eval|
+            eval.symbol.get_symbol_as_weak(session, &mut None, &mut vec![], None).weak.upgrade()
+        ).collect()
+    }
+
+    /* Return the ranges of the parts of a string argument that are resolved to one of the targets,
+    like 'partner_id' in @api.depends('partner_id.name') */
+    fn find_targets_in_string(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, string: &ExprStringLiteral, call_expr: &ExprCall, names: &HashSet<String>, targets: &Vec<Rc<RefCell<Symbol>>>) -> Vec<TextRange> {
+        let mut ranges = vec![];
+        let value = string.value.to_string();
+        let scope = Symbol::get_scope_symbol(file_symbol.clone(), string.range.start().to_u32(), false);
+        let from_module = file_symbol.borrow().find_module();
+        let mut offset = string.range.start().to_usize() + 1;
+        for part in value.split('.') {
+            if names.contains(part) {
+                let symbols = FeaturesUtils::find_argument_symbols(session, scope.clone(), from_module.clone(), &value, call_expr, offset, string.range);
+                for (symbol, range) in symbols.iter() {
+                    if !ReferenceFeature::is_target(symbol, targets) {
+                        continue;
+                    }
+                    //some arguments are resolved on the whole string, including quotes
+                    let range = match *range == string.range {
+                        true => TextRange::new(string.range.start() + TextSize::new(1), string.range.end() - TextSize::new(1)),
+                        false => *range
+                    };
+                    if !ranges.contains(&range) {
+                        ranges.push(range);
+                    }
+                }
+            }
+            offset += part.len() + 1;
+        }
+        ranges
+    }
+
+    /* Add the references to fields in data files: <field name="..."/> in xml records and headers of csv files */
+    fn find_data_references(session: &mut SessionInfo, targets: &Vec<Rc<RefCell<Symbol>>>, locations: &mut Vec<Location>) {
+        let mut fields: HashSet<(OYarn, OYarn)> = HashSet::new(); //(model, field)
+        for target in targets.iter() {
+            if target.borrow().typ() != SymType::VARIABLE {
+                continue;
+            }
+            if let Some((model_name, _)) = ReferenceFeature::get_model_member_info(target) {
+                if target.borrow().is_field(session) {
+                    fields.insert((model_name, target.borrow().name().clone()));
+                }
+            }
+        }
+        if fields.is_empty() {
+            return;
+        }
+        let modules = session.sync_odoo.modules.values().filter_map(|m| m.upgrade()).collect::<Vec<_>>();
+        for module in modules.iter() {
+            let data_symbols = module.borrow().as_module_package().data_symbols.values().cloned().collect::<Vec<_>>();
+            for data_symbol in data_symbols.iter() {
+                let path = data_symbol.borrow().paths()[0].clone();
+                let mut ranges = vec![];
+                match data_symbol.borrow().typ() {
+                    SymType::XML_FILE => {
+                        for xml_data in data_symbol.borrow().as_xml_file_sym().xml_ids.values().flatten() {
+                            let OdooData::RECORD(record) = xml_data else {
+                                continue;
+                            };
+                            for field in record.fields.iter() {
+                                if fields.contains(&(record.model.0.clone(), field.name.clone())) {
+                                    ranges.push(ReferenceFeature::attribute_value_range(&field.range, field.name.len()));
+                                }
+                            }
+                        }
+                    },
+                    SymType::CSV_FILE => {
+                        let data_symbol = data_symbol.borrow();
+                        let csv = data_symbol.as_csv_file_sym();
+                        for (header, header_range) in csv.headers.iter() {
+                            let field_name = header.as_str().split(|c| c == ':' || c == '/').next().unwrap_or("");
+                            if fields.iter().any(|(model, field)| *model == csv.model_name && field.as_str() == field_name) {
+                                ranges.push(header_range.start..header_range.start + field_name.len());
+                            }
+                        }
+                    },
+                    _ => {}
+                }
+                for range in ranges.iter() {
+                    let range = session.sync_odoo.get_file_mgr().borrow().std_range_to_range(session, &path, range);
+                    ReferenceFeature::push_location(locations, Location { uri: FileMgr::pathname2uri(&path), range });
+                }
+            }
+        }
+    }
+
+    /* Given the range of an attribute (name="value"), return the range of its value */
+    fn attribute_value_range(attribute_range: &StdRange<usize>, value_len: usize) -> StdRange<usize> {
+        let end = attribute_range.end.saturating_sub(1);
+        end.saturating_sub(value_len)..end
+    }
+
+    fn push_location(locations: &mut Vec<Location>, location: Location) {
+        if !locations.contains(&location) {
+            locations.push(location);
+        }
     }
 
     pub fn get_references_xml(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, line: u32, character: u32) -> Option<Vec<Location>> {
@@ -22,6 +439,10 @@ impl ReferenceFeature {
         let document = roxmltree::Document::parse(&data);
         if let Ok(document) = document {
             let root = document.root_element();
+            if let Some(occurrence) = XmlIdUsages::collect_xml(&root).into_iter().find(|o| o.range.start <= offset && offset <= o.range.end) {
+                let (module_name, xml_id) = XmlIdUsages::resolve(file_symbol, &occurrence.xml_id)?;
+                return Some(ReferenceFeature::find_xml_id_references(session, file_symbol, &module_name, &xml_id));
+            }
             let (symbols, _range) = XmlAstUtils::get_symbols(session, file_symbol, root, offset, false);
             if symbols.is_empty() {
                 return None;
@@ -64,7 +485,163 @@ impl ReferenceFeature {
         None
     }
 
-    pub fn get_references_csv(_session: &mut SessionInfo, _file_symbol: &Rc<RefCell<Symbol>>, _file_info: &Rc<RefCell<FileInfo>>, _line: u32, _character: u32) -> Option<Vec<Location>> {
-        None
+    pub fn get_references_csv(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, line: u32, character: u32) -> Option<Vec<Location>> {
+        let offset = file_info.borrow().position_to_offset(line, character);
+        let data = file_info.borrow().file_info_ast.borrow().text_rope.as_ref()?.to_string();
+        match CsvAstUtils::get_element(&data, offset)? {
+            CsvAstElement::HEADER(field_name, _) => {
+                let fields = CsvAstUtils::get_header_fields(session, file_symbol, &field_name, false);
+                if fields.is_empty() {
+                    return None;
+                }
+                Some(ReferenceFeature::find_symbol_references(session, file_symbol, fields))
+            },
+            CsvAstElement::XML_ID(occurrence) => {
+                let (module_name, xml_id) = XmlIdUsages::resolve(file_symbol, &occurrence.xml_id)?;
+                Some(ReferenceFeature::find_xml_id_references(session, file_symbol, &module_name, &xml_id))
+            }
+        }
     }
-}
\ No newline at end of file
+
+    /* Find all declarations and usages of an xml_id, with xml_id_locations and xml_id_usages of its module.
+    Returned ranges only cover the xml_id part, without the "module." prefix */
+    pub(crate) fn find_xml_id_references(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, module_name: &OYarn, xml_id: &OYarn) -> Vec<Location> {
+        let mut locations = vec![];
+        let mut files: PtrWeakHashSet<Weak<RefCell<Symbol>>> = PtrWeakHashSet::new();
+        files.insert(file_symbol.clone());
+        if let Some(module) = session.sync_odoo.modules.get(module_name).and_then(|m| m.upgrade()) {
+            let module = module.borrow();
+            let module = module.as_module_package();
+            for index in [&module.xml_id_locations, &module.xml_id_usages] {
+                if let Some(set) = index.get(xml_id) {
+                    for file in set.iter() {
+                        files.insert(file);
+                    }
+                }
+            }
+        }
+        for file in files.iter() {
+            let typ = file.borrow().typ();
+            let (path, occurrences) = match typ {
+                SymType::XML_FILE | SymType::CSV_FILE => {
+                    let path = file.borrow().paths()[0].clone();
+                    (path, RenameFeature::get_data_xml_ids(session, &file))
+                },
+                SymType::FILE | SymType::PACKAGE(_) => {
+                    let Some(path) = ReferenceFeature::get_python_file_path(&file) else {
+                        continue;
+                    };
+                    (path, RenameFeature::get_python_xml_ids(session, &file))
+                },
+                _ => continue
+            };
+            for occurrence in occurrences.iter() {
+                if XmlIdUsages::resolve(&file, &occurrence.xml_id) != Some((module_name.clone(), xml_id.clone())) {
+                    continue;
+                }
+                let range = session.sync_odoo.get_file_mgr().borrow().std_range_to_range(session, &path, &occurrence.id_range());
+                ReferenceFeature::push_location(&mut locations, Location { uri: FileMgr::pathname2uri(&path), range });
+            }
+        }
+        locations
+    }
+}
+
+enum NameOccurrence<'a> {
+    Declaration(&'a Identifier), //name of a class or a function
+    Expr(&'a Expr, TextRange), //expression to evaluate and range of the name in it
+    Parameter(&'a Parameter),
+    String(&'a ExprStringLiteral, &'a ExprCall), //string argument of a call, that contains one of the names
+}
+
+/* Collect all identifiers of an ast that have one of the given names, and the string arguments that
+contain one of them */
+struct NameOccurrenceVisitor<'a, 'b> {
+    names: &'b HashSet<String>,
+    occurrences: Vec<NameOccurrence<'a>>,
+    calls: Vec<&'a ExprCall>,
+}
+
+impl<'a, 'b> Visitor<'a> for NameOccurrenceVisitor<'a, 'b> {
+
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::FunctionDef(func_def) if self.names.contains(func_def.name.id.as_str()) => {
+                self.occurrences.push(NameOccurrence::Declaration(&func_def.name));
+            },
+            Stmt::ClassDef(class_def) if self.names.contains(class_def.name.id.as_str()) => {
+                self.occurrences.push(NameOccurrence::Declaration(&class_def.name));
+            },
+            _ => {}
+        }
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match expr {
+            Expr::Call(call) => {
+                self.calls.push(call);
+                walk_expr(self, expr);
+                self.calls.pop();
+                return;
+            },
+            Expr::StringLiteral(string) => {
+                if let Some(call) = self.calls.last().copied() {
+                    if string.value.to_str().split('.').any(|part| self.names.contains(part)) {
+                        self.occurrences.push(NameOccurrence::String(string, call));
+                    }
+                }
+            },
+            Expr::Name(name) if self.names.contains(name.id.as_str()) => {
+                self.occurrences.push(NameOccurrence::Expr(expr, name.range));
+            },
+            Expr::Attribute(attr) if self.names.contains(attr.attr.id.as_str()) => {
+                self.occurrences.push(NameOccurrence::Expr(expr, attr.attr.range));
+            },
+            _ => {}
+        }
+        walk_expr(self, expr);
+    }
+
+    fn visit_parameter(&mut self, parameter: &'a Parameter) {
+        if self.names.contains(parameter.name.id.as_str()) {
+            self.occurrences.push(NameOccurrence::Parameter(parameter));
+        }
+        walk_parameter(self, parameter);
+    }
+}
+
+/* Find the name of the class or function declared at the given offset */
+struct DeclarationFinderVisitor<'a> {
+    offset: TextSize,
+    name: Option<&'a Identifier>,
+}
+
+impl<'a> DeclarationFinderVisitor<'a> {
+    fn find_declaration_at(stmt: &'a Stmt, offset: u32) -> Option<&'a Identifier> {
+        let mut visitor = Self {
+            offset: TextSize::new(offset),
+            name: None,
+        };
+        visitor.visit_stmt(stmt);
+        visitor.name
+    }
+}
+
+impl<'a> Visitor<'a> for DeclarationFinderVisitor<'a> {
+
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        if self.name.is_some() || !stmt.range().contains_inclusive(self.offset) {
+            return;
+        }
+        match stmt {
+            Stmt::FunctionDef(func_def) if func_def.name.range.contains_inclusive(self.offset) => {
+                self.name = Some(&func_def.name);
+            },
+            Stmt::ClassDef(class_def) if class_def.name.range.contains_inclusive(self.offset) => {
+                self.name = Some(&class_def.name);
+            },
+            _ => walk_stmt(self, stmt)
+        }
+    }
+}
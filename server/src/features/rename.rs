@@ -0,0 +1,216 @@
+use std::{cell::RefCell, collections::HashMap, fs, rc::Rc};
+
+use lsp_types::{Location, PrepareRenameResponse, TextEdit, WorkspaceEdit};
+
+use crate::{constants::{OYarn, SymType}, core::{file_mgr::FileInfo, symbols::symbol::Symbol, xml_id_usages::{XmlIdOccurrence, XmlIdUsages}}, features::{references::ReferenceFeature, xml_ast_utils::{XmlAstResult, XmlAstUtils}}, threads::SessionInfo};
+
+
+pub struct RenameFeature {}
+
+impl RenameFeature {
+
+    pub fn prepare_rename(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, line: u32, character: u32) -> Option<PrepareRenameResponse> {
+        let offset = file_info.borrow().position_to_offset(line, character);
+        let targets = RenameFeature::get_python_targets(session, file_symbol, file_info, offset);
+        if targets.is_empty() {
+            let occurrences = RenameFeature::get_python_xml_ids(session, file_symbol);
+            return RenameFeature::build_xml_id_prepare_response(session, file_symbol, file_info, occurrences, offset);
+        }
+        RenameFeature::build_prepare_response(targets)
+    }
+
+    pub fn prepare_rename_xml(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, line: u32, character: u32) -> Option<PrepareRenameResponse> {
+        let offset = file_info.borrow().position_to_offset(line, character);
+        let targets = RenameFeature::get_xml_targets(session, file_symbol, file_info, offset);
+        if targets.is_empty() {
+            let occurrences = RenameFeature::get_data_xml_ids(session, file_symbol);
+            return RenameFeature::build_xml_id_prepare_response(session, file_symbol, file_info, occurrences, offset);
+        }
+        RenameFeature::build_prepare_response(targets)
+    }
+
+    pub fn prepare_rename_csv(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, line: u32, character: u32) -> Option<PrepareRenameResponse> {
+        let offset = file_info.borrow().position_to_offset(line, character);
+        let targets = RenameFeature::get_csv_targets(session, file_symbol, offset);
+        if targets.is_empty() {
+            let occurrences = RenameFeature::get_data_xml_ids(session, file_symbol);
+            return RenameFeature::build_xml_id_prepare_response(session, file_symbol, file_info, occurrences, offset);
+        }
+        RenameFeature::build_prepare_response(targets)
+    }
+
+    pub fn rename(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, line: u32, character: u32, new_name: &String) -> Option<WorkspaceEdit> {
+        let offset = file_info.borrow().position_to_offset(line, character);
+        let targets = RenameFeature::get_python_targets(session, file_symbol, file_info, offset);
+        if targets.is_empty() {
+            let occurrences = RenameFeature::get_python_xml_ids(session, file_symbol);
+            return RenameFeature::build_xml_id_workspace_edit(session, file_symbol, occurrences, offset, new_name);
+        }
+        RenameFeature::build_workspace_edit(session, file_symbol, targets, new_name)
+    }
+
+    pub fn rename_xml(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, line: u32, character: u32, new_name: &String) -> Option<WorkspaceEdit> {
+        let offset = file_info.borrow().position_to_offset(line, character);
+        let targets = RenameFeature::get_xml_targets(session, file_symbol, file_info, offset);
+        if targets.is_empty() {
+            let occurrences = RenameFeature::get_data_xml_ids(session, file_symbol);
+            return RenameFeature::build_xml_id_workspace_edit(session, file_symbol, occurrences, offset, new_name);
+        }
+        RenameFeature::build_workspace_edit(session, file_symbol, targets, new_name)
+    }
+
+    pub fn rename_csv(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, line: u32, character: u32, new_name: &String) -> Option<WorkspaceEdit> {
+        let offset = file_info.borrow().position_to_offset(line, character);
+        let targets = RenameFeature::get_csv_targets(session, file_symbol, offset);
+        if targets.is_empty() {
+            let occurrences = RenameFeature::get_data_xml_ids(session, file_symbol);
+            return RenameFeature::build_xml_id_workspace_edit(session, file_symbol, occurrences, offset, new_name);
+        }
+        RenameFeature::build_workspace_edit(session, file_symbol, targets, new_name)
+    }
+
+    /* Among python symbols, only model fields can be renamed for now */
+    fn is_renamable(session: &mut SessionInfo, symbol: &Rc<RefCell<Symbol>>) -> bool {
+        ReferenceFeature::get_model_member_info(symbol).is_some() && symbol.borrow().is_field(session)
+    }
+
+    fn get_python_targets(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, offset: usize) -> Vec<Rc<RefCell<Symbol>>> {
+        let targets = ReferenceFeature::get_target_symbols(session, file_symbol, file_info, offset);
+        targets.into_iter().filter(|t| RenameFeature::is_renamable(session, t)).collect()
+    }
+
+    fn get_xml_targets(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, offset: usize) -> Vec<Rc<RefCell<Symbol>>> {
+        let data = file_info.borrow().file_info_ast.borrow().text_rope.as_ref().unwrap().to_string();
+        let Ok(document) = roxmltree::Document::parse(&data) else {
+            return vec![];
+        };
+        let (results, _range) = XmlAstUtils::get_symbols(session, file_symbol, document.root_element(), offset, false);
+        let mut targets = vec![];
+        for result in results.iter() {
+            if let XmlAstResult::SYMBOL(symbol) = result {
+                if RenameFeature::is_renamable(session, symbol) {
+                    targets.push(symbol.clone());
+                }
+            }
+        }
+        targets
+    }
+
+    /* Return the fields of the csv header at the given offset */
+    fn get_csv_targets(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, offset: usize) -> Vec<Rc<RefCell<Symbol>>> {
+        let (model_name, field_name) = {
+            let file = file_symbol.borrow();
+            let csv = file.as_csv_file_sym();
+            let Some((header, _)) = csv.headers.iter().find(|(_, range)| range.start <= offset && offset <= range.end) else {
+                return vec![];
+            };
+            let field_name = header.as_str().split(|c| c == ':' || c == '/').next().unwrap_or("").to_string();
+            (csv.model_name.clone(), field_name)
+        };
+        let Some(model) = session.sync_odoo.models.get(&model_name).cloned() else {
+            return vec![];
+        };
+        let from_module = file_symbol.borrow().find_module();
+        let classes = model.borrow().all_symbols(session, from_module, true);
+        let mut targets = vec![];
+        for (class, _) in classes.iter() {
+            let symbols = class.borrow().get_content_symbol(&field_name, u32::MAX).symbols;
+            for symbol in symbols.iter() {
+                if RenameFeature::is_renamable(session, symbol) {
+                    targets.push(symbol.clone());
+                }
+            }
+        }
+        targets
+    }
+
+    fn build_prepare_response(targets: Vec<Rc<RefCell<Symbol>>>) -> Option<PrepareRenameResponse> {
+        if targets.is_empty() {
+            return None;
+        }
+        Some(PrepareRenameResponse::DefaultBehavior { default_behavior: true })
+    }
+
+    fn build_workspace_edit(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, targets: Vec<Rc<RefCell<Symbol>>>, new_name: &String) -> Option<WorkspaceEdit> {
+        if targets.is_empty() {
+            return None;
+        }
+        let locations = ReferenceFeature::find_symbol_references(session, file_symbol, targets);
+        let mut changes: HashMap<lsp_types::Uri, Vec<TextEdit>> = HashMap::new();
+        for Location { uri, range } in locations.into_iter() {
+            changes.entry(uri).or_default().push(TextEdit { range, new_text: new_name.clone() });
+        }
+        Some(WorkspaceEdit::new(changes))
+    }
+
+    /* Return the module name and the xml_id of the occurrence at offset, if it is declared in a data file */
+    fn get_renamable_xml_id(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, occurrences: &Vec<XmlIdOccurrence>, offset: usize) -> Option<(XmlIdOccurrence, OYarn, OYarn)> {
+        let occurrence = occurrences.iter().find(|o| o.range.start <= offset && offset <= o.range.end)?;
+        let (module_name, xml_id) = XmlIdUsages::resolve(file_symbol, &occurrence.xml_id)?;
+        let module = session.sync_odoo.modules.get(&module_name).and_then(|m| m.upgrade())?;
+        let declarations = module.borrow().as_module_package().get_xml_id(&xml_id);
+        let in_data_file = declarations.iter().any(|d| {
+            d.get_file_symbol().and_then(|f| f.upgrade()).map(|f| matches!(f.borrow().typ(), SymType::XML_FILE | SymType::CSV_FILE)).unwrap_or(false)
+        });
+        if !in_data_file {
+            return None;
+        }
+        Some((occurrence.clone(), module_name, xml_id))
+    }
+
+    fn build_xml_id_prepare_response(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, occurrences: Vec<XmlIdOccurrence>, offset: usize) -> Option<PrepareRenameResponse> {
+        let (occurrence, _, xml_id) = RenameFeature::get_renamable_xml_id(session, file_symbol, &occurrences, offset)?;
+        Some(PrepareRenameResponse::RangeWithPlaceholder {
+            range: file_info.borrow().std_range_to_range(&occurrence.id_range()),
+            placeholder: xml_id.to_string()
+        })
+    }
+
+    /* Rename the xml_id part of all declarations and usages found with xml_id_locations and xml_id_usages of the module */
+    fn build_xml_id_workspace_edit(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, occurrences: Vec<XmlIdOccurrence>, offset: usize, new_name: &String) -> Option<WorkspaceEdit> {
+        let (_, module_name, xml_id) = RenameFeature::get_renamable_xml_id(session, file_symbol, &occurrences, offset)?;
+        let locations = ReferenceFeature::find_xml_id_references(session, file_symbol, &module_name, &xml_id);
+        let mut changes: HashMap<lsp_types::Uri, Vec<TextEdit>> = HashMap::new();
+        for Location { uri, range } in locations.into_iter() {
+            changes.entry(uri).or_default().push(TextEdit { range, new_text: new_name.clone() });
+        }
+        Some(WorkspaceEdit::new(changes))
+    }
+
+    pub(crate) fn get_data_xml_ids(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>) -> Vec<XmlIdOccurrence> {
+        let path = file_symbol.borrow().paths()[0].clone();
+        let content = match session.sync_odoo.get_file_mgr().borrow().get_file_info(&path) {
+            Some(file_info) if file_info.borrow().file_info_ast.borrow().text_rope.is_some() => {
+                file_info.borrow().file_info_ast.borrow().text_rope.as_ref().unwrap().to_string()
+            },
+            _ => match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(_) => return vec![]
+            }
+        };
+        match file_symbol.borrow().typ() {
+            SymType::XML_FILE => {
+                match roxmltree::Document::parse(&content) {
+                    Ok(document) => XmlIdUsages::collect_xml(&document.root_element()),
+                    Err(_) => vec![]
+                }
+            },
+            SymType::CSV_FILE => XmlIdUsages::collect_csv(&content),
+            _ => vec![]
+        }
+    }
+
+    pub(crate) fn get_python_xml_ids(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>) -> Vec<XmlIdOccurrence> {
+        let Some(path) = ReferenceFeature::get_python_file_path(file_symbol) else {
+            return vec![];
+        };
+        let Some(file_info) = session.sync_odoo.get_file_mgr().borrow().get_file_info(&path) else {
+            return vec![];
+        };
+        FileInfo::with_ast(&file_info, session, |_| {
+            let file_info_ast = file_info.borrow().file_info_ast.clone();
+            let file_info_ast = file_info_ast.borrow();
+            file_info_ast.get_stmts().map(XmlIdUsages::collect_python).unwrap_or_default()
+        })
+    }
+}
@@ -0,0 +1,277 @@
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+use lsp_types::{SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokensLegend};
+use ruff_python_ast::{visitor::{walk_expr, walk_stmt, Visitor}, Expr, ExprCall, ExprStringLiteral, Stmt};
+use ruff_text_size::{Ranged, TextRange, TextSize};
+
+use crate::{core::{file_mgr::FileInfo, symbols::symbol::Symbol, xml_id_usages::XmlIdUsages}, features::features_utils::FeaturesUtils, oyarn, threads::SessionInfo};
+
+/* Indexes in the legend */
+const MODEL_TOKEN: u32 = 0;
+const FIELD_TOKEN: u32 = 1;
+const XML_ID_TOKEN: u32 = 2;
+const UNRESOLVED_MODIFIER: u32 = 1 << 0;
+
+struct OdooToken {
+    range: TextRange,
+    token_type: u32,
+    unresolved: bool,
+}
+
+pub struct SemanticTokensFeature {}
+
+impl SemanticTokensFeature {
+
+    pub fn legend() -> SemanticTokensLegend {
+        SemanticTokensLegend {
+            token_types: vec![SemanticTokenType::CLASS, SemanticTokenType::PROPERTY, SemanticTokenType::VARIABLE],
+            token_modifiers: vec![SemanticTokenModifier::new("unresolved")],
+        }
+    }
+
+    /* Tokens for the strings that have a meaning for Odoo: model names, field paths and xml_ids.
+    If a range is given, only the strings in this range are resolved */
+    pub fn get_semantic_tokens(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, range: Option<lsp_types::Range>) -> Vec<SemanticToken> {
+        let text_range = range.map(|range| {
+            let start = file_info.borrow().position_to_offset(range.start.line, range.start.character);
+            let end = file_info.borrow().position_to_offset(range.end.line, range.end.character);
+            TextRange::new(TextSize::new(start as u32), TextSize::new(end as u32))
+        });
+        let file_info_ast = file_info.borrow().file_info_ast.clone();
+        let file_info_ast = file_info_ast.borrow();
+        let Some(stmts) = file_info_ast.get_stmts() else {
+            return vec![];
+        };
+        let mut visitor = OdooStringVisitor::default();
+        for stmt in stmts.iter() {
+            visitor.visit_stmt(stmt);
+        }
+        let in_range = |string: &ExprStringLiteral| text_range.map(|r| r.intersect(string.range).is_some()).unwrap_or(true);
+        let mut tokens = vec![];
+        for string in visitor.model_names.iter().filter(|s| in_range(**s)) {
+            let Some(range) = SemanticTokensFeature::get_content_range(string) else {
+                continue;
+            };
+            let resolved = session.sync_odoo.models.get(&oyarn!("{}", string.value.to_str())).map(|model| model.borrow_mut().has_symbols()).unwrap_or(false);
+            tokens.push(OdooToken { range, token_type: MODEL_TOKEN, unresolved: !resolved });
+        }
+        for string in visitor.xml_ids.iter().filter(|s| in_range(**s)) {
+            let Some(range) = SemanticTokensFeature::get_content_range(string) else {
+                continue;
+            };
+            let resolved = XmlIdUsages::resolve(file_symbol, string.value.to_str()).and_then(|(module_name, xml_id)| {
+                let module = session.sync_odoo.modules.get(&module_name).and_then(|m| m.upgrade())?;
+                let found = !module.borrow().as_module_package().get_xml_id(&xml_id).is_empty();
+                Some(found)
+            }).unwrap_or(false);
+            tokens.push(OdooToken { range, token_type: XML_ID_TOKEN, unresolved: !resolved });
+        }
+        for (string, call_expr) in visitor.field_paths.iter().filter(|(s, _)| in_range(*s)) {
+            let known_context = visitor.field_contexts.contains(&string.range.start());
+            SemanticTokensFeature::add_field_path_tokens(session, file_symbol, string, call_expr, known_context, &mut tokens);
+        }
+        SemanticTokensFeature::encode(file_info, tokens)
+    }
+
+    /* Resolve each part of a field path like "partner_id.country_id". A part that is not resolved is only
+    marked if the string is known to be a field path, or if a previous part was resolved */
+    fn add_field_path_tokens(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, string: &ExprStringLiteral, call_expr: &ExprCall, known_context: bool, tokens: &mut Vec<OdooToken>) {
+        let Some(content_range) = SemanticTokensFeature::get_content_range(string) else {
+            return;
+        };
+        let value = string.value.to_str().to_string();
+        let scope = Symbol::get_scope_symbol(file_symbol.clone(), string.range.start().to_u32(), false);
+        let from_module = file_symbol.borrow().find_module();
+        let mut is_field_path = known_context;
+        let mut offset = content_range.start().to_usize();
+        for part in value.split('.') {
+            let symbols = FeaturesUtils::find_argument_symbols(session, scope.clone(), from_module.clone(), &value, call_expr, offset, string.range);
+            let part_range = TextRange::new(TextSize::new(offset as u32), TextSize::new((offset + part.len()) as u32));
+            if symbols.is_empty() {
+                if is_field_path {
+                    tokens.push(OdooToken { range: part_range, token_type: FIELD_TOKEN, unresolved: true });
+                }
+            } else {
+                is_field_path = true;
+                for (_, range) in symbols.iter() {
+                    //some arguments are resolved on the whole string, including quotes
+                    let range = if *range == string.range { content_range } else { *range };
+                    if !tokens.iter().any(|t| t.range == range) {
+                        tokens.push(OdooToken { range, token_type: FIELD_TOKEN, unresolved: false });
+                    }
+                }
+            }
+            offset += part.len() + 1;
+        }
+    }
+
+    /* Range of the string without quotes, if the string is a simple literal */
+    fn get_content_range(string: &ExprStringLiteral) -> Option<TextRange> {
+        let value = string.value.to_str();
+        if string.range.len().to_usize() != value.len() + 2 {
+            return None;
+        }
+        Some(TextRange::new(string.range.start() + TextSize::new(1), string.range.end() - TextSize::new(1)))
+    }
+
+    fn encode(file_info: &Rc<RefCell<FileInfo>>, mut tokens: Vec<OdooToken>) -> Vec<SemanticToken> {
+        tokens.sort_by_key(|t| (t.range.start(), t.range.end()));
+        let mut result = vec![];
+        let mut previous_line = 0;
+        let mut previous_start = 0;
+        let mut previous_end = TextSize::new(0);
+        for token in tokens.iter() {
+            if token.range.start() < previous_end || token.range.is_empty() {
+                continue;
+            }
+            let start = file_info.borrow().offset_to_position(token.range.start().to_usize());
+            let end = file_info.borrow().offset_to_position(token.range.end().to_usize());
+            if start.line != end.line {
+                continue;
+            }
+            result.push(SemanticToken {
+                delta_line: start.line - previous_line,
+                delta_start: if start.line == previous_line { start.character - previous_start } else { start.character },
+                length: end.character - start.character,
+                token_type: token.token_type,
+                token_modifiers_bitset: if token.unresolved { UNRESOLVED_MODIFIER } else { 0 },
+            });
+            previous_line = start.line;
+            previous_start = start.character;
+            previous_end = token.range.end();
+        }
+        result
+    }
+}
+
+/* Collect the strings of an ast that can be model names, xml_ids or field paths */
+#[derive(Default)]
+struct OdooStringVisitor<'a> {
+    model_names: Vec<&'a ExprStringLiteral>,
+    xml_ids: Vec<&'a ExprStringLiteral>,
+    field_paths: Vec<(&'a ExprStringLiteral, &'a ExprCall)>,
+    field_contexts: HashSet<TextSize>, //start of the strings that can only be field paths
+    classified: HashSet<TextSize>,
+    calls: Vec<&'a ExprCall>,
+}
+
+impl<'a> OdooStringVisitor<'a> {
+
+    fn add_model_names(&mut self, expr: &'a Expr) {
+        match expr {
+            Expr::StringLiteral(string) => {
+                self.classified.insert(string.range.start());
+                self.model_names.push(string);
+            },
+            Expr::List(list) => list.elts.iter().for_each(|elt| self.add_model_names(elt)),
+            Expr::Tuple(tuple) => tuple.elts.iter().for_each(|elt| self.add_model_names(elt)),
+            _ => {}
+        }
+    }
+
+    fn is_field_path(value: &str) -> bool {
+        !value.is_empty() && value.split('.').all(|part|
+            part.chars().next().map(|c| c.is_ascii_lowercase() || c == '_').unwrap_or(false)
+            && part.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        )
+    }
+
+    fn visit_call(&mut self, call: &'a ExprCall) {
+        let callee = match call.func.as_ref() {
+            Expr::Name(name) => Some(name.id.as_str()),
+            Expr::Attribute(attr) => Some(attr.attr.id.as_str()),
+            _ => None
+        };
+        let first_string = match call.arguments.args.first() {
+            Some(Expr::StringLiteral(string)) => Some(string),
+            _ => None
+        };
+        match (callee, first_string) {
+            (Some("ref"), Some(string)) if matches!(call.func.as_ref(), Expr::Attribute(_)) => {
+                self.classified.insert(string.range.start());
+                self.xml_ids.push(string);
+            },
+            (Some("Many2one") | Some("One2many") | Some("Many2many"), Some(string)) => {
+                self.classified.insert(string.range.start());
+                self.model_names.push(string);
+            },
+            _ => {}
+        }
+        if let Expr::Attribute(attr) = call.func.as_ref() {
+            let is_api_decorator = matches!(attr.value.as_ref(), Expr::Name(name) if name.id.as_str() == "api")
+                && ["depends", "onchange", "constrains"].contains(&attr.attr.id.as_str());
+            if is_api_decorator {
+                for arg in call.arguments.args.iter() {
+                    self.field_contexts.insert(arg.range().start());
+                }
+            }
+        }
+        for keyword in call.arguments.keywords.iter() {
+            match (keyword.arg.as_ref().map(|arg| arg.id.as_str()), &keyword.value) {
+                (Some("comodel_name"), Expr::StringLiteral(string)) => {
+                    self.classified.insert(string.range.start());
+                    self.model_names.push(string);
+                },
+                (Some("related"), Expr::StringLiteral(string)) => {
+                    self.field_contexts.insert(string.range.start());
+                },
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for OdooStringVisitor<'a> {
+
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        if let Stmt::Assign(assign) = stmt {
+            if let [Expr::Name(target)] = assign.targets.as_slice() {
+                match target.id.as_str() {
+                    "_name" | "_inherit" => self.add_model_names(&assign.value),
+                    "_inherits" => {
+                        if let Expr::Dict(dict) = assign.value.as_ref() {
+                            for item in dict.items.iter() {
+                                if let Some(key) = item.key.as_ref() {
+                                    self.add_model_names(key);
+                                }
+                            }
+                        }
+                    },
+                    _ => {}
+                }
+            }
+        }
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match expr {
+            Expr::Call(call) => {
+                self.visit_call(call);
+                self.calls.push(call);
+                walk_expr(self, expr);
+                self.calls.pop();
+                return;
+            },
+            Expr::Subscript(subscript) => {
+                let is_env = match subscript.value.as_ref() {
+                    Expr::Name(name) => name.id.as_str() == "env",
+                    Expr::Attribute(attr) => attr.attr.id.as_str() == "env",
+                    _ => false
+                };
+                if is_env {
+                    self.add_model_names(&subscript.slice);
+                }
+            },
+            Expr::StringLiteral(string) => {
+                if let Some(call) = self.calls.last().copied() {
+                    if !self.classified.contains(&string.range.start()) && OdooStringVisitor::is_field_path(string.value.to_str()) {
+                        self.field_paths.push((string, call));
+                    }
+                }
+            },
+            _ => {}
+        }
+        walk_expr(self, expr);
+    }
+}
@@ -0,0 +1,188 @@
+use std::{cell::RefCell, collections::HashMap, path::PathBuf, rc::Rc};
+
+use lsp_types::{SymbolKind, TypeHierarchyItem};
+use serde_json::{json, Value};
+
+use crate::{constants::{OYarn, SymType}, core::{file_mgr::FileInfo, odoo::SyncOdoo, symbols::symbol::Symbol}, features::{features_utils::FeaturesUtils, references::ReferenceFeature}, oyarn, threads::SessionInfo};
+
+/* An item of the hierarchy is either a model, or a python class that is not a model.
+The item data contains the model name, or the path, name and position of the class */
+enum HierarchyNode {
+    Model(OYarn),
+    Class(Rc<RefCell<Symbol>>),
+}
+
+pub struct TypeHierarchyFeature {}
+
+impl TypeHierarchyFeature {
+
+    pub fn prepare(session: &mut SessionInfo, file_symbol: &Rc<RefCell<Symbol>>, file_info: &Rc<RefCell<FileInfo>>, line: u32, character: u32) -> Option<Vec<TypeHierarchyItem>> {
+        let offset = file_info.borrow().position_to_offset(line, character);
+        let targets = ReferenceFeature::get_target_symbols(session, file_symbol, file_info, offset);
+        let mut nodes = vec![];
+        for target in targets.iter() {
+            if target.borrow().typ() != SymType::CLASS {
+                continue;
+            }
+            let model_name = target.borrow().as_class_sym()._model.as_ref().map(|m| m.name.clone());
+            match model_name {
+                Some(model_name) => TypeHierarchyFeature::push_node(&mut nodes, HierarchyNode::Model(model_name)),
+                None => TypeHierarchyFeature::push_node(&mut nodes, HierarchyNode::Class(target.clone())),
+            }
+        }
+        TypeHierarchyFeature::build_items(session, nodes)
+    }
+
+    /* Supertypes of a model are the models in _inherit and _inherits, and the python bases of its classes */
+    pub fn get_supertypes(session: &mut SessionInfo, item: &TypeHierarchyItem) -> Option<Vec<TypeHierarchyItem>> {
+        let node = TypeHierarchyFeature::get_node(session, item)?;
+        let mut nodes = vec![];
+        let classes = match &node {
+            HierarchyNode::Model(model_name) => {
+                let Some(model) = session.sync_odoo.models.get(model_name).cloned() else {
+                    return None;
+                };
+                let classes = model.borrow().get_symbols(session, None);
+                for class in classes.iter() {
+                    let class_bw = class.borrow();
+                    let Some(model_data) = class_bw.as_class_sym()._model.as_ref() else {
+                        continue;
+                    };
+                    for inherit in model_data.inherit.iter().chain(model_data.inherits.iter().map(|(inherits, _)| inherits)) {
+                        if inherit != model_name {
+                            TypeHierarchyFeature::push_node(&mut nodes, HierarchyNode::Model(inherit.clone()));
+                        }
+                    }
+                }
+                classes
+            },
+            HierarchyNode::Class(class) => vec![class.clone()],
+        };
+        for class in classes.iter() {
+            let bases = class.borrow().as_class_sym().bases.iter().filter_map(|b| b.upgrade()).collect::<Vec<_>>();
+            for base in bases.iter() {
+                let base_model = base.borrow().as_class_sym()._model.as_ref().map(|m| m.name.clone());
+                match base_model {
+                    Some(base_model) => {
+                        if !matches!(&node, HierarchyNode::Model(model_name) if *model_name == base_model) {
+                            TypeHierarchyFeature::push_node(&mut nodes, HierarchyNode::Model(base_model));
+                        }
+                    },
+                    None => TypeHierarchyFeature::push_node(&mut nodes, HierarchyNode::Class(base.clone())),
+                }
+            }
+        }
+        TypeHierarchyFeature::build_items(session, nodes)
+    }
+
+    /* Subtypes are the models that _inherit or _inherits the model, or that have the class as python base.
+    Python classes that are not models are not indexed, so only models can be subtypes */
+    pub fn get_subtypes(session: &mut SessionInfo, item: &TypeHierarchyItem) -> Option<Vec<TypeHierarchyItem>> {
+        let node = TypeHierarchyFeature::get_node(session, item)?;
+        let mut nodes = vec![];
+        for name in TypeHierarchyFeature::get_subtype_map(session, &node) {
+            TypeHierarchyFeature::push_node(&mut nodes, HierarchyNode::Model(name));
+        }
+        TypeHierarchyFeature::build_items(session, nodes)
+    }
+
+    /* Build in one pass over the models the reverse map of their _inherit, _inherits and python bases, and return
+    the models found for the node */
+    fn get_subtype_map(session: &mut SessionInfo, node: &HierarchyNode) -> Vec<OYarn> {
+        let mut inherited_by: HashMap<OYarn, Vec<OYarn>> = HashMap::new();
+        let mut based_on: Vec<(Rc<RefCell<Symbol>>, OYarn)> = vec![];
+        let models = session.sync_odoo.models.iter().map(|(name, model)| (name.clone(), model.clone())).collect::<Vec<_>>();
+        for (name, model) in models.iter() {
+            for class in model.borrow().get_symbols(session, None).iter() {
+                let class_bw = class.borrow();
+                let class_sym = class_bw.as_class_sym();
+                if let Some(model_data) = class_sym._model.as_ref() {
+                    for parent in model_data.inherit.iter().chain(model_data.inherits.iter().map(|(inherits, _)| inherits)) {
+                        if parent != name {
+                            inherited_by.entry(parent.clone()).or_default().push(name.clone());
+                        }
+                    }
+                }
+                for base in class_sym.bases.iter().filter_map(|b| b.upgrade()) {
+                    based_on.push((base, name.clone()));
+                }
+            }
+        }
+        match node {
+            HierarchyNode::Model(model_name) => inherited_by.remove(model_name).unwrap_or_default(),
+            HierarchyNode::Class(class) => based_on.into_iter().filter(|(base, _)| Rc::ptr_eq(base, class)).map(|(_, name)| name).collect(),
+        }
+    }
+
+    fn push_node(nodes: &mut Vec<HierarchyNode>, node: HierarchyNode) {
+        let exists = nodes.iter().any(|n| match (n, &node) {
+            (HierarchyNode::Model(a), HierarchyNode::Model(b)) => a == b,
+            (HierarchyNode::Class(a), HierarchyNode::Class(b)) => Rc::ptr_eq(a, b),
+            _ => false
+        });
+        if !exists {
+            nodes.push(node);
+        }
+    }
+
+    fn build_items(session: &mut SessionInfo, nodes: Vec<HierarchyNode>) -> Option<Vec<TypeHierarchyItem>> {
+        let items = nodes.iter().filter_map(|node| TypeHierarchyFeature::build_item(session, node)).collect::<Vec<_>>();
+        if items.is_empty() {
+            return None;
+        }
+        Some(items)
+    }
+
+    fn build_item(session: &mut SessionInfo, node: &HierarchyNode) -> Option<TypeHierarchyItem> {
+        let (name, class, data) = match node {
+            HierarchyNode::Model(model_name) => {
+                let model = session.sync_odoo.models.get(model_name).cloned()?;
+                let main_classes = model.borrow().get_main_symbols(session, None);
+                let class = match main_classes.first() {
+                    Some(class) => class.clone(),
+                    None => model.borrow().get_symbols(session, None).first()?.clone(),
+                };
+                (model_name.to_string(), class, json!({"model": model_name.to_string()}))
+            },
+            HierarchyNode::Class(class) => {
+                let file = class.borrow().get_file()?.upgrade()?;
+                let path = ReferenceFeature::get_python_file_path(&file)?;
+                let class_bw = class.borrow();
+                let data = json!({
+                    "path": path,
+                    "name": class_bw.name().to_string(),
+                    "start": class_bw.range().start().to_u32(),
+                });
+                (class_bw.name().to_string(), class.clone(), data)
+            }
+        };
+        let location = FeaturesUtils::get_symbol_location(session, &class)?;
+        let detail = class.borrow().find_module().map(|m| m.borrow().name().to_string());
+        Some(TypeHierarchyItem {
+            name,
+            kind: SymbolKind::CLASS,
+            tags: None,
+            detail,
+            uri: location.uri,
+            range: location.range,
+            selection_range: location.range,
+            data: Some(data),
+        })
+    }
+
+    fn get_node(session: &mut SessionInfo, item: &TypeHierarchyItem) -> Option<HierarchyNode> {
+        let data = item.data.as_ref()?;
+        if let Some(model_name) = data.get("model").and_then(Value::as_str) {
+            return Some(HierarchyNode::Model(oyarn!("{}", model_name)));
+        }
+        let path = data.get("path").and_then(Value::as_str)?;
+        let name = data.get("name").and_then(Value::as_str)?;
+        let start = data.get("start").and_then(Value::as_u64)? as u32;
+        let file_symbol = SyncOdoo::get_symbol_of_opened_file(session, &PathBuf::from(path))?;
+        let class = Symbol::get_scope_symbol(file_symbol, start, true);
+        if class.borrow().typ() != SymType::CLASS || class.borrow().name().as_str() != name {
+            return None;
+        }
+        Some(HierarchyNode::Class(class))
+    }
+}
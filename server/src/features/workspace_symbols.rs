@@ -0,0 +1,154 @@
+use std::{cell::RefCell, iter::once, path::PathBuf, rc::Rc};
+
+use lsp_types::{Location, OneOf, Range, SymbolKind, WorkspaceSymbol, WorkspaceSymbolResponse};
+
+use crate::{constants::{OYarn, SymType}, core::{file_mgr::FileMgr, model::Model, symbols::symbol::Symbol}, features::features_utils::FeaturesUtils, threads::SessionInfo, utils::PathSanitizer};
+
+const MAX_RESULTS: usize = 100;
+
+enum Candidate {
+    Module(Rc<RefCell<Symbol>>),
+    Model(Rc<RefCell<Model>>),
+    Field(Vec<Rc<RefCell<Symbol>>>), //all the declarations of the field in the classes of the model
+    XmlId(Rc<RefCell<Symbol>>, OYarn), //module and xml_id without module part
+}
+
+pub struct WorkspaceSymbolFeature;
+
+impl WorkspaceSymbolFeature {
+
+    /* Search modules, models, fields ("model.field") and xml_ids ("module.xml_id") matching the query.
+    Candidates are only matched on their names, and symbols and locations are resolved for the best ones only */
+    pub fn get_workspace_symbols(session: &mut SessionInfo, query: &str) -> Option<WorkspaceSymbolResponse> {
+        let query = query.trim().to_lowercase().chars().collect::<Vec<char>>();
+        if query.is_empty() {
+            return None;
+        }
+        let mut candidates: Vec<(i32, String, Candidate)> = vec![];
+        let modules = session.sync_odoo.modules.values().filter_map(|m| m.upgrade()).collect::<Vec<_>>();
+        for module in modules.iter() {
+            let module_bw = module.borrow();
+            let module_name = module_bw.name().clone();
+            if let Some(score) = WorkspaceSymbolFeature::fuzzy_score(&query, module_name.as_str().chars()) {
+                candidates.push((score, module_name.to_string(), Candidate::Module(module.clone())));
+            }
+            for xml_id in module_bw.as_module_package().xml_id_locations.keys() {
+                if let Some(score) = WorkspaceSymbolFeature::fuzzy_score(&query, module_name.as_str().chars().chain(once('.')).chain(xml_id.as_str().chars())) {
+                    candidates.push((score, format!("{}.{}", module_name.as_str(), xml_id.as_str()), Candidate::XmlId(module.clone(), xml_id.clone())));
+                }
+            }
+        }
+        let models = session.sync_odoo.models.iter().map(|(name, model)| (name.clone(), model.clone())).collect::<Vec<_>>();
+        for (model_name, model) in models.iter() {
+            if let Some(score) = WorkspaceSymbolFeature::fuzzy_score(&query, model_name.as_str().chars()) {
+                candidates.push((score, model_name.to_string(), Candidate::Model(model.clone())));
+            }
+            let mut fields: Vec<(OYarn, i32, Vec<Rc<RefCell<Symbol>>>)> = vec![];
+            let classes = model.borrow().all_symbols(session, None, false);
+            for (class, _) in classes.iter() {
+                for (name, sections) in class.borrow().iter_symbols() {
+                    let symbols = sections.values().flatten().filter(|s| s.borrow().typ() == SymType::VARIABLE).cloned();
+                    if let Some((_, _, declarations)) = fields.iter_mut().find(|(field_name, _, _)| field_name.as_str() == name.as_str()) {
+                        declarations.extend(symbols);
+                        continue;
+                    }
+                    let Some(score) = WorkspaceSymbolFeature::fuzzy_score(&query, model_name.as_str().chars().chain(once('.')).chain(name.as_str().chars())) else {
+                        continue;
+                    };
+                    let symbols = symbols.collect::<Vec<_>>();
+                    if !symbols.is_empty() {
+                        fields.push((name.clone(), score, symbols));
+                    }
+                }
+            }
+            for (name, score, declarations) in fields {
+                candidates.push((score, format!("{}.{}", model_name.as_str(), name.as_str()), Candidate::Field(declarations)));
+            }
+        }
+        candidates.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        let mut results = vec![];
+        for (_, name, candidate) in candidates.into_iter() {
+            if results.len() >= MAX_RESULTS {
+                break;
+            }
+            if let Some(symbol) = WorkspaceSymbolFeature::build_workspace_symbol(session, name, candidate) {
+                results.push(symbol);
+            }
+        }
+        Some(WorkspaceSymbolResponse::Nested(results))
+    }
+
+    fn build_workspace_symbol(session: &mut SessionInfo, name: String, candidate: Candidate) -> Option<WorkspaceSymbol> {
+        let (kind, location, container) = match candidate {
+            Candidate::Module(module) => {
+                let path = PathBuf::from(module.borrow().paths().first()?).join("__manifest__.py").sanitize();
+                (SymbolKind::MODULE, Location { uri: FileMgr::pathname2uri(&path), range: Range::default() }, None)
+            },
+            Candidate::Model(model) => {
+                let main_classes = model.borrow().get_main_symbols(session, None);
+                let class = main_classes.first()?;
+                (SymbolKind::CLASS, FeaturesUtils::get_symbol_location(session, class)?, class.borrow().find_module())
+            },
+            Candidate::Field(declarations) => {
+                let symbol = declarations.into_iter().find(|s| s.borrow().is_field(session))?;
+                (SymbolKind::FIELD, FeaturesUtils::get_symbol_location(session, &symbol)?, symbol.borrow().find_module())
+            },
+            Candidate::XmlId(module, xml_id) => {
+                let declarations = module.borrow().as_module_package().get_xml_id(&xml_id);
+                let declaration = declarations.first()?;
+                let symbol = declaration.get_file_symbol()?.upgrade()?;
+                let typ = symbol.borrow().typ();
+                let location = match typ {
+                    SymType::XML_FILE | SymType::CSV_FILE => {
+                        let path = symbol.borrow().paths().first()?.clone();
+                        let range = session.sync_odoo.get_file_mgr().borrow().std_range_to_range(session, &path, &declaration.get_range());
+                        Location { uri: FileMgr::pathname2uri(&path), range }
+                    },
+                    _ => FeaturesUtils::get_symbol_location(session, &symbol)?
+                };
+                (SymbolKind::KEY, location, Some(module))
+            }
+        };
+        Some(WorkspaceSymbol {
+            name,
+            kind,
+            tags: None,
+            container_name: container.map(|m| m.borrow().name().to_string()),
+            location: OneOf::Left(location),
+            data: None,
+        })
+    }
+
+    /* Return a score if all chars of the query are found in order in the candidate. Matches at the start of the
+    candidate or of a segment ('.' or '_'), consecutive matches and short candidates are preferred */
+    pub fn fuzzy_score(query: &[char], candidate: impl Iterator<Item = char>) -> Option<i32> {
+        let mut score = 0;
+        let mut index = 0;
+        let mut consecutive = 0;
+        let mut length = 0;
+        let mut previous: Option<char> = None;
+        for c in candidate {
+            length += 1;
+            if index < query.len() && c.to_ascii_lowercase() == query[index] {
+                score += match previous {
+                    None => 10,
+                    Some('.') | Some('_') => 6,
+                    _ => 1,
+                };
+                score += 3 * consecutive.min(5);
+                consecutive += 1;
+                index += 1;
+            } else {
+                consecutive = 0;
+            }
+            previous = Some(c);
+        }
+        if index < query.len() {
+            return None;
+        }
+        if length == query.len() as i32 {
+            score += 50;
+        }
+        Some(score * 10 - length)
+    }
+}
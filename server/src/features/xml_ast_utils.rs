@@ -2,7 +2,7 @@ use std::{cell::RefCell, collections::HashMap, ops::Range, rc::Rc};
 
 use roxmltree::Node;
 
-use crate::{constants::OYarn, core::{evaluation::ContextValue, odoo::SyncOdoo, symbols::{module_symbol::ModuleSymbol, symbol::Symbol}, xml_data::OdooData}, threads::SessionInfo, Sy, S};
+use crate::{constants::OYarn, core::{evaluation::ContextValue, odoo::SyncOdoo, symbols::{module_symbol::ModuleSymbol, symbol::Symbol}, xml_data::{OdooData, XmlDataArchNode}, xml_id_usages::XmlIdUsages, xml_validation::XmlValidator}, threads::SessionInfo, Sy, S};
 
 pub enum XmlAstResult {
     SYMBOL(Rc<RefCell<Symbol>>),
@@ -42,6 +42,8 @@ impl XmlAstUtils {
 
     fn visit_node(session: &mut SessionInfo<'_>, node: &Node, offset: usize, from_module: Option<Rc<RefCell<Symbol>>>, ctxt: &mut HashMap<String, ContextValue>, results: &mut (Vec<XmlAstResult>, Option<Range<usize>>), on_dep_only: bool) {
         if node.is_element() {
+            XmlAstUtils::visit_qweb_attributes(session, node, offset, from_module.clone(), results, on_dep_only);
+            XmlAstUtils::visit_expression_xml_ids(session, node, offset, from_module.clone(), results, on_dep_only);
             match node.tag_name().name()  {
                 "record" => {
                     XmlAstUtils::visit_record(session, &node, offset, from_module.clone(), ctxt, results, on_dep_only);
@@ -88,10 +90,27 @@ impl XmlAstUtils {
                 }
             }
         }
+        let is_view = ctxt.get(&S!("record_model")).map(|m| m.as_string() == "ir.ui.view").unwrap_or(false);
+        if is_view {
+            if let Some(view_model) = XmlAstUtils::get_view_model(session, node, from_module.clone()) {
+                ctxt.insert(S!("view_model"), ContextValue::STRING(view_model));
+            }
+        }
         for child in node.children() {
             XmlAstUtils::visit_node(session, &child, offset, from_module.clone(), ctxt, results, on_dep_only);
         }
         ctxt.remove(&S!("record_model"));
+        ctxt.remove(&S!("view_model"));
+    }
+
+    /* Model of the view declared by the record node, from its 'model' field or from the inherited view */
+    fn get_view_model(session: &mut SessionInfo, node: &Node, from_module: Option<Rc<RefCell<Symbol>>>) -> Option<String> {
+        let fields = node.children().filter(|n| n.is_element() && n.tag_name().name() == "field").collect::<Vec<_>>();
+        if let Some(model) = fields.iter().find(|f| f.attribute("name") == Some("model")).and_then(|f| f.text()) {
+            return Some(model.trim().to_string());
+        }
+        let inherit_id = fields.iter().find(|f| f.attribute("name") == Some("inherit_id")).and_then(|f| f.attribute("ref"))?;
+        XmlValidator::get_inherited_view_model(session, &from_module?, inherit_id, 0).map(|model| model.to_string())
     }
 
     fn visit_field(session: &mut SessionInfo<'_>, node: &Node, offset: usize, from_module: Option<Rc<RefCell<Symbol>>>, ctxt: &mut HashMap<String, ContextValue>, results: &mut (Vec<XmlAstResult>, Option<Range<usize>>), on_dep_only: bool) {
@@ -126,12 +145,66 @@ impl XmlAstUtils {
                 }
             }
         }
+        let field_name = ctxt.get(&S!("field_name")).cloned().unwrap_or(ContextValue::STRING(S!(""))).as_string();
+        let view_model = ctxt.get(&S!("view_model")).cloned().unwrap_or(ContextValue::STRING(S!(""))).as_string();
+        if ["arch", "arch_db", "arch_base"].contains(&field_name.as_str()) && !view_model.is_empty() {
+            let main_symbol = session.sync_odoo.models.get(&Sy!(view_model)).cloned()
+                .and_then(|model| model.borrow().get_main_symbols(session, from_module.clone()).first().cloned());
+            for child in node.children() {
+                XmlAstUtils::visit_arch_node(session, &child, offset, from_module.clone(), main_symbol.clone(), results, on_dep_only);
+            }
+            ctxt.remove(&S!("field_name"));
+            return;
+        }
         for child in node.children() {
             XmlAstUtils::visit_node(session, &child, offset, from_module.clone(), ctxt, results, on_dep_only);
         }
         ctxt.remove(&S!("field_name"));
     }
 
+    /* Node of a view arch. Names of fields and buttons refer to main_symbol, that is the model of the view or of the current sub-view */
+    fn visit_arch_node(session: &mut SessionInfo<'_>, node: &Node, offset: usize, from_module: Option<Rc<RefCell<Symbol>>>, main_symbol: Option<Rc<RefCell<Symbol>>>, results: &mut (Vec<XmlAstResult>, Option<Range<usize>>), on_dep_only: bool) {
+        if !node.is_element() || node.range().start > offset || node.range().end < offset {
+            return;
+        }
+        XmlAstUtils::visit_qweb_attributes(session, node, offset, from_module.clone(), results, on_dep_only);
+        XmlAstUtils::visit_expression_xml_ids(session, node, offset, from_module.clone(), results, on_dep_only);
+        let lookup_module = match on_dep_only {
+            true => from_module.clone(),
+            false => None,
+        };
+        let mut children_symbol = main_symbol.clone();
+        let position = node.attribute("position");
+        match (node.tag_name().name(), node.attribute_node("name"), main_symbol.as_ref()) {
+            ("field", Some(attr), Some(symbol)) | ("groupby", Some(attr), Some(symbol)) => {
+                let fields = XmlValidator::get_model_members(session, lookup_module.clone(), symbol, attr.value(), false);
+                if position.is_none() || position == Some("inside") {
+                    children_symbol = XmlValidator::get_comodel_symbol(session, lookup_module, &fields);
+                }
+                if attr.range_value().start <= offset && attr.range_value().end >= offset {
+                    results.0.extend(fields.into_iter().map(|f| XmlAstResult::SYMBOL(f)));
+                    results.1 = Some(attr.range_value());
+                }
+            },
+            ("button", Some(attr), Some(symbol)) if node.attribute("type") == Some("object") => {
+                if attr.range_value().start <= offset && attr.range_value().end >= offset {
+                    let methods = XmlValidator::get_model_members(session, lookup_module, symbol, attr.value(), true);
+                    results.0.extend(methods.into_iter().map(|m| XmlAstResult::SYMBOL(m)));
+                    results.1 = Some(attr.range_value());
+                }
+            },
+            ("xpath", _, _) => {
+                if !XmlDataArchNode::xpath_keeps_model(node.attribute("expr").unwrap_or(""), position) {
+                    children_symbol = None;
+                }
+            },
+            _ => {}
+        }
+        for child in node.children() {
+            XmlAstUtils::visit_arch_node(session, &child, offset, from_module.clone(), children_symbol.clone(), results, on_dep_only);
+        }
+    }
+
     fn visit_text(session: &mut SessionInfo, node: &Node, offset: usize, from_module: Option<Rc<RefCell<Symbol>>>, ctxt: &mut HashMap<String, ContextValue>, results: &mut (Vec<XmlAstResult>, Option<Range<usize>>), on_dep_only: bool) {
         if node.range().start <= offset && node.range().end >= offset {
             let model = ctxt.get(&S!("record_model")).cloned().unwrap_or(ContextValue::STRING(S!(""))).as_string();
@@ -183,6 +256,33 @@ impl XmlAstUtils {
         }
     }
 
+    /* t-call and t-inherit can be used on any node of a template or a view arch */
+    fn visit_qweb_attributes(session: &mut SessionInfo<'_>, node: &Node, offset: usize, from_module: Option<Rc<RefCell<Symbol>>>, results: &mut (Vec<XmlAstResult>, Option<Range<usize>>), on_dep_only: bool) {
+        for attr in node.attributes() {
+            if (attr.name() == "t-call" || attr.name() == "t-inherit") && attr.range_value().start <= offset && attr.range_value().end >= offset {
+                XmlAstUtils::add_xml_id_result(session, attr.value(), &from_module.as_ref().unwrap(), attr.range_value(), results, on_dep_only);
+                results.1 = Some(attr.range_value());
+            }
+        }
+    }
+
+    /* ref('xml_id') in eval attributes and %(xml_id)d in the other attributes */
+    fn visit_expression_xml_ids(session: &mut SessionInfo<'_>, node: &Node, offset: usize, from_module: Option<Rc<RefCell<Symbol>>>, results: &mut (Vec<XmlAstResult>, Option<Range<usize>>), on_dep_only: bool) {
+        let source = node.document().input_text();
+        for attr in node.attributes() {
+            let range = attr.range_value();
+            if range.start > offset || range.end < offset {
+                continue;
+            }
+            let mut occurrences = vec![];
+            XmlIdUsages::push_expression(&mut occurrences, attr.name(), &source[range.clone()], range.start);
+            if let Some(occurrence) = occurrences.into_iter().find(|o| o.range.start <= offset && o.range.end >= offset) {
+                XmlAstUtils::add_xml_id_result(session, &occurrence.xml_id, &from_module.as_ref().unwrap(), occurrence.range.clone(), results, on_dep_only);
+                results.1 = Some(occurrence.range);
+            }
+        }
+    }
+
     fn add_model_result(session: &mut SessionInfo, node: &Node, from_module: Option<Rc<RefCell<Symbol>>>, results: &mut (Vec<XmlAstResult>, Option<Range<usize>>), on_dep_only: bool) {
         if let Some(model) = session.sync_odoo.models.get(node.text().unwrap()).cloned() {
             let from_module = match on_dep_only {
@@ -194,8 +294,8 @@ impl XmlAstUtils {
         }
     }
 
-    fn add_xml_id_result(session: &mut SessionInfo, xml_id: &str, file_symbol: &Rc<RefCell<Symbol>>, range: Range<usize>, results: &mut (Vec<XmlAstResult>, Option<Range<usize>>), on_dep_only: bool) {
-        let mut xml_ids = SyncOdoo::get_xml_ids(session, file_symbol, xml_id, &range, &mut vec![]);
+    pub(crate) fn add_xml_id_result(session: &mut SessionInfo, xml_id: &str, file_symbol: &Rc<RefCell<Symbol>>, range: Range<usize>, results: &mut (Vec<XmlAstResult>, Option<Range<usize>>), on_dep_only: bool) {
+        let mut xml_ids = SyncOdoo::get_xml_ids(session, file_symbol, xml_id, &range, &mut vec![]).unwrap_or_default();
         if on_dep_only {
             xml_ids = xml_ids.into_iter().filter(|x| 
                 {
@@ -217,6 +317,9 @@ impl XmlAstUtils {
                 OdooData::RECORD(r) => {
                     results.0.push(XmlAstResult::XML_DATA(r.file_symbol.upgrade().unwrap(), r.range.clone()));
                 },
+                OdooData::TEMPLATE(t) => {
+                    results.0.push(XmlAstResult::XML_DATA(t.file_symbol.upgrade().unwrap(), t.range.clone()));
+                },
                 _ => {}
             }
         }
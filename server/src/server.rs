@@ -1,17 +1,19 @@
-use std::{collections::HashMap, io::Error, panic, sync::{atomic::AtomicBool, Arc, Mutex}, thread::JoinHandle};
+use std::{collections::{HashMap, HashSet}, io::Error, panic, sync::{atomic::AtomicBool, Arc, Mutex}, thread::JoinHandle};
 
 use crossbeam_channel::{Receiver, Select, Sender};
 use lsp_server::{Connection, IoThreads, Message, ProtocolError, RequestId, ResponseError};
-use lsp_types::{notification::{DidChangeConfiguration, DidChangeTextDocument, DidChangeWatchedFiles, DidChangeWorkspaceFolders, DidCloseTextDocument,
-    DidCreateFiles, DidDeleteFiles, DidOpenTextDocument, DidRenameFiles, DidSaveTextDocument, Notification}, request::{Completion, DocumentSymbolRequest, GotoDefinition, HoverRequest, References, Request, ResolveCompletionItem, Shutdown}, CompletionOptions, DefinitionOptions, DocumentSymbolOptions, FileOperationFilter, FileOperationPattern, FileOperationRegistrationOptions, HoverProviderCapability, InitializeParams, InitializeResult, OneOf, ReferencesOptions, SaveOptions, ServerCapabilities, ServerInfo, TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions, WorkDoneProgressOptions, WorkspaceFileOperationsServerCapabilities, WorkspaceFoldersServerCapabilities, WorkspaceServerCapabilities};
+use lsp_types::{notification::{Cancel, DidChangeConfiguration, DidChangeTextDocument, DidChangeWatchedFiles, DidChangeWorkspaceFolders, DidCloseTextDocument,
+    DidCreateFiles, DidDeleteFiles, DidOpenTextDocument, DidRenameFiles, DidSaveTextDocument, Notification}, request::{CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls, CallHierarchyPrepare, CodeActionRequest, Completion, DocumentDiagnosticRequest, DocumentSymbolRequest, GotoDefinition, GotoImplementation, HoverRequest, InlayHintRequest, PrepareRenameRequest, References, Rename, Request, ResolveCompletionItem, SemanticTokensFullRequest, SemanticTokensRangeRequest, Shutdown, TypeHierarchyPrepare, TypeHierarchySubtypes, TypeHierarchySupertypes, WorkspaceDiagnosticRequest, WorkspaceSymbolRequest}, CallHierarchyServerCapability, CancelParams, CodeActionKind, CodeActionOptions, CodeActionProviderCapability, CompletionOptions, DefinitionOptions, DiagnosticOptions, DiagnosticServerCapabilities, DocumentSymbolOptions, FileOperationFilter, FileOperationPattern, FileOperationRegistrationOptions, HoverProviderCapability, ImplementationProviderCapability, InitializeParams, InitializeResult, OneOf, ReferencesOptions, RenameOptions, SaveOptions, SemanticTokensFullOptions, SemanticTokensOptions, SemanticTokensServerCapabilities, ServerCapabilities, ServerInfo, TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions, WorkDoneProgressOptions, NumberOrString, WorkspaceFileOperationsServerCapabilities, WorkspaceFoldersServerCapabilities, WorkspaceServerCapabilities};
 use serde_json::json;
 #[cfg(target_os = "linux")]
 use nix;
 use tracing::{error, info, warn};
 
-use crate::{constants::{DEBUG_THREADS, EXTENSION_VERSION}, core::{file_mgr::FileMgr, odoo::SyncOdoo}, threads::{delayed_changes_process_thread, message_processor_thread_main, DelayedProcessingMessage}, S};
+use crate::{constants::{DEBUG_THREADS, EXTENSION_VERSION}, core::{file_mgr::FileMgr, odoo::SyncOdoo}, features::semantic_tokens::SemanticTokensFeature, threads::{delayed_changes_process_thread, message_processor_thread_main, DelayedProcessingMessage}, S};
 
 
+const DELAYED_THREAD_INDEX: usize = 2; //index of the delayed process thread in the Select of Server::run (0 is the client, 1 the main thread)
+
 /**
  * Server handle connection between the client and the extension.
  * It can create a connection through io or tcp.
@@ -24,12 +26,16 @@ pub struct Server {
     msg_id: i32,
     main_thread: JoinHandle<()>,
     res_sender_s_to_main: Sender<Message>, // specific channel to threads, to handle responses (main -> s -> client and back)
+    res_sender_s_to_delayed: Sender<Message>, // same for the delayed process thread
+    pending_requests: HashMap<RequestId, usize>, //index of the thread that sent a request to the client, to route the response
     req_sender_s_to_main: Sender<Message>, //channel server to main threads. Will handle new request message (client -> s -> main and back)
     delayed_process_thread: JoinHandle<()>,
     sender_to_delayed_process: Sender<DelayedProcessingMessage>, //unique channel to delayed process thread
     sync_odoo: Arc<Mutex<SyncOdoo>>,
     interrupt_rebuild_boolean: Arc<AtomicBool>,
     terminate_rebuild_boolean: Arc<AtomicBool>,
+    cancelled_requests: Arc<Mutex<HashSet<RequestId>>>, //shared with SyncOdoo, checked by the main thread
+    requests_in_progress: HashSet<RequestId>, //client requests forwarded to the main thread, not answered yet
 }
 
 #[derive(Debug)]
@@ -73,6 +79,7 @@ impl Server {
         let sync_odoo = Arc::new(Mutex::new(SyncOdoo::new()));
         let interrupt_rebuild_boolean = sync_odoo.lock().unwrap().interrupt_rebuild.clone();
         let terminate_rebuild_boolean = sync_odoo.lock().unwrap().terminate_rebuild.clone();
+        let cancelled_requests = sync_odoo.lock().unwrap().cancelled_requests.clone();
         let mut receivers_w_to_s = vec![];
         let (sender_to_delayed_process, receiver_delayed_process) = crossbeam_channel::unbounded();
         let (req_sender_s_to_main, generic_receiver_s_to_main) = crossbeam_channel::unbounded(); //unique channel to dispatch to any ready main thread
@@ -88,7 +95,7 @@ impl Server {
             })
         };
 
-        let (_, receiver_s_to_delayed) = crossbeam_channel::unbounded();
+        let (res_sender_s_to_delayed, receiver_s_to_delayed) = crossbeam_channel::unbounded();
         let (sender_delayed_to_s, receiver_delayed_to_s) = crossbeam_channel::unbounded();
         receivers_w_to_s.push(receiver_delayed_to_s);
         let so = sync_odoo.clone();
@@ -104,11 +111,15 @@ impl Server {
             main_thread,
             req_sender_s_to_main,
             res_sender_s_to_main,
+            res_sender_s_to_delayed,
+            pending_requests: HashMap::new(),
             sender_to_delayed_process: sender_to_delayed_process,
             delayed_process_thread,
             sync_odoo: sync_odoo,
             interrupt_rebuild_boolean: interrupt_rebuild_boolean,
-            terminate_rebuild_boolean
+            terminate_rebuild_boolean,
+            cancelled_requests,
+            requests_in_progress: HashSet::new(),
         }
     }
 
@@ -169,12 +180,38 @@ impl Server {
                         work_done_progress: Some(false)
                     }
                 })),
+                implementation_provider: Some(ImplementationProviderCapability::Simple(true)),
+                call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+                inlay_hint_provider: Some(OneOf::Left(true)),
+                diagnostic_provider: Some(DiagnosticServerCapabilities::Options(DiagnosticOptions {
+                    identifier: Some(S!("odoo")),
+                    inter_file_dependencies: true,
+                    workspace_diagnostics: true,
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                })),
+                code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+                    code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+                    ..CodeActionOptions::default()
+                })),
+                semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
+                    legend: SemanticTokensFeature::legend(),
+                    range: Some(true),
+                    full: Some(SemanticTokensFullOptions::Bool(true)),
+                    ..SemanticTokensOptions::default()
+                })),
+                rename_provider: Some(OneOf::Right(RenameOptions {
+                    prepare_provider: Some(true),
+                    work_done_progress_options: WorkDoneProgressOptions {
+                        work_done_progress: Some(false)
+                    }
+                })),
                 document_symbol_provider: Some(OneOf::Right(DocumentSymbolOptions{
                     label: Some(S!("Odoo")),
                     work_done_progress_options: WorkDoneProgressOptions{
                         work_done_progress: Some(false)
                     },
                 })),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
                 workspace: Some(WorkspaceServerCapabilities {
                     workspace_folders: Some(WorkspaceFoldersServerCapabilities {
                         supported: Some(true),
@@ -222,7 +259,10 @@ impl Server {
             }
         };
 
-        self.connection.as_ref().unwrap().initialize_finish(id, serde_json::to_value(initialize_data).unwrap())?;
+        let mut initialize_data = serde_json::to_value(initialize_data).unwrap();
+        //typeHierarchyProvider is not part of lsp_types ServerCapabilities
+        initialize_data["capabilities"]["typeHierarchyProvider"] = json!(true);
+        self.connection.as_ref().unwrap().initialize_finish(id, initialize_data)?;
         let _ = self.connection.as_ref().unwrap().sender.send(Message::Notification(lsp_server::Notification {
             method: "$Odoo/setPid".to_string(),
             params: json!({
@@ -243,6 +283,7 @@ impl Server {
         });
         self.req_sender_s_to_main.send(shutdown_notification.clone()).unwrap();
         self.res_sender_s_to_main.send(shutdown_notification.clone()).unwrap();
+        let _ = self.res_sender_s_to_delayed.send(shutdown_notification.clone());
         info!(message);
     }
 
@@ -318,6 +359,7 @@ impl Server {
                     Message::Request(mut r) => {
                         r.id = RequestId::from(self.msg_id);
                         self.msg_id += 1;
+                        self.pending_requests.insert(r.id.clone(), index);
                         self.connection.as_ref().unwrap().sender.send(Message::Request(r)).unwrap();
                     },
                     Message::Notification(n) => {
@@ -328,6 +370,8 @@ impl Server {
                         self.connection.as_ref().unwrap().sender.send(Message::Notification(n)).unwrap();
                     },
                     Message::Response(r) => {
+                        self.requests_in_progress.remove(&r.id);
+                        self.cancelled_requests.lock().unwrap().remove(&r.id);
                         self.connection.as_ref().unwrap().sender.send(Message::Response(r)).unwrap();
                     }
                 }
@@ -353,11 +397,16 @@ impl Server {
         match msg {
             Message::Request(r) => {
                 match r.method.as_str() {
-                    HoverRequest::METHOD | GotoDefinition::METHOD | References::METHOD | DocumentSymbolRequest::METHOD=> {
+                    HoverRequest::METHOD | GotoDefinition::METHOD | GotoImplementation::METHOD | References::METHOD | DocumentSymbolRequest::METHOD | PrepareRenameRequest::METHOD | Rename::METHOD | WorkspaceSymbolRequest::METHOD | InlayHintRequest::METHOD |
+                    SemanticTokensFullRequest::METHOD | SemanticTokensRangeRequest::METHOD |
+                    TypeHierarchyPrepare::METHOD | TypeHierarchySupertypes::METHOD | TypeHierarchySubtypes::METHOD |
+                    CallHierarchyPrepare::METHOD | CallHierarchyIncomingCalls::METHOD | CallHierarchyOutgoingCalls::METHOD |
+                    CodeActionRequest::METHOD => {
                         self.interrupt_rebuild_boolean.store(true, std::sync::atomic::Ordering::SeqCst);
                         if DEBUG_THREADS {
                             info!("Sending request to main thread : {} - {}", r.method, r.id);
                         }
+                        self.requests_in_progress.insert(r.id.clone());
                         self.req_sender_s_to_main.send(Message::Request(r)).unwrap();
                     },
                     Completion::METHOD => {
@@ -365,6 +414,15 @@ impl Server {
                         if DEBUG_THREADS {
                             info!("Sending request to main thread : {} - {}", r.method, r.id);
                         }
+                        self.requests_in_progress.insert(r.id.clone());
+                        self.req_sender_s_to_main.send(Message::Request(r)).unwrap();
+                    },
+                    DocumentDiagnosticRequest::METHOD | WorkspaceDiagnosticRequest::METHOD => {
+                        //the client pulls diagnostics regularly, it should not interrupt the rebuilds
+                        if DEBUG_THREADS {
+                            info!("Sending request to main thread : {} - {}", r.method, r.id);
+                        }
+                        self.requests_in_progress.insert(r.id.clone());
                         self.req_sender_s_to_main.send(Message::Request(r)).unwrap();
                     },
                     ResolveCompletionItem::METHOD => {
@@ -374,8 +432,13 @@ impl Server {
                 }
             },
             Message::Response(r) => {
-                info!("Sending response to main thread : {}", r.id);
-                self.res_sender_s_to_main.send(Message::Response(r)).unwrap();
+                if self.pending_requests.remove(&r.id) == Some(DELAYED_THREAD_INDEX) {
+                    info!("Sending response to delayed process thread : {}", r.id);
+                    self.res_sender_s_to_delayed.send(Message::Response(r)).unwrap();
+                } else {
+                    info!("Sending response to main thread : {}", r.id);
+                    self.res_sender_s_to_main.send(Message::Response(r)).unwrap();
+                }
             },
             Message::Notification(n) => {
                 match n.method.as_str() {
@@ -388,6 +451,19 @@ impl Server {
                         self.interrupt_rebuild_boolean.store(true, std::sync::atomic::Ordering::SeqCst);
                         self.req_sender_s_to_main.send(Message::Notification(n)).unwrap();
                     }
+                    Cancel::METHOD => {
+                        //malformed cancellations are ignored
+                        if let Ok(params) = serde_json::from_value::<CancelParams>(n.params) {
+                            let id = match params.id {
+                                NumberOrString::Number(id) => RequestId::from(id),
+                                NumberOrString::String(id) => RequestId::from(id),
+                            };
+                            //the main thread will answer with RequestCancelled instead of processing it, or as soon as it checks the flag
+                            if self.requests_in_progress.contains(&id) {
+                                self.cancelled_requests.lock().unwrap().insert(id);
+                            }
+                        }
+                    },
                     method if method.starts_with("$/") => warn!("Not handled message id: {}", n.method),
                     method => error!("Not handled Notification Id: {}", method),
                 }
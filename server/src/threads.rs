@@ -1,16 +1,31 @@
-use std::{collections::VecDeque, path::PathBuf, sync::{atomic::Ordering, Arc, Mutex}, time::Instant};
+use std::{collections::VecDeque, path::PathBuf, sync::{atomic::{AtomicU32, Ordering}, Arc, Mutex}, time::Instant};
 
 use crossbeam_channel::{Receiver, Sender, TryRecvError};
-use lsp_server::{Message, RequestId, Response, ResponseError};
+use lsp_server::{ErrorCode, Message, RequestId, Response, ResponseError};
 use lsp_types::{notification::{DidChangeConfiguration, DidChangeTextDocument, DidChangeWatchedFiles, DidChangeWorkspaceFolders,
     DidCloseTextDocument, DidCreateFiles, DidDeleteFiles, DidOpenTextDocument, DidRenameFiles, DidSaveTextDocument, LogMessage,
-    Notification, ShowMessage}, request::{Completion, DocumentSymbolRequest, GotoDefinition, GotoTypeDefinitionResponse, HoverRequest, References, Request, Shutdown}, CompletionResponse, DocumentSymbolResponse, Hover, Location, LogMessageParams, MessageType, ShowMessageParams};
+    Notification, Progress, ShowMessage}, request::{CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls, CallHierarchyPrepare, CodeActionRequest, Completion, DocumentDiagnosticRequest, DocumentSymbolRequest, GotoDefinition, GotoImplementation, GotoImplementationResponse, GotoTypeDefinitionResponse, HoverRequest, InlayHintRequest, PrepareRenameRequest, References, Rename, Request, SemanticTokensFullRequest, SemanticTokensRangeRequest, Shutdown, TypeHierarchyPrepare, TypeHierarchySubtypes, TypeHierarchySupertypes, WorkDoneProgressCreate, WorkspaceDiagnosticRequest, WorkspaceSymbolRequest}, CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall, CodeActionResponse, CompletionResponse, DocumentDiagnosticReportResult, DocumentSymbolResponse, Hover, InlayHint, Location, PrepareRenameResponse, SemanticTokensRangeResult, SemanticTokensResult, TypeHierarchyItem, WorkspaceDiagnosticReportResult, WorkspaceEdit, WorkspaceSymbolResponse, LogMessageParams, MessageType, NumberOrString, ProgressParams, ProgressParamsValue, ShowMessageParams,
+    WorkDoneProgress, WorkDoneProgressBegin, WorkDoneProgressCreateParams, WorkDoneProgressEnd, WorkDoneProgressReport};
 use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
 use tracing::{error, info, warn};
 
 use crate::{core::{config::RefreshMode, file_mgr::NoqaInfo, odoo::{Odoo, SyncOdoo}}, server::ServerError, utils::PathSanitizer, S};
 
+static PROGRESS_COUNTER: AtomicU32 = AtomicU32::new(0);
+/* Minimum delay between two reports of a progress */
+const PROGRESS_REPORT_DELAY_MS: u128 = 200;
+/* Rebuilds outside of the initialization get a progress only if their queue reaches this size. A single file save
+rebuilds a few symbols in a fraction of a second, and a progress would only blink in the client */
+pub const REBUILD_PROGRESS_MIN_QUEUE_SIZE: usize = 10;
+
+/* Work done progress currently displayed by the client */
+pub struct ProgressInfo {
+    token: NumberOrString,
+    last_report: Instant,
+    max_queue_size: usize, //biggest rebuild queue since the start of the stage, to compute the percentage
+}
+
 pub struct SessionInfo<'a> {
     sender: Sender<Message>,
     receiver: Receiver<Message>,
@@ -90,6 +105,92 @@ impl <'a> SessionInfo<'a> {
         }
     }
 
+    /* Create a work done progress in the client. Return false if the client does not support it or if a progress is
+    already running, in which case the caller should only report on it */
+    pub fn start_progress(&mut self, title: &str) -> bool {
+        if self.sync_odoo.progress.is_some() {
+            return false;
+        }
+        let supported = self.sync_odoo.capabilities.window.as_ref().and_then(|window| window.work_done_progress).unwrap_or(false);
+        if !supported {
+            return false;
+        }
+        let token = NumberOrString::String(format!("odoo/{}", PROGRESS_COUNTER.fetch_add(1, Ordering::SeqCst)));
+        //Blocking on purpose, while the SyncOdoo lock is held: the token can't be used before the client acknowledged it,
+        //and the response is read from the same channel as the other responses. Clients answer it without user interaction
+        if let Err(e) = self.send_request::<WorkDoneProgressCreateParams, ()>(WorkDoneProgressCreate::METHOD, WorkDoneProgressCreateParams { token: token.clone() }) {
+            warn!("Unable to create a progress: {:?}", e);
+            return false;
+        }
+        self.send_notification(Progress::METHOD, ProgressParams {
+            token: token.clone(),
+            value: ProgressParamsValue::WorkDone(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                title: title.to_string(),
+                cancellable: Some(false),
+                message: None,
+                percentage: Some(0),
+            })),
+        });
+        self.sync_odoo.progress = Some(ProgressInfo { token, last_report: Instant::now(), max_queue_size: 0 });
+        true
+    }
+
+    /* Report a new stage of the progress. The percentage restarts from the size of the rebuild queue */
+    pub fn report_progress_stage(&mut self, message: &str) {
+        if let Some(progress) = self.sync_odoo.progress.as_mut() {
+            progress.max_queue_size = 0;
+        }
+        self.report_progress(message.to_string(), true);
+    }
+
+    /* Report the progress with the size of the rebuild queues. Reports are throttled unless forced */
+    pub fn report_progress(&mut self, message: String, force: bool) {
+        let queue_size = self.sync_odoo.get_rebuild_queue_size();
+        let Some(progress) = self.sync_odoo.progress.as_mut() else {
+            return;
+        };
+        if !force && progress.last_report.elapsed().as_millis() < PROGRESS_REPORT_DELAY_MS {
+            return;
+        }
+        progress.last_report = Instant::now();
+        progress.max_queue_size = std::cmp::max(progress.max_queue_size, queue_size);
+        let percentage = match progress.max_queue_size {
+            0 => None,
+            max => Some((100 * (max - queue_size) / max) as u32),
+        };
+        let token = progress.token.clone();
+        self.send_notification(Progress::METHOD, ProgressParams {
+            token,
+            value: ProgressParamsValue::WorkDone(WorkDoneProgress::Report(WorkDoneProgressReport {
+                cancellable: Some(false),
+                message: Some(message),
+                percentage,
+            })),
+        });
+    }
+
+    /* Return true if the client cancelled the request being processed. Long features should check it to stop early */
+    pub fn is_request_cancelled(&self) -> bool {
+        match self.sync_odoo.current_request.as_ref() {
+            Some(id) => self.sync_odoo.cancelled_requests.lock().unwrap().contains(id),
+            None => false,
+        }
+    }
+
+    pub fn is_progress_report_due(&self) -> bool {
+        self.sync_odoo.progress.as_ref().map(|progress| progress.last_report.elapsed().as_millis() >= PROGRESS_REPORT_DELAY_MS).unwrap_or(false)
+    }
+
+    pub fn end_progress(&mut self) {
+        let Some(progress) = self.sync_odoo.progress.take() else {
+            return;
+        };
+        self.send_notification(Progress::METHOD, ProgressParams {
+            token: progress.token,
+            value: ProgressParamsValue::WorkDone(WorkDoneProgress::End(WorkDoneProgressEnd { message: None })),
+        });
+    }
+
     /*
     * Request an update of the file in the index.
     * path: path of the file
@@ -326,6 +427,100 @@ pub fn delayed_changes_process_thread(sender_session: Sender<Message>, receiver_
     }
 }
 
+/* Return true if the request targets a document that is modified by a notification waiting in the buffer.
+The answer would be computed on an outdated version of the document, and the client will ask again anyway */
+pub fn is_superseded_request(request: &lsp_server::Request, buffer: &VecDeque<Message>) -> bool {
+    if !matches!(request.method.as_str(), HoverRequest::METHOD | Completion::METHOD | InlayHintRequest::METHOD |
+        SemanticTokensFullRequest::METHOD | SemanticTokensRangeRequest::METHOD | CodeActionRequest::METHOD) {
+        return false;
+    }
+    let Some(uri) = request.params.get("textDocument").and_then(|doc| doc.get("uri")) else {
+        return false;
+    };
+    buffer.iter().any(|msg| matches!(msg, Message::Notification(n) if n.method == DidChangeTextDocument::METHOD
+        && n.params.get("textDocument").and_then(|doc| doc.get("uri")) == Some(uri)))
+}
+
+/* Process a request on the main thread and build its response */
+fn handle_request(session: &mut SessionInfo, r: lsp_server::Request) -> Response {
+    session.sync_odoo.current_request = Some(r.id.clone());
+    let (value, error) = match r.method.as_str() {
+        HoverRequest::METHOD => {
+            to_value::<Hover>(Odoo::handle_hover(session, serde_json::from_value(r.params).unwrap()))
+        },
+        GotoDefinition::METHOD => {
+            to_value::<GotoTypeDefinitionResponse>(Odoo::handle_goto_definition(session, serde_json::from_value(r.params).unwrap()))
+        },
+        GotoImplementation::METHOD => {
+            to_value::<GotoImplementationResponse>(Odoo::handle_implementation(session, serde_json::from_value(r.params).unwrap()))
+        },
+        DocumentDiagnosticRequest::METHOD => {
+            to_value::<DocumentDiagnosticReportResult>(Odoo::handle_document_diagnostic(session, serde_json::from_value(r.params).unwrap()))
+        },
+        WorkspaceDiagnosticRequest::METHOD => {
+            to_value::<WorkspaceDiagnosticReportResult>(Odoo::handle_workspace_diagnostic(session, serde_json::from_value(r.params).unwrap()))
+        },
+        CodeActionRequest::METHOD => {
+            to_value::<CodeActionResponse>(Odoo::handle_code_action(session, serde_json::from_value(r.params).unwrap()))
+        },
+        InlayHintRequest::METHOD => {
+            to_value::<Vec<InlayHint>>(Odoo::handle_inlay_hints(session, serde_json::from_value(r.params).unwrap()))
+        },
+        SemanticTokensFullRequest::METHOD => {
+            to_value::<SemanticTokensResult>(Odoo::handle_semantic_tokens_full(session, serde_json::from_value(r.params).unwrap()))
+        },
+        SemanticTokensRangeRequest::METHOD => {
+            to_value::<SemanticTokensRangeResult>(Odoo::handle_semantic_tokens_range(session, serde_json::from_value(r.params).unwrap()))
+        },
+        CallHierarchyPrepare::METHOD => {
+            to_value::<Vec<CallHierarchyItem>>(Odoo::handle_prepare_call_hierarchy(session, serde_json::from_value(r.params).unwrap()))
+        },
+        CallHierarchyIncomingCalls::METHOD => {
+            to_value::<Vec<CallHierarchyIncomingCall>>(Odoo::handle_call_hierarchy_incoming_calls(session, serde_json::from_value(r.params).unwrap()))
+        },
+        CallHierarchyOutgoingCalls::METHOD => {
+            to_value::<Vec<CallHierarchyOutgoingCall>>(Odoo::handle_call_hierarchy_outgoing_calls(session, serde_json::from_value(r.params).unwrap()))
+        },
+        TypeHierarchyPrepare::METHOD => {
+            to_value::<Vec<TypeHierarchyItem>>(Odoo::handle_prepare_type_hierarchy(session, serde_json::from_value(r.params).unwrap()))
+        },
+        TypeHierarchySupertypes::METHOD => {
+            to_value::<Vec<TypeHierarchyItem>>(Odoo::handle_type_hierarchy_supertypes(session, serde_json::from_value(r.params).unwrap()))
+        },
+        TypeHierarchySubtypes::METHOD => {
+            to_value::<Vec<TypeHierarchyItem>>(Odoo::handle_type_hierarchy_subtypes(session, serde_json::from_value(r.params).unwrap()))
+        },
+        References::METHOD => {
+            to_value::<Vec<Location>>(Odoo::handle_references(session, serde_json::from_value(r.params).unwrap()))
+        },
+        PrepareRenameRequest::METHOD => {
+            to_value::<PrepareRenameResponse>(Odoo::handle_prepare_rename(session, serde_json::from_value(r.params).unwrap()))
+        },
+        Rename::METHOD => {
+            to_value::<WorkspaceEdit>(Odoo::handle_rename(session, serde_json::from_value(r.params).unwrap()))
+        },
+        DocumentSymbolRequest::METHOD => {
+            to_value::<DocumentSymbolResponse>(Odoo::handle_document_symbols(session, serde_json::from_value(r.params).unwrap()))
+        },
+        WorkspaceSymbolRequest::METHOD => {
+            to_value::<WorkspaceSymbolResponse>(Odoo::handle_workspace_symbols(session, serde_json::from_value(r.params).unwrap()))
+        },
+        Completion::METHOD => {
+            to_value::<CompletionResponse>(Odoo::handle_autocomplete(session, serde_json::from_value(r.params).unwrap()))
+        },
+        _ => {error!("Request not handled by main thread: {}", r.method); (None, Some(ResponseError{
+            code: 1,
+            message: S!("Request not handled by the server"),
+            data: None
+        }))}
+    };
+    session.sync_odoo.current_request = None;
+    if session.sync_odoo.cancelled_requests.lock().unwrap().contains(&r.id) {
+        return Response::new_err(r.id, ErrorCode::RequestCanceled as i32, S!("Request cancelled"));
+    }
+    Response { id: r.id, result: value, error: error }
+}
+
 pub fn message_processor_thread_main(sync_odoo: Arc<Mutex<SyncOdoo>>, generic_receiver: Receiver<Message>, sender: Sender<Message>, receiver: Receiver<Message>, delayed_process_sender: Sender<DelayedProcessingMessage>) {
     let mut buffer = VecDeque::new();
     loop {
@@ -377,29 +572,15 @@ pub fn message_processor_thread_main(sync_odoo: Arc<Mutex<SyncOdoo>>, generic_re
             };
             match msg {
                 Message::Request(r) => {
-                    let (value, error) = match r.method.as_str() {
-                        HoverRequest::METHOD => {
-                            to_value::<Hover>(Odoo::handle_hover(&mut session, serde_json::from_value(r.params).unwrap()))
-                        },
-                        GotoDefinition::METHOD => {
-                            to_value::<GotoTypeDefinitionResponse>(Odoo::handle_goto_definition(&mut session, serde_json::from_value(r.params).unwrap()))
-                        },
-                        References::METHOD => {
-                            to_value::<Vec<Location>>(Odoo::handle_references(&mut session, serde_json::from_value(r.params).unwrap()))
-                        },
-                        DocumentSymbolRequest::METHOD => {
-                            to_value::<DocumentSymbolResponse>(Odoo::handle_document_symbols(&mut session, serde_json::from_value(r.params).unwrap()))
-                        },
-                        Completion::METHOD => {
-                            to_value::<CompletionResponse>(Odoo::handle_autocomplete(&mut session, serde_json::from_value(r.params).unwrap()))
-                        },
-                        _ => {error!("Request not handled by main thread: {}", r.method); (None, Some(ResponseError{
-                            code: 1,
-                            message: S!("Request not handled by the server"),
-                            data: None
-                        }))}
+                    //cancelled or superseded requests are answered without being processed
+                    let response = if session.sync_odoo.cancelled_requests.lock().unwrap().contains(&r.id) {
+                        Response::new_err(r.id, ErrorCode::RequestCanceled as i32, S!("Request cancelled"))
+                    } else if is_superseded_request(&r, &buffer) {
+                        Response::new_err(r.id, ErrorCode::ContentModified as i32, S!("Document changed since the request"))
+                    } else {
+                        handle_request(&mut session, r)
                     };
-                    sender.send(Message::Response(Response { id: r.id, result: value, error: error })).unwrap();
+                    sender.send(Message::Response(response)).unwrap();
                 },
                 Message::Notification(n) => {
                     match n.method.as_str() {
@@ -424,6 +605,7 @@ pub fn message_processor_thread_main(sync_odoo: Arc<Mutex<SyncOdoo>>, generic_re
                     return;
                 }
             }
+            Odoo::refresh_diagnostics(&mut session);
         }
     }
 }
@@ -1832,4 +1832,37 @@ fn test_base_and_version_resolve_for_workspace_subpaths() {
     ws_folders.insert(S!("ws_invalid"), vdir.path().sanitize().to_string());
     let result = get_configuration(&ws_folders, &None);
     assert!(result.is_err(), "Expected error when $base is not a valid path");
-}
\ No newline at end of file
+}
+#[test]
+fn test_inlay_hints_categories() {
+    let temp = TempDir::new().unwrap();
+    let ws_folder = temp.child("workspace1");
+    ws_folder.create_dir_all().unwrap();
+
+    // Categories are enabled by default
+    let mut ws_folders = HashMap::new();
+    ws_folders.insert(S!("ws1"), ws_folder.path().sanitize().to_string());
+    let (config_map, _config_file) = get_configuration(&ws_folders, &None).unwrap();
+    let config = config_map.get("default").unwrap();
+    assert_eq!(config.inlay_hints_variables, true);
+    assert_eq!(config.inlay_hints_loop_variables, true);
+
+    // Each category can be disabled independently, and is inherited through extends
+    let ws_toml = r#"
+        [[config]]
+        name = "base"
+        inlay_hints_loop_variables = false
+
+        [[config]]
+        name = "default"
+        extends = "base"
+        inlay_hints_variables = false
+    "#;
+    ws_folder.child("odools.toml").write_str(ws_toml).unwrap();
+    let (config_map, _config_file) = get_configuration(&ws_folders, &None).unwrap();
+    let config = config_map.get("default").unwrap();
+    assert_eq!(config.inlay_hints_variables, false);
+    assert_eq!(config.inlay_hints_loop_variables, false);
+    let base = config_map.get("base").unwrap();
+    assert_eq!(base.inlay_hints_variables, true);
+}
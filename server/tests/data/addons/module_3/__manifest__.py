@@ -0,0 +1,24 @@
+# -*- coding: utf-8 -*-
+# Part of Odoo. See LICENSE file for full copyright and licensing details.
+{
+    'name' : 'Module 3',
+    'version' : '1.0',
+    'summary': 'Test Module 3',
+    'sequence': 10,
+    'description': """
+Module 3
+====================
+This is the description of the module 3, with data files to validate
+    """,
+    'category': 'Accounting/Accounting',
+    'depends' : ["base"],
+    'data': [
+        'data/menus.xml',
+        'data/records.xml',
+        'data/pygls.tests.xml_test_model.csv',
+        'data/views.xml',
+    ],
+    'installable': True,
+    'application': True,
+    'license': 'LGPL-3',
+}
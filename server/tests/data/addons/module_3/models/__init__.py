@@ -0,0 +1 @@
+from . import xml_test_models
\ No newline at end of file
@@ -0,0 +1,15 @@
+from odoo import fields, models
+
+class XmlTestModel(models.Model):
+    _name = "pygls.tests.xml_test_model"
+    _description = "Xml Test Model"
+
+    name = fields.Char(required=True)
+    code = fields.Char(required=True, default="code")
+    partner_id = fields.Many2one("res.partner")
+    sequence = fields.Integer()
+    amount = fields.Float()
+    parent_id = fields.Many2one("pygls.tests.xml_test_model")
+
+    def _get_complete_record(self):
+        return self.env.ref("module_3.record_complete")
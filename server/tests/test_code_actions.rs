@@ -0,0 +1,67 @@
+use odoo_ls_server::features::code_actions::CodeActionFeature;
+use odoo_ls_server::oyarn;
+use ruff_python_ast::{Expr, ExprDict, Stmt};
+use ruff_text_size::{TextRange, TextSize};
+
+fn parse_manifest(source: &str) -> ExprDict {
+    let parsed = ruff_python_parser::parse_module(source).unwrap();
+    let Some(Stmt::Expr(expr)) = parsed.into_syntax().body.into_iter().next() else {
+        panic!("manifest is not an expression");
+    };
+    let Expr::Dict(dict) = *expr.value else {
+        panic!("manifest is not a dict");
+    };
+    dict
+}
+
+/* Source of the manifest once 'sale' is added to its depends */
+fn add_sale(source: &str) -> Option<String> {
+    let dict = parse_manifest(source);
+    let depends = dict.items.iter().find(|item| matches!(&item.key, Some(Expr::StringLiteral(key)) if key.value.to_str() == "depends"));
+    let (offset, text) = match depends.map(|item| &item.value) {
+        Some(Expr::List(list)) => CodeActionFeature::insert_in_list(source, list, &oyarn!("sale"))?,
+        _ => CodeActionFeature::insert_depends_key(source, &dict, &oyarn!("sale")),
+    };
+    let mut result = source.to_string();
+    result.insert_str(offset.to_usize(), &text);
+    Some(result)
+}
+
+#[test]
+fn test_add_depends_in_list() {
+    assert_eq!(add_sale("{'name': 'M', 'depends': ['base']}").unwrap(), "{'name': 'M', 'depends': ['base', 'sale']}");
+    // Quotes of the existing list are followed
+    assert_eq!(add_sale("{\"depends\": [\"base\"]}").unwrap(), "{\"depends\": [\"base\", \"sale\"]}");
+    assert_eq!(add_sale("{'depends': []}").unwrap(), "{'depends': ['sale']}");
+    // Nothing to do if the module is already there
+    assert!(add_sale("{'depends': ['base', 'sale']}").is_none());
+}
+
+#[test]
+fn test_add_depends_in_multiline_list() {
+    let source = "{\n    'depends': [\n        'base',\n        'mail',\n    ],\n}";
+    assert_eq!(add_sale(source).unwrap(), "{\n    'depends': [\n        'base',\n        'mail',\n        'sale',\n    ],\n}");
+    let source = "{\n    'depends': [\n        'base',\n        'mail'\n    ],\n}";
+    assert_eq!(add_sale(source).unwrap(), "{\n    'depends': [\n        'base',\n        'mail',\n        'sale'\n    ],\n}");
+}
+
+#[test]
+fn test_add_depends_key() {
+    assert_eq!(add_sale("{'name': 'M'}").unwrap(), "{'depends': ['sale'], 'name': 'M'}");
+    assert_eq!(add_sale("{\n    'name': 'M',\n}").unwrap(), "{\n    'depends': ['sale'],\n    'name': 'M',\n}");
+}
+
+fn merge_noqa(text: &str, code: &str) -> Option<(u32, String)> {
+    let range = TextRange::at(TextSize::new(10), TextSize::of(text));
+    CodeActionFeature::merge_noqa_code(text, range, &code.to_string()).map(|(offset, text)| (offset.to_u32(), text))
+}
+
+#[test]
+fn test_merge_noqa_code() {
+    assert_eq!(merge_noqa("# noqa: OLS01000", "OLS02000"), Some((26, ", OLS02000".to_string())));
+    assert_eq!(merge_noqa("# noqa: OLS01000, OLS01001  ", "OLS02000"), Some((36, ", OLS02000".to_string())));
+    // Already suppressed by the comment
+    assert_eq!(merge_noqa("# noqa: OLS01000, OLS02000", "OLS02000"), None);
+    // A noqa without codes suppresses everything
+    assert_eq!(merge_noqa("# noqa", "OLS02000"), None);
+}
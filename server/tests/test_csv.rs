@@ -0,0 +1,18 @@
+use odoo_ls_server::core::csv_arch_builder::CsvArchBuilder;
+
+#[test]
+fn test_csv_value_ranges() {
+    let content = "id,name,value\nrecord_1,\"Quoted, name\", 12 \nrecord_2,\"say \"\"hi\"\"\",3\n";
+    let mut rdr = csv::Reader::from_reader(content.as_bytes());
+    let records = rdr.records().map(|r| r.unwrap()).collect::<Vec<_>>();
+    assert_eq!(records.len(), 2);
+
+    // Quoted and padded values are located in the source
+    let ranges = CsvArchBuilder::get_value_ranges(content, &records[0]);
+    assert_eq!(ranges, vec![14..22, 24..36, 38..42]);
+    assert_eq!(&content[ranges[1].clone()], "Quoted, name");
+
+    // A value with escaped quotes is not found as is, and gets an empty range without moving the cursor
+    let ranges = CsvArchBuilder::get_value_ranges(content, &records[1]);
+    assert_eq!(ranges, vec![43..51, 51..51, 65..66]);
+}
@@ -0,0 +1,93 @@
+// Test the rename of model fields and xml_ids across the python, xml and csv files of module_3 in the test addons.
+
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+
+use lsp_types::{Position, RenameParams, TextDocumentIdentifier, TextDocumentPositionParams, WorkDoneProgressParams};
+use odoo_ls_server::core::file_mgr::FileMgr;
+use odoo_ls_server::core::odoo::Odoo;
+use odoo_ls_server::threads::SessionInfo;
+use odoo_ls_server::utils::PathSanitizer;
+
+mod setup;
+
+fn module_3_path(parts: &[&str]) -> String {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("data").join("addons").join("module_3");
+    for part in parts {
+        path = path.join(part);
+    }
+    path.sanitize()
+}
+
+/* Edits of a rename requested at the given position, as (line, start character, end character) by file path */
+fn rename(session: &mut SessionInfo, path: &String, line: u32, character: u32, new_name: &str) -> HashMap<String, Vec<(u32, u32, u32)>> {
+    let params = RenameParams {
+        text_document_position: TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: FileMgr::pathname2uri(path) },
+            position: Position::new(line, character),
+        },
+        new_name: new_name.to_string(),
+        work_done_progress_params: WorkDoneProgressParams::default(),
+    };
+    let edit = Odoo::handle_rename(session, params).unwrap().expect("no edit for the rename");
+    edit.changes.unwrap_or_default().into_iter().map(|(uri, edits)| {
+        assert!(edits.iter().all(|e| e.new_text == new_name));
+        let ranges = edits.iter().map(|e| (e.range.start.line, e.range.start.character, e.range.end.character)).collect();
+        (FileMgr::uri2pathname(uri.as_str()), ranges)
+    }).collect()
+}
+
+#[test]
+fn test_rename_field() {
+    let mut odoo = setup::setup::setup_server(true);
+    let mut session = setup::setup::create_session(&mut odoo);
+    let py_path = module_3_path(&["models", "xml_test_models.py"]);
+    let records_path = module_3_path(&["data", "records.xml"]);
+    let views_path = module_3_path(&["data", "views.xml"]);
+    let csv_path = module_3_path(&["data", "pygls.tests.xml_test_model.csv"]);
+
+    // From the csv header
+    let edits = rename(&mut session, &csv_path, 0, 5, "label");
+    assert!(edits[&py_path].contains(&(6, 4, 8)), "{:?}", edits);
+    assert!(edits[&records_path].contains(&(3, 21, 25)), "{:?}", edits);
+    assert!(edits[&views_path].contains(&(14, 21, 25)), "{:?}", edits);
+    // the name of the ir.ui.view record is another field
+    assert!(!edits[&views_path].iter().any(|(line, _, _)| *line == 3), "{:?}", edits);
+    assert_eq!(edits[&csv_path], vec![(0, 3, 7)]);
+
+    // From the python declaration
+    let edits = rename(&mut session, &py_path, 6, 5, "label");
+    assert!(edits[&records_path].contains(&(3, 21, 25)), "{:?}", edits);
+    assert_eq!(edits[&csv_path], vec![(0, 3, 7)]);
+
+    // Only the field part of "field/id" and "field:id" headers is renamed
+    let edits = rename(&mut session, &csv_path, 0, 15, "company_partner_id");
+    assert!(edits[&py_path].contains(&(8, 4, 14)), "{:?}", edits);
+    assert_eq!(edits[&csv_path], vec![(0, 13, 23)]);
+    let edits = rename(&mut session, &csv_path, 0, 29, "parent_record_id");
+    assert!(edits[&py_path].contains(&(11, 4, 13)), "{:?}", edits);
+    assert_eq!(edits[&csv_path], vec![(0, 27, 36)]);
+}
+
+#[test]
+fn test_rename_xml_id() {
+    let mut odoo = setup::setup::setup_server(true);
+    let mut session = setup::setup::create_session(&mut odoo);
+    let py_path = module_3_path(&["models", "xml_test_models.py"]);
+    let records_path = module_3_path(&["data", "records.xml"]);
+    let csv_path = module_3_path(&["data", "pygls.tests.xml_test_model.csv"]);
+
+    // Declarations, ref="module_3.id", ref('id') in an eval, a ":id" csv cell and env.ref("module_3.id") in python.
+    // Only the id part is replaced, the "module_3." prefix is kept
+    for (path, line, character) in [(&records_path, 2, 20), (&py_path, 14, 40)] {
+        let mut edits = rename(&mut session, path, line, character, "record_main");
+        for ranges in edits.values_mut() {
+            ranges.sort();
+        }
+        assert_eq!(edits[&records_path], vec![(2, 16, 31), (8, 16, 31), (23, 46, 61), (24, 42, 57)], "{:?}", edits);
+        assert_eq!(edits[&csv_path], vec![(1, 50, 65)], "{:?}", edits);
+        assert_eq!(edits[&py_path], vec![(14, 38, 53)], "{:?}", edits);
+        assert_eq!(edits.len(), 3, "{:?}", edits);
+    }
+}
@@ -0,0 +1,26 @@
+use std::collections::VecDeque;
+
+use lsp_server::{Message, Notification, Request, RequestId};
+use odoo_ls_server::threads::is_superseded_request;
+use odoo_ls_server::S;
+use serde_json::json;
+
+fn did_change(uri: &str) -> Message {
+    Message::Notification(Notification::new(S!("textDocument/didChange"), json!({"textDocument": {"uri": uri, "version": 2}, "contentChanges": []})))
+}
+
+fn request(method: &str, uri: &str) -> Request {
+    Request::new(RequestId::from(1), S!(method), json!({"textDocument": {"uri": uri}, "position": {"line": 0, "character": 0}}))
+}
+
+#[test]
+fn test_superseded_request() {
+    let buffer = VecDeque::from([did_change("file:///a.py")]);
+    assert!(is_superseded_request(&request("textDocument/hover", "file:///a.py"), &buffer));
+    assert!(is_superseded_request(&request("textDocument/completion", "file:///a.py"), &buffer));
+    // Another document is modified
+    assert!(!is_superseded_request(&request("textDocument/hover", "file:///b.py"), &buffer));
+    // Requests that are not asked again by the client are always answered
+    assert!(!is_superseded_request(&request("textDocument/definition", "file:///a.py"), &buffer));
+    assert!(!is_superseded_request(&request("textDocument/hover", "file:///a.py"), &VecDeque::new()));
+}
@@ -0,0 +1,28 @@
+use odoo_ls_server::features::workspace_symbols::WorkspaceSymbolFeature;
+
+fn score(query: &str, candidate: &str) -> Option<i32> {
+    let query = query.chars().collect::<Vec<char>>();
+    WorkspaceSymbolFeature::fuzzy_score(&query, candidate.chars())
+}
+
+#[test]
+fn test_fuzzy_score_matching() {
+    // All chars of the query have to be found in order, case insensitively
+    assert!(score("prt", "res.partner").is_some());
+    assert!(score("rp", "ResPartner").is_some());
+    assert!(score("tpr", "res.partner").is_none());
+    assert!(score("xyz", "res.partner").is_none());
+    assert!(score("res.partners", "res.partner").is_none());
+}
+
+#[test]
+fn test_fuzzy_score_ordering() {
+    // An exact match is preferred to a longer candidate
+    assert!(score("sale", "sale").unwrap() > score("sale", "sale_stock").unwrap());
+    // Matches at the start of segments are preferred
+    assert!(score("sp", "sale_purchase").unwrap() > score("sp", "dispatch").unwrap());
+    // Consecutive matches are preferred
+    assert!(score("part", "res.partner").unwrap() > score("part", "res.pos_art").unwrap());
+    // With the same matches, shorter candidates are preferred
+    assert!(score("res", "res.users").unwrap() > score("res", "res.partner").unwrap());
+}
@@ -0,0 +1,41 @@
+use odoo_ls_server::core::xml_id_usages::{XmlIdOccurrence, XmlIdUsages};
+
+fn summary(occurrences: &[XmlIdOccurrence]) -> Vec<(&str, usize, usize, bool)> {
+    occurrences.iter().map(|o| (o.xml_id.as_str(), o.range.start, o.range.end, o.is_declaration)).collect()
+}
+
+#[test]
+fn test_xml_ids_in_eval() {
+    let mut occurrences = vec![];
+    let raw = "[(4, ref('base.group_user')), (4, ref(\"group_x\")), env.ref('skipped'), ref(var)]";
+    XmlIdUsages::push_expression(&mut occurrences, "eval", raw, 100);
+    // Calls on other objects and arguments that are not string literals are ignored
+    assert_eq!(summary(&occurrences), vec![("base.group_user", 110, 125, false), ("group_x", 139, 146, false)]);
+    assert_eq!(occurrences[0].id_range(), 115..125);
+}
+
+#[test]
+fn test_xml_ids_in_other_attributes() {
+    let mut occurrences = vec![];
+    let raw = "{'default_id': %(module.rec)d, 'a': %(action_x)d}";
+    XmlIdUsages::push_expression(&mut occurrences, "context", raw, 0);
+    assert_eq!(summary(&occurrences), vec![("module.rec", 17, 27, false), ("action_x", 38, 46, false)]);
+    // ref() is only read in eval attributes
+    let mut occurrences = vec![];
+    XmlIdUsages::push_expression(&mut occurrences, "context", "{'a': ref('module.rec')}", 0);
+    assert!(occurrences.is_empty());
+}
+
+#[test]
+fn test_xml_ids_in_csv() {
+    let content = "id,name,group_id:id,implied_ids/id\nrec_1,Name,base.group_user,\"base.group_a,-group_b\"\n";
+    let occurrences = XmlIdUsages::collect_csv(content);
+    assert_eq!(summary(&occurrences), vec![
+        ("rec_1", 35, 40, true),
+        ("base.group_user", 46, 61, false),
+        ("base.group_a", 63, 75, false),
+        ("group_b", 77, 84, false),
+    ]);
+    // Without xml_id columns, nothing is collected
+    assert!(XmlIdUsages::collect_csv("name,value\nrec_1,base.group_user\n").is_empty());
+}
@@ -0,0 +1,90 @@
+// Test the diagnostics of the data files of module_3 in the test addons.
+
+use lsp_types::NumberOrString;
+use odoo_ls_server::threads::SessionInfo;
+use odoo_ls_server::utils::PathSanitizer;
+use std::env;
+use std::path::PathBuf;
+
+mod setup;
+
+/* Codes and lines of the diagnostics of a data file of module_3 */
+fn get_diagnostics(session: &mut SessionInfo, file_name: &str) -> Vec<(String, u32)> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("data").join("addons").join("module_3").join("data").join(file_name).sanitize();
+    let file_info = session.sync_odoo.get_file_mgr().borrow().get_file_info(&path).expect("data file not loaded");
+    let (_, diagnostics) = file_info.borrow_mut().pull_diagnostics(session);
+    diagnostics.into_iter().filter_map(|d| match d.code {
+        Some(NumberOrString::String(code)) => Some((code, d.range.start.line)),
+        _ => None,
+    }).collect()
+}
+
+#[test]
+fn test_menuitem_parent() {
+    let mut odoo = setup::setup::setup_server(true);
+    let mut session = setup::setup::create_session(&mut odoo);
+    let diagnostics = get_diagnostics(&mut session, "menus.xml");
+    // The parent of menu_wrong_parent exists, but is a res.partner record
+    assert!(diagnostics.contains(&("OLS05075".to_string(), 7)), "{:?}", diagnostics);
+    assert_eq!(diagnostics.iter().filter(|(code, _)| code == "OLS05075").count(), 1, "{:?}", diagnostics);
+    assert!(!diagnostics.iter().any(|(code, _)| code == "OLS05052"), "{:?}", diagnostics);
+}
+
+#[test]
+fn test_nested_root_nodes() {
+    let mut odoo = setup::setup::setup_server(true);
+    let mut session = setup::setup::create_session(&mut odoo);
+    // an openerp node in a data node of an odoo node is valid
+    let diagnostics = get_diagnostics(&mut session, "menus.xml");
+    assert!(!diagnostics.iter().any(|(code, _)| code == "OLS05005"), "{:?}", diagnostics);
+}
+
+#[test]
+fn test_missing_required_fields() {
+    let mut odoo = setup::setup::setup_server(true);
+    let mut session = setup::setup::create_session(&mut odoo);
+    // code has a default, and the last record updates an existing xml_id
+    let diagnostics = get_diagnostics(&mut session, "records.xml");
+    let missing = diagnostics.iter().filter(|(code, _)| code == "OLS05071").collect::<Vec<_>>();
+    assert_eq!(missing, vec![&("OLS05071".to_string(), 5)], "{:?}", diagnostics);
+    // empty cells are not set
+    let diagnostics = get_diagnostics(&mut session, "pygls.tests.xml_test_model.csv");
+    let missing = diagnostics.iter().filter(|(code, _)| code == "OLS05071").collect::<Vec<_>>();
+    assert_eq!(missing, vec![&("OLS05071".to_string(), 2)], "{:?}", diagnostics);
+}
+
+#[test]
+fn test_python_expressions() {
+    let mut odoo = setup::setup::setup_server(true);
+    let mut session = setup::setup::create_session(&mut odoo);
+    let diagnostics = get_diagnostics(&mut session, "views.xml");
+    let mut expression_diagnostics = diagnostics.iter().filter(|(code, _)| ["OLS05072", "OLS05073", "OLS05074"].contains(&code.as_str())).collect::<Vec<_>>();
+    expression_diagnostics.sort_by_key(|(_, line)| *line);
+    // fields of the model, names of the web client and builtins are known
+    assert_eq!(expression_diagnostics, vec![
+        &("OLS05073".to_string(), 7),
+        &("OLS05072".to_string(), 8),
+        &("OLS05074".to_string(), 15),
+    ], "{:?}", diagnostics);
+}
+
+#[test]
+fn test_number_field_contents() {
+    let mut odoo = setup::setup::setup_server(true);
+    let mut session = setup::setup::create_session(&mut odoo);
+    // without type attribute, the content is checked against the type of the python field
+    let diagnostics = get_diagnostics(&mut session, "records.xml");
+    let mut number_diagnostics = diagnostics.iter().filter(|(code, _)| code == "OLS05018" || code == "OLS05019").collect::<Vec<_>>();
+    number_diagnostics.sort_by_key(|(_, line)| *line);
+    assert_eq!(number_diagnostics, vec![&("OLS05018".to_string(), 13), &("OLS05019".to_string(), 14)], "{:?}", diagnostics);
+}
+
+#[test]
+fn test_delete_unknown_id() {
+    let mut odoo = setup::setup::setup_server(true);
+    let mut session = setup::setup::create_session(&mut odoo);
+    let diagnostics = get_diagnostics(&mut session, "records.xml");
+    // reported on the id attribute, not on the delete node that starts a line above
+    let unknown = diagnostics.iter().filter(|(code, _)| code == "OLS05067").collect::<Vec<_>>();
+    assert_eq!(unknown, vec![&("OLS05067".to_string(), 27)], "{:?}", diagnostics);
+}
@@ -0,0 +1,36 @@
+use odoo_ls_server::core::xpath::check_xpath_syntax;
+
+#[test]
+fn test_valid_xpath() {
+    for expr in [
+        "/form/sheet",
+        ".//field",
+        "//field[@name='partner_id']",
+        "//field[@name=\"partner_id\"][1]",
+        "//*[@id='main']",
+        "//field[@name='a' and (@invisible='1' or not(@readonly))]",
+        "//div[hasclass('o_title')]/h1",
+        "//field/@name",
+        "//text()",
+        "ancestor-or-self::div[last()]",
+        "count(//field) > 2 * 3",
+        "//field[position() mod 2 = 0] | //button",
+        "$node/..",
+        "/",
+    ] {
+        assert_eq!(check_xpath_syntax(expr), Ok(()), "{}", expr);
+    }
+}
+
+#[test]
+fn test_invalid_xpath() {
+    assert_eq!(check_xpath_syntax(""), Err("empty expression".to_string()));
+    assert_eq!(check_xpath_syntax("//field[@name='x]"), Err("unclosed string".to_string()));
+    assert_eq!(check_xpath_syntax("//field[]"), Err("empty predicate".to_string()));
+    assert_eq!(check_xpath_syntax("//field/"), Err("missing node test at the end of the expression".to_string()));
+    assert_eq!(check_xpath_syntax("//field[@name=]"), Err("missing node test before ']'".to_string()));
+    assert_eq!(check_xpath_syntax("//field[@name='a'"), Err("expected ']' at the end of the expression".to_string()));
+    assert_eq!(check_xpath_syntax("//field]"), Err("unexpected ']'".to_string()));
+    assert_eq!(check_xpath_syntax("sibling::div"), Err("unknown axis 'sibling'".to_string()));
+    assert_eq!(check_xpath_syntax("//field#"), Err("unexpected character '#'".to_string()));
+}